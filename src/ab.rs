@@ -0,0 +1,137 @@
+use std::env;
+use std::error::Error;
+use chrono::Utc;
+use rusqlite::{Connection, params};
+use crate::ai_client::{self, Analysis, AnalysisProvider};
+use crate::budget;
+use crate::i18n;
+use crate::output::{self, Outputs};
+use crate::prompt_generator::PromptTemplate;
+use crate::renderer;
+use crate::report::TechnicalReport;
+
+/// Default location of the A/B comparison database
+const DEFAULT_AB_DB: &str = "ab_results.db";
+
+/// One side of an --ab run: a prompt version and the models it is sent to
+pub struct Variant {
+    pub tag: String,               // As given on the command line, e.g. "brief" or "default@openai"
+    pub version: String,
+    template: PromptTemplate,
+    providers: Vec<Box<dyn AnalysisProvider>>,
+}
+
+/// The two sides of --ab, each `version` or `version@provider` (e.g. `default,brief` or `default@anthropic,default@openai`).
+/// Sides without a provider use AI_PROVIDER.
+pub fn parse_variants(spec: &str, load_template: impl Fn(&str) -> Result<PromptTemplate, Box<dyn Error>>) -> Result<Vec<Variant>, Box<dyn Error>> {
+    let sides: Vec<&str> = spec.split(',').map(str::trim).filter(|side| !side.is_empty()).collect();
+    if sides.len() != 2 {
+        return Err("--ab needs two variants, e.g. --ab default,brief or --ab default@anthropic,default@openai".into());
+    }
+
+    sides.into_iter().map(|side| {
+        let (version, provider) = match side.split_once('@') {
+            Some((version, provider)) => (version, Some(provider)),
+            None => (side, None),
+        };
+        let version = if version.is_empty() { "default" } else { version };
+        Ok(Variant {
+            tag: side.to_string(),
+            version: version.to_string(),
+            template: load_template(version)?,
+            providers: match provider {
+                Some(provider) => vec![ai_client::provider_by_name(provider)?],
+                None => ai_client::providers_from_env()?,
+            },
+        })
+    }).collect()
+}
+
+/// Path of the A/B database, from AB_RESULTS_DB (default ab_results.db)
+fn ab_path() -> String {
+    env::var("AB_RESULTS_DB").unwrap_or_else(|_| DEFAULT_AB_DB.to_string())
+}
+
+/// Open the A/B database, creating its table on first use
+fn open() -> Result<Connection, Box<dyn Error>> {
+    let connection = Connection::open(ab_path())?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS ab_results (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_at TEXT NOT NULL,
+            variant TEXT NOT NULL,
+            prompt_version TEXT NOT NULL,
+            providers TEXT NOT NULL,
+            price REAL NOT NULL,
+            action TEXT,
+            confidence REAL,
+            analysis TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(connection)
+}
+
+/// Store one side's output, tagged with its prompt version; rows from the same run share `run_at`
+fn record(connection: &Connection, run_at: &str, variant: &Variant, price: f64, analysis: &Analysis) -> Result<(), Box<dyn Error>> {
+    let providers: Vec<&str> = variant.providers.iter().map(|provider| provider.name()).collect();
+    let confidence = analysis.result.as_ref().map(|result| result.confidence)
+        .or_else(|| analysis.recommendation.as_ref().map(|recommendation| recommendation.confidence));
+    connection.execute(
+        "INSERT INTO ab_results (run_at, variant, prompt_version, providers, price, action, confidence, analysis)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![run_at, variant.tag, variant.version, providers.join(","), price,
+            analysis.action().map(|action| action.as_str()), confidence, analysis.text],
+    )?;
+    Ok(())
+}
+
+/// Run the same data through both variants, sending and storing each output with its tag.
+/// `extra_data` is appended to the rendered report (e.g. the previous analyses section).
+pub async fn run_ab(variants: Vec<Variant>, report: &mut TechnicalReport, extra_data: &str, previous_analysis: Option<String>,
+                    outputs: &Outputs) -> Result<(), Box<dyn Error>> {
+    let variants: Vec<Variant> = variants.into_iter()
+        .map(|variant| match &previous_analysis {
+            Some(previous) => Variant { template: variant.template.with_previous_analysis(previous.clone()), ..variant },
+            None => variant,
+        })
+        .collect();
+
+    // Trim for each prompt in turn, then render both from the final report so they see exactly the same data
+    let budget = budget::PromptBudget::from_env();
+    for variant in &variants {
+        let (_, trimmed) = budget::fit_prompt(report, &budget, |report| {
+            variant.template.render(&format!("{}{}", renderer::render_text(report), extra_data))
+        });
+        for part in &trimmed {
+            log::warn!("trimmed {} to fit the {} token prompt budget", part, budget.tokens);
+        }
+    }
+
+    let connection = open()?;
+    let run_at = Utc::now().to_rfc3339();
+    let price = report.last_price();
+    for variant in &variants {
+        let prompt = variant.template.render(&format!("{}{}", renderer::render_text(report), extra_data));
+        log::info!("Prompt version {}: ~{} tokens", variant.tag, budget::estimate_tokens(&prompt));
+
+        match ai_client::get_analysis(&variant.providers, &prompt, None, false).await {
+            Ok(analysis) => {
+                record(&connection, &run_at, variant, price, &analysis)?;
+
+                let mut text = format!("=== {} ===\n{}", i18n::tf("PROMPT VERSION {version}", &[("version", &variant.tag)]), analysis.text);
+                if let Some(result) = &analysis.result {
+                    text.push_str(&format!("\n\n{}", renderer::render_analysis_result(result, report.currency)));
+                }
+                if let Some(recommendation) = &analysis.recommendation {
+                    text.push_str(&format!("\n\n{}", renderer::render_recommendation(recommendation, report.currency)));
+                }
+                let delivery = output::Delivery { text: &text, symbol: "BTCUSDT", report: Some(report), analysis: Some(&analysis), chart: None, candles: &[] };
+                outputs.send(&delivery).await?;
+            }
+            Err(e) => log::warn!("Analysis with prompt version {} failed: {}", variant.tag, e),
+        }
+    }
+    log::info!("A/B results stored in {}", ab_path());
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use chrono::{TimeDelta, Utc};
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use crate::data_fetcher;
+use crate::history::{self, Run};
+use crate::renderer;
+
+/// Confidence ranges the calls are grouped into for calibration, as lower bounds
+const CONFIDENCE_BUCKETS: [f64; 4] = [0.0, 50.0, 70.0, 85.0];
+
+/// Settings for scoring calls, from ACCURACY_* environment variables
+pub struct AccuracyConfig {
+    pub horizon_hours: u32,     // How long after a call its price is checked
+    pub hold_band_pct: f64,     // A HOLD is right when price moved less than this
+    pub days: i64,              // Only calls made within this many days are scored
+}
+
+impl AccuracyConfig {
+    pub fn from_env() -> Self {
+        AccuracyConfig {
+            horizon_hours: env::var("ACCURACY_HORIZON_HOURS").ok().and_then(|value| value.trim().parse().ok()).unwrap_or(24),
+            hold_band_pct: env::var("ACCURACY_HOLD_BAND_PCT").ok().and_then(|value| value.trim().parse().ok()).unwrap_or(2.0),
+            days: 30,
+        }
+    }
+}
+
+/// How the calls of one model and prompt version played out
+#[derive(Serialize)]
+pub struct Scorecard {
+    pub providers: String,
+    pub prompt_version: String,
+    pub calls: usize,
+    pub correct: usize,
+    pub accuracy: f64,                      // Percent of calls whose direction played out
+    pub average_move: f64,                  // Average price move in the called direction, in percent
+    pub target_error: Option<f64>,          // Average distance of the first target from the realized price, in percent
+    pub calibration: Vec<CalibrationBucket>,
+}
+
+/// Stated confidence against the hit rate, for calls in one confidence range
+#[derive(Serialize)]
+pub struct CalibrationBucket {
+    pub range: String,
+    pub calls: usize,
+    pub average_confidence: f64,
+    pub accuracy: f64,
+}
+
+/// A call checked against the price after the horizon
+struct Evaluation {
+    run: Run,
+    realized_price: f64,        // In USD(T), as fetched
+}
+
+impl Evaluation {
+    /// The price move since the call, in percent, in US dollars whatever currency the run was priced in
+    fn change(&self) -> f64 {
+        (self.realized_price - self.run.usd_price()) / self.run.usd_price() * 100.0
+    }
+
+    fn correct(&self, config: &AccuracyConfig) -> bool {
+        match self.run.action.as_deref() {
+            Some("BUY") => self.change() > 0.0,
+            Some("SELL") => self.change() < 0.0,
+            _ => self.change().abs() < config.hold_band_pct,
+        }
+    }
+
+    /// The move in the direction of the call; a HOLD counts the move either way against it
+    fn directional_move(&self) -> f64 {
+        match self.run.action.as_deref() {
+            Some("BUY") => self.change(),
+            Some("SELL") => -self.change(),
+            _ => -self.change().abs(),
+        }
+    }
+
+    /// The stated confidence, from the structured result or the recommendation block
+    fn confidence(&self) -> Option<f64> {
+        [&self.run.result, &self.run.recommendation].into_iter()
+            .find_map(|value| value.as_ref()?["confidence"].as_f64())
+    }
+
+    /// The first price target, from the structured result or the recommendation block, in US dollars
+    fn target(&self) -> Option<f64> {
+        self.run.result.as_ref().and_then(|result| result["take_profits"][0].as_f64())
+            .or_else(|| self.run.recommendation.as_ref().and_then(|recommendation| recommendation["targets"][0].as_f64()))
+            .map(|target| target / self.run.usd_rate)
+    }
+}
+
+/// Create the table of checked prices in the history database
+fn create_table(connection: &Connection) -> Result<(), Box<dyn Error>> {
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS evaluations (
+            run_id INTEGER NOT NULL REFERENCES runs(id),
+            horizon_hours INTEGER NOT NULL,
+            realized_price REAL NOT NULL,
+            evaluated_at TEXT NOT NULL,
+            PRIMARY KEY (run_id, horizon_hours)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Look up the price after the horizon for every call old enough that hasn't been checked yet
+async fn evaluate_due(connection: &Connection, api_base_url: &str, config: &AccuracyConfig) -> Result<(), Box<dyn Error>> {
+    let due_before = Utc::now() - TimeDelta::hours(config.horizon_hours as i64);
+    let ids: Vec<i64> = {
+        let mut statement = connection.prepare(
+            "SELECT id FROM runs WHERE action IS NOT NULL AND run_at <= ?1
+             AND id NOT IN (SELECT run_id FROM evaluations WHERE horizon_hours = ?2) ORDER BY id")?;
+        let rows = statement.query_map(params![due_before, config.horizon_hours], |row| row.get(0))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    if !ids.is_empty() {
+        log::info!("Checking {} calls against the price {} hours later...", ids.len(), config.horizon_hours);
+    }
+
+    for id in ids {
+        let Some(run) = history::get(connection, id)? else { continue };
+        let checked_at = run.run_at + TimeDelta::hours(config.horizon_hours as i64);
+        match data_fetcher::fetch_price_at(api_base_url, &run.symbol, checked_at).await {
+            Ok(Some(price)) => {
+                connection.execute(
+                    "INSERT INTO evaluations (run_id, horizon_hours, realized_price, evaluated_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![id, config.horizon_hours, price, Utc::now()],
+                )?;
+            }
+            Ok(None) => log::warn!("No {} price at {} for run #{}", run.symbol, checked_at.format("%Y-%m-%d %H:%M"), id),
+            // Keep going, the call is retried next time
+            Err(e) => log::warn!("Could not check run #{}: {}", id, e),
+        }
+    }
+    Ok(())
+}
+
+/// The checked calls made within the scoring window
+fn evaluations(connection: &Connection, config: &AccuracyConfig) -> Result<Vec<Evaluation>, Box<dyn Error>> {
+    let since = Utc::now() - TimeDelta::days(config.days);
+    let checked: Vec<(i64, f64)> = {
+        let mut statement = connection.prepare(
+            "SELECT evaluations.run_id, evaluations.realized_price FROM evaluations JOIN runs ON runs.id = evaluations.run_id
+             WHERE evaluations.horizon_hours = ?1 AND runs.run_at >= ?2 ORDER BY evaluations.run_id")?;
+        let rows = statement.query_map(params![config.horizon_hours, since], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut evaluations = Vec::new();
+    for (id, realized_price) in checked {
+        if let Some(run) = history::get(connection, id)?
+            && run.price > 0.0 {
+            evaluations.push(Evaluation { run, realized_price });
+        }
+    }
+    Ok(evaluations)
+}
+
+/// Accuracy, average move, target error and calibration for each model and prompt version
+fn score(evaluations: &[Evaluation], config: &AccuracyConfig) -> Vec<Scorecard> {
+    let mut groups: BTreeMap<(&str, &str), Vec<&Evaluation>> = BTreeMap::new();
+    for evaluation in evaluations {
+        groups.entry((evaluation.run.providers.as_str(), evaluation.run.prompt_version.as_str())).or_default().push(evaluation);
+    }
+    let average = |values: &[f64]| if values.is_empty() { None } else { Some(values.iter().sum::<f64>() / values.len() as f64) };
+    let hit_rate = |calls: &[&Evaluation]| {
+        calls.iter().filter(|evaluation| evaluation.correct(config)).count() as f64 / calls.len().max(1) as f64 * 100.0
+    };
+
+    groups.into_iter().map(|((providers, prompt_version), calls)| {
+        let moves: Vec<f64> = calls.iter().map(|evaluation| evaluation.directional_move()).collect();
+        let target_errors: Vec<f64> = calls.iter()
+            .filter_map(|evaluation| Some((evaluation.target()? - evaluation.realized_price).abs() / evaluation.realized_price * 100.0))
+            .collect();
+
+        let calibration = CONFIDENCE_BUCKETS.iter().enumerate().filter_map(|(index, low)| {
+            let high = CONFIDENCE_BUCKETS.get(index + 1).copied().unwrap_or(f64::INFINITY);
+            let bucket: Vec<&Evaluation> = calls.iter().copied()
+                .filter(|evaluation| evaluation.confidence().is_some_and(|confidence| confidence >= *low && confidence < high))
+                .collect();
+            let confidences: Vec<f64> = bucket.iter().filter_map(|evaluation| evaluation.confidence()).collect();
+            Some(CalibrationBucket {
+                range: if high.is_finite() { format!("{:.0}-{:.0}%", low, high) } else { format!("{:.0}%+", low) },
+                calls: bucket.len(),
+                average_confidence: average(&confidences)?,
+                accuracy: hit_rate(&bucket),
+            })
+        }).collect();
+
+        Scorecard {
+            providers: providers.to_string(),
+            prompt_version: prompt_version.to_string(),
+            calls: calls.len(),
+            correct: calls.iter().filter(|evaluation| evaluation.correct(config)).count(),
+            accuracy: hit_rate(&calls),
+            average_move: average(&moves).unwrap_or(0.0),
+            target_error: average(&target_errors),
+            calibration,
+        }
+    }).collect()
+}
+
+/// Check the calls in the run history whose horizon has passed, then print the scorecards
+pub async fn run_accuracy(api_base_url: &str, config: &AccuracyConfig, json_only: bool) -> Result<(), Box<dyn Error>> {
+    let connection = history::open()?;
+    create_table(&connection)?;
+    evaluate_due(&connection, api_base_url, config).await?;
+    let scorecards = score(&evaluations(&connection, config)?, config);
+
+    if json_only {
+        println!("{}", serde_json::to_string_pretty(&scorecards)?);
+    } else {
+        println!("{}", renderer::render_accuracy(&scorecards, config));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::currency::Currency;
+
+    fn config() -> AccuracyConfig {
+        AccuracyConfig { horizon_hours: 24, hold_band_pct: 2.0, days: 30 }
+    }
+
+    fn evaluation(action: &str, price: f64, currency: Currency, usd_rate: f64, realized_price: f64) -> Evaluation {
+        Evaluation {
+            run: Run {
+                id: 1,
+                run_at: Utc::now(),
+                symbol: "BTCUSDT".to_string(),
+                interval: "1d".to_string(),
+                prompt_version: "v1".to_string(),
+                providers: "openai".to_string(),
+                prompt_hash: String::new(),
+                price,
+                currency,
+                usd_rate,
+                action: Some(action.to_string()),
+                result: Some(serde_json::json!({ "confidence": 80.0, "take_profits": [110000.0 * usd_rate] })),
+                recommendation: None,
+                report: serde_json::Value::Null,
+                analysis: String::new(),
+            },
+            realized_price,
+        }
+    }
+
+    #[test]
+    fn calls_are_scored_by_direction() {
+        let config = config();
+        assert!(evaluation("BUY", 100000.0, Currency::USD, 1.0, 105000.0).correct(&config));
+        assert!(!evaluation("SELL", 100000.0, Currency::USD, 1.0, 105000.0).correct(&config));
+        assert!(evaluation("HOLD", 100000.0, Currency::USD, 1.0, 101000.0).correct(&config));
+        assert!(!evaluation("HOLD", 100000.0, Currency::USD, 1.0, 103000.0).correct(&config));
+        assert_eq!(evaluation("HOLD", 100000.0, Currency::USD, 1.0, 97000.0).directional_move(), -3.0);
+    }
+
+    #[test]
+    fn runs_priced_in_another_currency_are_compared_in_dollars() {
+        let eur = Currency::named("EUR").unwrap();
+        // 92,000 EUR at 0.92 EUR per dollar is 100,000 USD, so 102,000 USDT later is a 2% rise
+        let evaluation = evaluation("BUY", 92000.0, eur, 0.92, 102000.0);
+        assert!((evaluation.change() - 2.0).abs() < 1e-9);
+        assert!((evaluation.target().unwrap() - 110000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn scorecards_group_by_model_and_prompt() {
+        let config = config();
+        let evaluations = [
+            evaluation("BUY", 100000.0, Currency::USD, 1.0, 110000.0),
+            evaluation("SELL", 100000.0, Currency::USD, 1.0, 110000.0),
+        ];
+        let scorecards = score(&evaluations, &config);
+        assert_eq!(scorecards.len(), 1);
+        let scorecard = &scorecards[0];
+        assert_eq!((scorecard.calls, scorecard.correct), (2, 1));
+        assert_eq!(scorecard.accuracy, 50.0);
+        assert_eq!(scorecard.average_move, 0.0);
+        assert_eq!(scorecard.target_error, Some(0.0));
+        let bucket = scorecard.calibration.iter().find(|bucket| bucket.range == "70-85%").unwrap();
+        assert_eq!((bucket.calls, bucket.average_confidence, bucket.accuracy), (2, 80.0, 50.0));
+    }
+}
@@ -0,0 +1,344 @@
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::env;
+use std::error::Error;
+use std::time::Duration;
+use base64::Engine;
+use crate::chart::Chart;
+use super::AnalysisProvider;
+use super::structured::{self, AnalysisResult};
+
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+const DEFAULT_API_VERSION: &str = "2023-06-01";
+
+/// Asks the model to record its conclusions with the structured output tool after the prose report
+const SYSTEM_PROMPT: &str = "After writing the full report, call the record_analysis tool with the key conclusions of your overall recommendation.";
+
+// Structure for Anthropic API requests
+#[derive(Debug, Serialize)]
+pub struct AnthropicRequest {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Message {
+    role: String,
+    content: Vec<Content>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Content {
+    Text { text: String },
+    Image { source: ImageSource },
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImageSource {
+    #[serde(rename = "type")]
+    source_type: String,
+    media_type: String,
+    data: String,
+}
+
+// Structure for Anthropic API responses
+#[derive(Debug, Deserialize)]
+struct AnthropicResponse {
+    content: Vec<ResponseContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseContent {
+    #[serde(rename = "type")]
+    content_type: String,
+    text: Option<String>,
+    input: Option<Value>,      // Arguments of a tool_use block
+}
+
+/// Anthropic Claude Messages API
+pub struct AnthropicProvider {
+    api_key: String,
+    base_url: String,          // Overridable for gateways and proxies (e.g. LiteLLM)
+    api_version: String,       // anthropic-version header
+    max_retries: u32,          // Retries after rate limiting (429) or overload (529)
+    batch_poll: Duration,      // Wait between Message Batches status checks
+}
+
+impl AnthropicProvider {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let api_key = env::var("ANTHROPIC_API_KEY")
+            .map_err(|_| "ANTHROPIC_API_KEY must be set in the .env file")?;
+        let max_retries = env::var("ANTHROPIC_MAX_RETRIES").ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(3);
+        let batch_poll_seconds = env::var("ANTHROPIC_BATCH_POLL_SECONDS").ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(30);
+        Ok(AnthropicProvider {
+            api_key,
+            base_url: env::var("ANTHROPIC_BASE_URL").map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+            api_version: env::var("ANTHROPIC_VERSION").unwrap_or_else(|_| DEFAULT_API_VERSION.to_string()),
+            max_retries,
+            batch_poll: Duration::from_secs(batch_poll_seconds),
+        })
+    }
+}
+
+impl AnthropicProvider {
+    fn headers(&self) -> Result<HeaderMap, Box<dyn Error>> {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_str(&self.api_key)?);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert("anthropic-version", HeaderValue::from_str(&self.api_version)?);
+        Ok(headers)
+    }
+
+    /// Messages API request for the prompt and optional chart image, optionally as a server-sent event stream
+    fn request(&self, prompt: &str, chart: Option<&Chart>, stream: bool) -> Result<reqwest::RequestBuilder, Box<dyn Error>> {
+        Ok(super::http_client(stream)
+            .post(format!("{}/v1/messages", self.base_url))
+            .headers(self.headers()?)
+            .json(&request_body(prompt, chart, stream)))
+    }
+
+    /// Send the request, backing off and retrying while the API is rate limited or overloaded
+    async fn send(&self, prompt: &str, chart: Option<&Chart>, stream: bool) -> Result<reqwest::Response, Box<dyn Error>> {
+        let mut attempt = 0;
+        loop {
+            let request = self.request(prompt, chart, stream)?;
+            // The streaming client has no overall timeout, so bound the wait for the response headers here
+            let response = tokio::time::timeout(super::request_timeout(), request.send()).await
+                .map_err(|_| format!("anthropic did not respond within {}s; raise AI_TIMEOUT_SECONDS if the model needs longer",
+                    super::request_timeout().as_secs()))??;
+
+            let status = response.status().as_u16();
+            if (status != 429 && status != 529) || attempt >= self.max_retries {
+                return Ok(response);
+            }
+
+            // Honour Retry-After when given, otherwise back off exponentially (2s, 4s, 8s, ... up to a minute)
+            let delay = response.headers().get("retry-after")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .unwrap_or_else(|| 2u64.saturating_pow(attempt + 1).min(60));
+            attempt += 1;
+            log::warn!("Anthropic API returned {}, retrying in {}s ({}/{})", status, delay, attempt, self.max_retries);
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl AnalysisProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn check(&self) -> Result<(), Box<dyn Error>> {
+        let headers = self.headers()?;
+        super::check_request(super::http_client(false).get(format!("{}/v1/models", self.base_url)).headers(headers)).await
+    }
+
+    async fn analyze(&self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        Ok(self.analyze_structured(prompt, None, None).await?.0)
+    }
+
+    async fn analyze_streaming(&self, prompt: &str, on_text: &mut (dyn for<'t> FnMut(&'t str) + Send)) -> Result<String, Box<dyn Error>> {
+        Ok(self.analyze_structured(prompt, None, Some(on_text)).await?.0)
+    }
+
+    async fn analyze_structured(&self, prompt: &str, chart: Option<&Chart>, on_text: Option<&mut (dyn for<'t> FnMut(&'t str) + Send)>)
+        -> Result<(String, Option<AnalysisResult>), Box<dyn Error>> {
+        let Some(on_text) = on_text else {
+            let response = self.send(prompt, chart, false).await?;
+            return parse_structured_response(response).await;
+        };
+
+        let mut response = self.send(prompt, chart, true).await?;
+        if !response.status().is_success() {
+            return Err(format!("API request failed with status: {}", response.status()).into());
+        }
+
+        // Server-sent events arrive in arbitrary chunks; only parse complete lines
+        let mut text = String::new();
+        let mut tool_input = String::new();
+        let mut buffer: Vec<u8> = Vec::new();
+        let idle_timeout = super::request_timeout();
+        loop {
+            // A stalled stream keeps what has arrived so far rather than discarding it
+            let chunk = match tokio::time::timeout(idle_timeout, response.chunk()).await {
+                Ok(chunk) => chunk?,
+                Err(_) if !text.is_empty() => {
+                    eprintln!();    // End the streamed line
+                    log::warn!("no data from anthropic for {}s, keeping the partial response", idle_timeout.as_secs());
+                    text.push_str(&format!("\n\n[Response cut off: no data received for {}s]", idle_timeout.as_secs()));
+                    break;
+                }
+                Err(_) => return Err(format!("anthropic did not respond within {}s; raise AI_TIMEOUT_SECONDS if the model needs longer",
+                    idle_timeout.as_secs()).into()),
+            };
+            let Some(chunk) = chunk else {
+                break;
+            };
+
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let Some(data) = std::str::from_utf8(&line).ok().and_then(|line| line.trim().strip_prefix("data:")) else {
+                    continue;
+                };
+                let Ok(event) = serde_json::from_str::<Value>(data.trim()) else {
+                    continue;
+                };
+
+                match event["type"].as_str() {
+                    Some("content_block_delta") => {
+                        // Text is shown as it arrives; the tool call's JSON arrives in fragments
+                        if let Some(delta) = event["delta"]["text"].as_str() {
+                            on_text(delta);
+                            text.push_str(delta);
+                        } else if let Some(partial) = event["delta"]["partial_json"].as_str() {
+                            tool_input.push_str(partial);
+                        }
+                    }
+                    Some("error") => return Err(format!("Stream error: {}", event["error"]["message"]).into()),
+                    _ => {}
+                }
+            }
+        }
+
+        if text.is_empty() {
+            return Err("No content in the response".into());
+        }
+        Ok((text, serde_json::from_str(&tool_input).ok()))
+    }
+
+    /// Submit every prompt as one Message Batch (half the price of individual requests) and poll until it has ended
+    async fn analyze_batch(&self, prompts: &[String]) -> Result<Vec<Result<(String, Option<AnalysisResult>), String>>, Box<dyn Error>> {
+        let client = super::http_client(false);
+        let requests: Vec<Value> = prompts.iter().enumerate()
+            .map(|(index, prompt)| json!({ "custom_id": format!("prompt-{}", index), "params": request_body(prompt, None, false) }))
+            .collect();
+
+        let headers = self.headers()?;
+        let batches_url = format!("{}/v1/messages/batches", self.base_url);
+        let response = client.post(&batches_url).headers(headers).json(&json!({ "requests": requests })).send().await?;
+        if !response.status().is_success() {
+            return Err(format!("Batch submission failed with status: {}", response.status()).into());
+        }
+        let batch: Value = response.json().await?;
+        let id = batch["id"].as_str().ok_or("No batch id in the response")?.to_string();
+        log::info!("Submitted batch {} with {} prompts", id, prompts.len());
+
+        // Batches usually finish within minutes but may take up to a day
+        let results_url = loop {
+            tokio::time::sleep(self.batch_poll).await;
+            let headers = self.headers()?;
+            let status: Value = client.get(format!("{}/{}", batches_url, id)).headers(headers).send().await?.json().await?;
+            if status["processing_status"] == "ended" {
+                break status["results_url"].as_str().ok_or("No results URL for the ended batch")?.to_string();
+            }
+            log::info!("Batch {} is {} ({} of {} requests processing)", id,
+                status["processing_status"].as_str().unwrap_or("pending"), status["request_counts"]["processing"], prompts.len());
+        };
+
+        // Results are JSON lines in no particular order, matched back to prompts by custom_id
+        let headers = self.headers()?;
+        let body = client.get(&results_url).headers(headers).send().await?.text().await?;
+        let mut results: Vec<Result<(String, Option<AnalysisResult>), String>> =
+            prompts.iter().map(|_| Err("No result returned for this request".to_string())).collect();
+        for line in body.lines().filter(|line| !line.trim().is_empty()) {
+            let entry: Value = serde_json::from_str(line)?;
+            let Some(index) = entry["custom_id"].as_str()
+                .and_then(|id| id.strip_prefix("prompt-"))
+                .and_then(|index| index.parse::<usize>().ok())
+                .filter(|index| *index < results.len()) else {
+                continue;
+            };
+
+            results[index] = match entry["result"]["type"].as_str() {
+                Some("succeeded") => serde_json::from_value::<AnthropicResponse>(entry["result"]["message"].clone())
+                    .map_err(|e| e.to_string())
+                    .and_then(collect_content),
+                Some(other) => Err(format!("Batch request {}: {}", other, entry["result"]["error"])),
+                None => Err("Malformed batch result".to_string()),
+            };
+        }
+        Ok(results)
+    }
+}
+
+/// Messages API request body for the prompt, with the chart image first when given
+fn request_body(prompt: &str, chart: Option<&Chart>, stream: bool) -> AnthropicRequest {
+    // The chart goes before the prompt, with a caption explaining what it shows
+    let mut content = Vec::new();
+    if let Some(chart) = chart {
+        content.push(Content::Image {
+            source: ImageSource {
+                source_type: "base64".to_string(),
+                media_type: "image/png".to_string(),
+                data: base64::engine::general_purpose::STANDARD.encode(&chart.png),
+            },
+        });
+        content.push(Content::Text { text: chart.caption.clone() });
+    }
+    content.push(Content::Text { text: prompt.to_string() });
+
+    // Prepare the request body - increase max_tokens to 4096 (Claude's maximum)
+    AnthropicRequest {
+        model: "claude-opus-4-20250514".to_string(),
+        max_tokens: 4096,
+        messages: vec![Message {
+            role: "user".to_string(),
+            content,
+        }],
+        system: Some(SYSTEM_PROMPT.to_string()),
+        tools: vec![structured::tool_definition()],
+        stream,
+    }
+}
+
+/// Text of a Messages API response (also returned by Bedrock)
+pub(super) async fn parse_messages_response(response: reqwest::Response) -> Result<String, Box<dyn Error>> {
+    Ok(parse_structured_response(response).await?.0)
+}
+
+/// Text blocks of a Messages API response, plus the structured result if the model called the tool
+async fn parse_structured_response(response: reqwest::Response) -> Result<(String, Option<AnalysisResult>), Box<dyn Error>> {
+    if !response.status().is_success() {
+        return Err(format!("API request failed with status: {}", response.status()).into());
+    }
+
+    let response_data: AnthropicResponse = response.json().await?;
+    Ok(collect_content(response_data)?)
+}
+
+/// Join the text blocks and pick up the record_analysis tool input
+fn collect_content(response: AnthropicResponse) -> Result<(String, Option<AnalysisResult>), String> {
+    let mut text = String::new();
+    let mut result = None;
+    for content in response.content {
+        match content.content_type.as_str() {
+            "text" => text.push_str(content.text.as_deref().unwrap_or_default()),
+            "tool_use" => result = content.input.and_then(|input| serde_json::from_value(input).ok()),
+            _ => {}
+        }
+    }
+
+    if text.is_empty() {
+        Err("No content in the response".to_string())
+    } else {
+        Ok((text, result))
+    }
+}
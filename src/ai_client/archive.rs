@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use super::AnalysisResult;
+
+/// One model request as it was sent and answered
+#[derive(Serialize)]
+struct ArchivedRequest<'a> {
+    time: DateTime<Utc>,
+    provider: &'a str,
+    prompt_hash: String,            // The same SHA-256 the run history stores
+    prompt: &'a str,
+    chart: bool,                    // Whether the chart image was attached
+    duration_ms: u128,
+    response: Option<&'a str>,      // The model's reply before the analysis is extracted from it
+    result: Option<&'a AnalysisResult>,
+    error: Option<String>,
+}
+
+/// Every model request written to a directory, one JSON file each, for auditing and prompt debugging
+pub struct RequestArchive {
+    dir: PathBuf,
+}
+
+impl RequestArchive {
+    /// Enabled by AI_ARCHIVE_DIR (off by default)
+    pub fn from_env() -> Option<Self> {
+        let dir = env::var("AI_ARCHIVE_DIR").ok().filter(|dir| !dir.trim().is_empty())?;
+        Some(RequestArchive { dir: PathBuf::from(dir) })
+    }
+
+    /// Store a request with its raw response, or the message of the error it failed with
+    pub fn put(&self, provider: &str, prompt: &str, chart: bool, duration_ms: u128,
+               outcome: Result<&(String, Option<AnalysisResult>), String>) -> Result<PathBuf, Box<dyn Error>> {
+        fs::create_dir_all(&self.dir)?;
+        let time = Utc::now();
+        let prompt_hash = hex::encode(Sha256::digest(prompt));
+        let (response, result, error) = match outcome {
+            Ok((response, result)) => (Some(response.as_str()), result.as_ref(), None),
+            Err(error) => (None, None, Some(error)),
+        };
+        let archived = ArchivedRequest { time, provider, prompt_hash, prompt, chart, duration_ms, response, result, error };
+
+        // Sorts by time; the hash keeps requests made in the same millisecond apart
+        let path = self.dir.join(format!("{}-{}-{}.json", time.format("%Y%m%dT%H%M%S%.3fZ"), provider, &archived.prompt_hash[..12]));
+        fs::write(&path, serde_json::to_string_pretty(&archived)?)?;
+        Ok(path)
+    }
+}
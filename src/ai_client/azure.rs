@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::env;
+use std::error::Error;
+use super::AnalysisProvider;
+use super::openai::parse_chat_response;
+
+/// Default API version when AZURE_OPENAI_API_VERSION is not set
+const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+/// Azure OpenAI deployment, addressed by resource endpoint and deployment name
+pub struct AzureOpenAiProvider {
+    endpoint: String,
+    deployment: String,
+    api_version: String,
+    api_key: String,
+}
+
+impl AzureOpenAiProvider {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let required = |name: &str| env::var(name)
+            .map_err(|_| format!("{} must be set when AI_PROVIDER=azure", name));
+
+        Ok(AzureOpenAiProvider {
+            endpoint: required("AZURE_OPENAI_ENDPOINT")?,
+            deployment: required("AZURE_OPENAI_DEPLOYMENT")?,
+            api_version: env::var("AZURE_OPENAI_API_VERSION").unwrap_or_else(|_| DEFAULT_API_VERSION.to_string()),
+            api_key: required("AZURE_OPENAI_API_KEY")?,
+        })
+    }
+}
+
+#[async_trait]
+impl AnalysisProvider for AzureOpenAiProvider {
+    fn name(&self) -> &str {
+        "azure"
+    }
+
+    async fn analyze(&self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        // The deployment determines the model, so the body carries no model name
+        let url = format!("{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.endpoint.trim_end_matches('/'), self.deployment, self.api_version);
+        let request_body = json!({
+            "max_tokens": 4096,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+
+        let response = super::http_client(false)
+            .post(&url)
+            .header("api-key", &self.api_key)
+            .json(&request_body)
+            .send()
+            .await?;
+
+        parse_chat_response(response).await
+    }
+}
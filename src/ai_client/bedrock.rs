@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::error::Error;
+use super::AnalysisProvider;
+use super::anthropic::parse_messages_response;
+
+/// Default Claude model (cross-region inference profile) when BEDROCK_MODEL_ID is not set
+const DEFAULT_MODEL_ID: &str = "us.anthropic.claude-opus-4-20250514-v1:0";
+
+/// Claude on AWS Bedrock, authenticated with IAM credentials via SigV4
+pub struct BedrockProvider {
+    region: String,
+    model_id: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl BedrockProvider {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let required = |name: &str| env::var(name)
+            .map_err(|_| format!("{} must be set when AI_PROVIDER=bedrock", name));
+
+        Ok(BedrockProvider {
+            region: env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            model_id: env::var("BEDROCK_MODEL_ID").unwrap_or_else(|_| DEFAULT_MODEL_ID.to_string()),
+            access_key_id: required("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: required("AWS_SECRET_ACCESS_KEY")?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    /// SigV4 Authorization header for a POST to `path` with the given headers and body
+    fn authorization(&self, path: &str, headers: &[(&str, &str)], body: &[u8], amz_date: &str) -> Result<String, Box<dyn Error>> {
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/bedrock/aws4_request", date, self.region);
+
+        // Headers must be signed in lowercase, sorted order
+        let mut headers: Vec<(String, &str)> = headers.iter()
+            .map(|(name, value)| (name.to_lowercase(), value.trim()))
+            .collect();
+        headers.sort();
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+        let signed_headers = headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+
+        // Every path segment is encoded once more on top of its encoding in the URL
+        let canonical_uri = path.split('/')
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let canonical_request = format!("POST\n{}\n\n{}\n{}\n{}",
+            canonical_uri, canonical_headers, signed_headers, hex::encode(Sha256::digest(body)));
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, scope, hex::encode(Sha256::digest(canonical_request.as_bytes())));
+
+        let mut key = hmac(format!("AWS4{}", self.secret_access_key).as_bytes(), date.as_bytes())?;
+        for part in [self.region.as_str(), "bedrock", "aws4_request"] {
+            key = hmac(&key, part.as_bytes())?;
+        }
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes())?);
+
+        Ok(format!("AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature))
+    }
+}
+
+#[async_trait]
+impl AnalysisProvider for BedrockProvider {
+    fn name(&self) -> &str {
+        "bedrock"
+    }
+
+    async fn analyze(&self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        let host = format!("bedrock-runtime.{}.amazonaws.com", self.region);
+        let path = format!("/model/{}/invoke", urlencoding::encode(&self.model_id));
+        let body = serde_json::to_vec(&json!({
+            "anthropic_version": "bedrock-2023-05-31",
+            "max_tokens": 4096,
+            "messages": [{ "role": "user", "content": [{ "type": "text", "text": prompt }] }],
+        }))?;
+
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let mut headers = vec![
+            ("content-type", "application/json"),
+            ("host", host.as_str()),
+            ("x-amz-date", amz_date.as_str()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.as_str()));
+        }
+        let authorization = self.authorization(&path, &headers, &body, &amz_date)?;
+
+        let mut request = super::http_client(false)
+            .post(format!("https://{}{}", host, path))
+            .header("authorization", authorization)
+            .body(body);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+
+        parse_messages_response(request.send().await?).await
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
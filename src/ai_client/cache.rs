@@ -0,0 +1,89 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use crate::chart::Chart;
+use super::{Analysis, AnalysisProvider, AnalysisResult, Recommendation};
+
+/// Default directory for cached analyses
+const DEFAULT_CACHE_DIR: &str = "analysis_cache";
+
+#[derive(Serialize, Deserialize)]
+struct CachedAnalysis {
+    created_at: DateTime<Utc>,
+    text: String,
+    result: Option<AnalysisResult>,
+    #[serde(default)]
+    recommendation: Option<Recommendation>,
+}
+
+/// Analyses stored on disk by prompt hash, so identical requests within the TTL aren't billed again
+pub struct AnalysisCache {
+    dir: PathBuf,
+    ttl: TimeDelta,
+}
+
+impl AnalysisCache {
+    /// Enabled by AI_CACHE_TTL_MINUTES (default 0, off); entries live in AI_CACHE_DIR (default analysis_cache)
+    pub fn from_env() -> Option<Self> {
+        let minutes: i64 = env::var("AI_CACHE_TTL_MINUTES").ok()
+            .and_then(|value| value.trim().parse().ok())
+            .filter(|minutes| *minutes > 0)?;
+        Some(AnalysisCache {
+            dir: PathBuf::from(env::var("AI_CACHE_DIR").unwrap_or_else(|_| DEFAULT_CACHE_DIR.to_string())),
+            ttl: TimeDelta::minutes(minutes),
+        })
+    }
+
+    /// Hash of everything that determines the response: the backends, the prompt and any chart image
+    pub fn key(providers: &[Box<dyn AnalysisProvider>], prompt: &str, chart: Option<&Chart>) -> String {
+        let mut hasher = Sha256::new();
+        for provider in providers {
+            hasher.update(provider.name());
+            hasher.update([0]);
+        }
+        hasher.update(prompt);
+        if let Some(chart) = chart {
+            hasher.update(&chart.png);
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// A cached analysis younger than the TTL; unreadable entries count as misses
+    pub fn get(&self, key: &str) -> Option<Analysis> {
+        let cached: CachedAnalysis = serde_json::from_str(&fs::read_to_string(self.path(key)).ok()?).ok()?;
+        if Utc::now() - cached.created_at > self.ttl {
+            return None;
+        }
+        Some(Analysis { text: cached.text, result: cached.result, recommendation: cached.recommendation })
+    }
+
+    /// Store an analysis and remove entries that have expired
+    pub fn put(&self, key: &str, analysis: &Analysis) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.dir)?;
+        let cached = CachedAnalysis {
+            created_at: Utc::now(),
+            text: analysis.text.clone(),
+            result: analysis.result.clone(),
+            recommendation: analysis.recommendation.clone(),
+        };
+        fs::write(self.path(key), serde_json::to_string_pretty(&cached)?)?;
+
+        for entry in fs::read_dir(&self.dir)?.flatten() {
+            let expired = fs::read_to_string(entry.path()).ok()
+                .and_then(|contents| serde_json::from_str::<CachedAnalysis>(&contents).ok())
+                .is_some_and(|cached| Utc::now() - cached.created_at > self.ttl);
+            if expired {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
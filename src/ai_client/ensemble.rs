@@ -0,0 +1,87 @@
+use futures::future::join_all;
+use std::error::Error;
+use crate::chart::Chart;
+use crate::renderer;
+use crate::signals::{self, Action};
+use super::structured::{self, AnalysisResult, Recommendation};
+use super::{Analysis, AnalysisProvider, extract_bitcoin_market_analysis, last_data_points_section, request_validated};
+
+/// One model's call in an ensemble run
+pub struct ModelCall {
+    pub provider: String,
+    pub action: Option<Action>,
+    pub result: Option<AnalysisResult>,
+    pub recommendation: Option<Recommendation>,
+    pub error: Option<String>,     // Set when the model could not be queried
+}
+
+/// The models' calls and the recommendation most of them agree on
+pub struct Consensus {
+    pub calls: Vec<ModelCall>,
+    pub action: Option<Action>,    // None when the vote is tied
+    pub votes: usize,              // Models recommending `action`
+}
+
+/// Query every provider in parallel with the same prompt and lead with where they agree and disagree
+pub async fn get_ensemble_analysis(providers: &[Box<dyn AnalysisProvider>], prompt: &str, chart: Option<&Chart>) -> Result<Analysis, Box<dyn Error>> {
+    let names: Vec<&str> = providers.iter().map(|provider| provider.name()).collect();
+    log::info!("Requesting analysis from {} in parallel...", names.join(", "));
+    let responses = join_all(providers.iter().map(|provider| request_validated(provider.as_ref(), prompt, chart, false))).await;
+
+    let mut calls = Vec::new();
+    let mut analyses = Vec::new();
+    for (name, response) in names.into_iter().zip(responses) {
+        match response {
+            Ok((response, result)) => {
+                let market_analysis = extract_bitcoin_market_analysis(&response);
+                let recommendation = structured::parse_recommendation(&response);
+                let action = result.as_ref().map(|result| result.recommendation)
+                    .or_else(|| recommendation.as_ref().map(|recommendation| recommendation.direction.action()))
+                    .or_else(|| signals::parse_llm_action(&market_analysis));
+                calls.push(ModelCall { provider: name.to_string(), action, result, recommendation, error: None });
+                analyses.push((name, market_analysis));
+            }
+            Err(e) => {
+                // One failing backend shouldn't sink the others' analyses
+                log::warn!("Analysis from {} failed: {}", name, e);
+                calls.push(ModelCall { provider: name.to_string(), action: None, result: None, recommendation: None, error: Some(e.to_string()) });
+            }
+        }
+    }
+    if analyses.is_empty() {
+        return Err("Every model in the ensemble failed".into());
+    }
+
+    let consensus = consensus(calls);
+    let mut text = last_data_points_section(prompt);
+    text.push_str(&renderer::render_consensus(&consensus));
+    for (name, market_analysis) in analyses {
+        text.push_str(&format!("\n=== BITCOIN MARKET ANALYSIS ({}) ===\n{}\n", name, market_analysis));
+    }
+
+    // Carry forward structured conclusions from models that voted with the majority
+    let majority: Vec<ModelCall> = consensus.calls.into_iter()
+        .filter(|call| consensus.action.is_some() && call.action == consensus.action)
+        .collect();
+    let result = majority.iter().find_map(|call| call.result.clone());
+    let recommendation = majority.into_iter().find_map(|call| call.recommendation);
+
+    Ok(Analysis { text, result, recommendation })
+}
+
+/// Majority vote over the models that made a call
+fn consensus(calls: Vec<ModelCall>) -> Consensus {
+    let count = |action: Action| calls.iter().filter(|call| call.action == Some(action)).count();
+    let mut tally: Vec<(Action, usize)> = [Action::Buy, Action::Sell, Action::Hold].into_iter()
+        .map(|action| (action, count(action)))
+        .collect();
+    tally.sort_by_key(|(_, votes)| std::cmp::Reverse(*votes));
+
+    let (action, votes) = tally[0];
+    let tied = tally[1].1 == votes;
+    Consensus {
+        action: if votes == 0 || tied { None } else { Some(action) },
+        votes,
+        calls,
+    }
+}
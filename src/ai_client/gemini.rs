@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::env;
+use std::error::Error;
+use super::AnalysisProvider;
+
+/// Default model when GEMINI_MODEL is not set
+const DEFAULT_MODEL: &str = "gemini-2.5-pro";
+
+// Structure for generateContent responses
+#[derive(Debug, Deserialize)]
+struct GenerateResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Candidate {
+    content: Option<CandidateContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CandidateContent {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Part {
+    text: Option<String>,
+}
+
+/// Google Gemini generateContent API
+pub struct GeminiProvider {
+    api_key: String,
+    model: String,
+}
+
+impl GeminiProvider {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let api_key = env::var("GEMINI_API_KEY")
+            .map_err(|_| "GEMINI_API_KEY must be set when AI_PROVIDER=gemini")?;
+        let model = env::var("GEMINI_MODEL").unwrap_or_else(|_| DEFAULT_MODEL.to_string());
+        Ok(GeminiProvider { api_key, model })
+    }
+}
+
+#[async_trait]
+impl AnalysisProvider for GeminiProvider {
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
+    async fn check(&self) -> Result<(), Box<dyn Error>> {
+        let url = format!("https://generativelanguage.googleapis.com/v1beta/models/{}", self.model);
+        super::check_request(super::http_client(false).get(&url).header("x-goog-api-key", &self.api_key)).await
+    }
+
+    async fn analyze(&self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        let url = format!("https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent", self.model);
+        let request_body = json!({
+            "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+            "generationConfig": { "maxOutputTokens": 4096 },
+        });
+
+        let response = super::http_client(false)
+            .post(&url)
+            .header("x-goog-api-key", &self.api_key)
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(format!("API request failed with status: {}", response.status()).into());
+        }
+
+        // The answer may be split over several text parts
+        let response_data: GenerateResponse = response.json().await?;
+        let text: String = response_data.candidates.into_iter().next()
+            .and_then(|candidate| candidate.content)
+            .map(|content| content.parts.into_iter().filter_map(|part| part.text).collect())
+            .unwrap_or_default();
+
+        if text.is_empty() {
+            Err("No content in the response".into())
+        } else {
+            Ok(text)
+        }
+    }
+}
@@ -0,0 +1,440 @@
+mod anthropic;
+mod archive;
+mod azure;
+mod bedrock;
+mod cache;
+mod ensemble;
+mod gemini;
+mod ollama;
+mod openai;
+mod structured;
+
+use async_trait::async_trait;
+use std::env;
+use std::error::Error;
+use std::time::{Duration, Instant};
+use crate::chart::Chart;
+use crate::signals::{self, Action};
+
+pub use anthropic::AnthropicProvider;
+pub use azure::AzureOpenAiProvider;
+pub use bedrock::BedrockProvider;
+pub use ensemble::Consensus;
+pub use gemini::GeminiProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+pub use structured::{AnalysisResult, Direction, Outlook, Recommendation};
+
+/// A model backend that turns the analysis prompt into the model's raw response
+#[async_trait]
+pub trait AnalysisProvider: Send + Sync {
+    /// Short name used in logs and configuration (e.g. "anthropic")
+    fn name(&self) -> &str;
+
+    async fn analyze(&self, prompt: &str) -> Result<String, Box<dyn Error>>;
+
+    /// Like `analyze`, but passes text to `on_text` as it is generated; backends without streaming emit it all at once
+    async fn analyze_streaming(&self, prompt: &str, on_text: &mut (dyn for<'t> FnMut(&'t str) + Send)) -> Result<String, Box<dyn Error>> {
+        let response = self.analyze(prompt).await?;
+        on_text(&response);
+        Ok(response)
+    }
+
+    /// Prose plus the structured conclusions; backends without tool use only return the prose.
+    /// Backends with vision also get the `chart` image. With `on_text`, text is passed on as it is generated.
+    async fn analyze_structured(&self, prompt: &str, _chart: Option<&Chart>, on_text: Option<&mut (dyn for<'t> FnMut(&'t str) + Send)>)
+        -> Result<(String, Option<AnalysisResult>), Box<dyn Error>> {
+        let text = match on_text {
+            Some(on_text) => self.analyze_streaming(prompt, on_text).await?,
+            None => self.analyze(prompt).await?,
+        };
+        Ok((text, None))
+    }
+
+    /// Check that the API is reachable and accepts the credentials, without generating anything;
+    /// backends without a cheap endpoint for this are only checked for their settings, when they are built
+    async fn check(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Analyze independent prompts, each result in prompt order; backends with a batch API submit them together at lower cost
+    async fn analyze_batch(&self, prompts: &[String]) -> Result<Vec<Result<(String, Option<AnalysisResult>), String>>, Box<dyn Error>> {
+        let mut results = Vec::new();
+        for prompt in prompts {
+            results.push(self.analyze_structured(prompt, None, None).await.map_err(|e| e.to_string()));
+        }
+        Ok(results)
+    }
+}
+
+/// The AI_PROVIDER names
+pub const PROVIDERS: [&str; 7] = ["anthropic", "openai", "openai-compatible", "gemini", "ollama", "azure", "bedrock"];
+
+/// Limit for establishing a connection to a model API
+const CONNECT_TIMEOUT_SECS: u64 = 30;
+
+/// Limit for a whole `check` request
+const CHECK_TIMEOUT_SECS: u64 = 10;
+
+/// How long to wait for a model's response, from AI_TIMEOUT_SECONDS (default 300)
+fn request_timeout() -> Duration {
+    Duration::from_secs(env::var("AI_TIMEOUT_SECONDS").ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(300))
+}
+
+/// Send a request made by `check`, failing on an error status
+async fn check_request(request: reqwest::RequestBuilder) -> Result<(), Box<dyn Error>> {
+    let response = request.timeout(Duration::from_secs(CHECK_TIMEOUT_SECS)).send().await?;
+    if !response.status().is_success() {
+        return Err(format!("API check failed with status: {}", response.status()).into());
+    }
+    Ok(())
+}
+
+/// HTTP client for model requests. Streamed responses can legitimately take longer than the timeout,
+/// so their client only bounds the connection and the backend bounds the wait for each chunk instead.
+fn http_client(stream: bool) -> reqwest::Client {
+    let builder = reqwest::Client::builder().connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS));
+    let builder = if stream { builder } else { builder.timeout(request_timeout()) };
+    builder.build().unwrap_or_default()
+}
+
+/// Sections a prompt can ask for; those it names (as "Section:") are checked case-insensitively in the reply
+const REQUIRED_SECTIONS: [&str; 9] = [
+    "Market Overview",
+    "Price Prediction",
+    "Long and Short Positions",
+    "Key Levels",
+    "Indicator Analysis",
+    "Risk Assessment",
+    "Overall Recommendation",
+    "Position Advice",
+    "Answer to Your Question",
+];
+
+/// Formatted analysis text, with the structured conclusions when the backend provided them
+/// and the recommendation block when the model included one
+pub struct Analysis {
+    pub text: String,
+    pub result: Option<AnalysisResult>,
+    pub recommendation: Option<Recommendation>,
+}
+
+impl Analysis {
+    /// The recommended action, preferring the structured result and recommendation block over parsing the prose
+    pub fn action(&self) -> Option<Action> {
+        self.result.as_ref().map(|result| result.recommendation)
+            .or_else(|| self.recommendation.as_ref().map(|recommendation| recommendation.direction.action()))
+            .or_else(|| signals::parse_llm_action(&self.text))
+    }
+
+    /// The market view from the structured result or recommendation block
+    pub fn outlook(&self) -> Option<Outlook> {
+        self.result.as_ref().map(|result| result.outlook)
+            .or_else(|| self.recommendation.as_ref().map(|recommendation| recommendation.direction.outlook()))
+    }
+}
+
+/// Build the providers listed in AI_PROVIDER (default anthropic); several comma-separated names form an ensemble
+pub fn providers_from_env() -> Result<Vec<Box<dyn AnalysisProvider>>, Box<dyn Error>> {
+    providers_from_list(&env::var("AI_PROVIDER").unwrap_or_else(|_| "anthropic".to_string()))
+}
+
+/// Providers named in a comma-separated list, as in AI_PROVIDER
+pub fn providers_from_list(providers: &str) -> Result<Vec<Box<dyn AnalysisProvider>>, Box<dyn Error>> {
+    providers.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(provider_by_name)
+        .collect()
+}
+
+/// Build one provider by its AI_PROVIDER name
+pub fn provider_by_name(provider: &str) -> Result<Box<dyn AnalysisProvider>, Box<dyn Error>> {
+    match provider {
+        "anthropic" => Ok(Box::new(AnthropicProvider::from_env()?)),
+        "openai" => Ok(Box::new(OpenAiProvider::from_env()?)),
+        "openai-compatible" => Ok(Box::new(OpenAiProvider::compatible_from_env()?)),
+        "gemini" => Ok(Box::new(GeminiProvider::from_env()?)),
+        "ollama" => Ok(Box::new(OllamaProvider::from_env())),
+        "azure" => Ok(Box::new(AzureOpenAiProvider::from_env()?)),
+        "bedrock" => Ok(Box::new(BedrockProvider::from_env()?)),
+        other => Err(format!("Unknown AI_PROVIDER '{}', expected {}", other, PROVIDERS.join(", ")).into()),
+    }
+}
+
+/// Get the market analysis, prefixed with the latest data points, attaching `chart` where supported.
+/// Several providers are queried in parallel and their analyses combined with a consensus section.
+/// With `stream`, a single provider's raw response is echoed to stderr as it arrives so long generations show progress.
+pub async fn get_analysis(providers: &[Box<dyn AnalysisProvider>], prompt: &str, chart: Option<&Chart>, stream: bool) -> Result<Analysis, Box<dyn Error>> {
+    // Re-running with unchanged data reuses the earlier response instead of billing the API again
+    let cache = cache::AnalysisCache::from_env();
+    let key = cache::AnalysisCache::key(providers, prompt, chart);
+    if let Some(analysis) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+        log::info!("Using the cached analysis for this prompt");
+        return Ok(analysis);
+    }
+
+    let analysis = fetch_analysis(providers, prompt, chart, stream).await?;
+    if let Some(cache) = &cache
+        && let Err(e) = cache.put(&key, &analysis) {
+        log::warn!("could not cache the analysis: {}", e);
+    }
+    Ok(analysis)
+}
+
+async fn fetch_analysis(providers: &[Box<dyn AnalysisProvider>], prompt: &str, chart: Option<&Chart>, stream: bool) -> Result<Analysis, Box<dyn Error>> {
+    let provider = match providers {
+        [provider] => provider.as_ref(),
+        [] => return Err("No analysis provider configured".into()),
+        _ => return ensemble::get_ensemble_analysis(providers, prompt, chart).await,
+    };
+
+    log::info!("Requesting analysis from {}...", provider.name());
+    let (response, result) = request_validated(provider, prompt, chart, stream).await?;
+    let recommendation = structured::parse_recommendation(&response);
+
+    // Extract the market analysis from the response if it contains <bitcoin_market_analysis> tags
+    let market_analysis = extract_bitcoin_market_analysis(&response);
+    
+    // Combine the model response with the last 3 data points
+    let mut final_response = last_data_points_section(prompt);
+    
+    // Add the model response (either the structured analysis or the full response)
+    final_response.push_str("=== BITCOIN MARKET ANALYSIS ===\n");
+    final_response.push_str(&market_analysis);
+    
+    Ok(Analysis { text: final_response, result, recommendation })
+}
+
+/// Analyses for several prompts from one provider, in prompt order; a failed prompt doesn't fail the others
+pub async fn get_batch_analyses(provider: &dyn AnalysisProvider, prompts: &[String]) -> Result<Vec<Result<Analysis, String>>, Box<dyn Error>> {
+    log::info!("Requesting {} analyses from {}...", prompts.len(), provider.name());
+    let started = Instant::now();
+    let responses = provider.analyze_batch(prompts).await?;
+    if let Some(archive) = archive::RequestArchive::from_env() {
+        for (prompt, response) in prompts.iter().zip(&responses) {
+            let outcome = response.as_ref().map_err(String::clone);
+            if let Err(e) = archive.put(provider.name(), prompt, false, started.elapsed().as_millis(), outcome) {
+                log::warn!("could not archive the {} request: {}", provider.name(), e);
+            }
+        }
+    }
+
+    Ok(prompts.iter().zip(responses)
+        .map(|(prompt, response)| response.map(|(response, result)| {
+            let missing = missing_sections(prompt, &response);
+            if !missing.is_empty() {
+                log::warn!("a batched analysis is missing {}", missing.join(", "));
+            }
+            let mut text = last_data_points_section(prompt);
+            text.push_str("=== BITCOIN MARKET ANALYSIS ===\n");
+            text.push_str(&extract_bitcoin_market_analysis(&response));
+            Analysis { text, result, recommendation: structured::parse_recommendation(&response) }
+        }))
+        .collect())
+}
+
+/// Raw response and structured result, re-prompting once if required sections are missing
+async fn request_validated(provider: &dyn AnalysisProvider, prompt: &str, chart: Option<&Chart>, stream: bool)
+    -> Result<(String, Option<AnalysisResult>), Box<dyn Error>> {
+    let (mut response, mut result) = request_analysis(provider, prompt, chart, stream).await?;
+
+    // Ask once more with a correction instead of passing a malformed reply on
+    let missing = missing_sections(prompt, &response);
+    if !missing.is_empty() {
+        log::warn!("Analysis from {} is missing {}; asking the model to correct it...", provider.name(), missing.join(", "));
+        let correction = format!("{}\n\nIMPORTANT: A previous reply to this request was incomplete; it was missing: {}. \
+            Reply again with the complete report inside <bitcoin_market_analysis> tags, including every numbered section.",
+            prompt, missing.join(", "));
+        (response, result) = request_analysis(provider, &correction, chart, stream).await?;
+
+        let still_missing = missing_sections(prompt, &response);
+        if !still_missing.is_empty() {
+            log::warn!("the corrected analysis is still missing {} from {}", still_missing.join(", "), provider.name());
+        }
+    }
+
+    Ok((response, result))
+}
+
+/// The last 3 data points from the prompt, as the opening section of the output
+fn last_data_points_section(prompt: &str) -> String {
+    format!("=== LAST 3 DATA POINTS ===\n{}\n\n", extract_last_3_data_points(prompt))
+}
+
+async fn request_analysis(provider: &dyn AnalysisProvider, prompt: &str, chart: Option<&Chart>, stream: bool)
+    -> Result<(String, Option<AnalysisResult>), Box<dyn Error>> {
+    let started = Instant::now();
+    let response = if stream {
+        let response = provider.analyze_structured(prompt, chart, Some(&mut |text: &str| eprint!("{}", text))).await;
+        eprintln!();
+        response
+    } else {
+        provider.analyze_structured(prompt, chart, None).await
+    };
+    let response = response.map_err(|e| describe_timeout(provider, e));
+
+    // Keep the exact request and reply when archiving is on, including failures and re-prompts
+    if let Some(archive) = archive::RequestArchive::from_env() {
+        match archive.put(provider.name(), prompt, chart.is_some(), started.elapsed().as_millis(),
+                          response.as_ref().map_err(|e| e.to_string())) {
+            Ok(path) => log::debug!("Archived the {} request to {}", provider.name(), path.display()),
+            Err(e) => log::warn!("could not archive the {} request: {}", provider.name(), e),
+        }
+    }
+    response
+}
+
+/// Replace reqwest's generic timeout error with one naming the backend and the setting to raise
+fn describe_timeout(provider: &dyn AnalysisProvider, e: Box<dyn Error>) -> Box<dyn Error> {
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(error) if error.is_timeout() => format!("{} did not respond within {}s; raise AI_TIMEOUT_SECONDS if the model needs longer",
+            provider.name(), request_timeout().as_secs()).into(),
+        _ => e,
+    }
+}
+
+/// Required parts absent from the reply: the analysis tags and any of the numbered sections the prompt asks for
+fn missing_sections(prompt: &str, response: &str) -> Vec<String> {
+    let mut missing = Vec::new();
+    if !response.contains("<bitcoin_market_analysis>") || !response.contains("</bitcoin_market_analysis>") {
+        missing.push("the <bitcoin_market_analysis> tags".to_string());
+    }
+
+    let analysis = extract_bitcoin_market_analysis(response).to_lowercase();
+    // A shorter template such as the --brief prompt asks for only some of the sections
+    for section in REQUIRED_SECTIONS.iter().filter(|section| prompt.contains(&format!("{}:", section))) {
+        if !analysis.contains(&section.to_lowercase()) {
+            missing.push(format!("the {} section", section));
+        }
+    }
+    missing
+}
+
+/// Extract the last 3 data points from the prompt
+fn extract_last_3_data_points(prompt: &str) -> String {
+    let mut last_3_lines = String::new();
+    
+    // Check for the new format with <historical_data> tags
+    if let Some(hist_start) = prompt.find("<historical_data>") {
+        let data_start = hist_start + "<historical_data>".len();
+        
+        if let Some(hist_end) = prompt[data_start..].find("</historical_data>") {
+            let historical_data = &prompt[data_start..(data_start + hist_end)].trim();
+            
+            // Parse the historical data section to find OHLCV data
+            let data_lines: Vec<&str> = historical_data
+                .lines()
+                // Only the field names: the sign and separators depend on --currency and --locale
+                .filter(|line| line.contains(": O=") || line.contains(": Price=") || line.contains(": C="))
+                .collect();
+            
+            last_3_lines = last_lines(&data_lines, 3);
+        }
+    } 
+    
+    // Fallback to the old format if the new format is not found
+    if last_3_lines.is_empty() {
+        if let Some(ohlc_start) = prompt.find("Bitcoin historical OHLC + Volume data") {
+            // Find where the data starts (usually after "Date,Open,High,Low,Close,Volume")
+            if let Some(data_start) = prompt[ohlc_start..].find("\n") {
+                let data_section = &prompt[(ohlc_start + data_start + 1)..];
+                
+                // Find where the data ends (usually before "=== TECHNICAL INDICATORS ===")
+                let data_end = if let Some(end_idx) = data_section.find("===") {
+                    end_idx
+                } else {
+                    data_section.len()
+                };
+                
+                let data_lines: Vec<&str> = data_section[..data_end]
+                    .trim()
+                    .lines()
+                    .collect();
+                
+                last_3_lines = last_lines(&data_lines, 3);
+            }
+        } else if let Some(price_start) = prompt.find("Bitcoin price data (timestamp, price in USD)")
+            && let Some(data_start) = prompt[price_start..].find("\n") {
+            let data_section = &prompt[(price_start + data_start + 1)..];
+            
+            // Find where the data ends
+            let data_end = if let Some(end_idx) = data_section.find("===") {
+                end_idx
+            } else {
+                data_section.len()
+            };
+            
+            let data_lines: Vec<&str> = data_section[..data_end]
+                .trim()
+                .lines()
+                .collect();
+            
+            last_3_lines = last_lines(&data_lines, 3);
+        }
+    }
+    
+    if last_3_lines.is_empty() {
+        "No data points found in the prompt.".to_string()
+    } else {
+        last_3_lines
+    }
+}
+
+/// The last `count` lines, each ending in a newline
+fn last_lines(lines: &[&str], count: usize) -> String {
+    lines[lines.len().saturating_sub(count)..].iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Extract the Bitcoin market analysis from the AI's response
+fn extract_bitcoin_market_analysis(response: &str) -> String {
+    // Look for content within <bitcoin_market_analysis> tags
+    if let Some(start_idx) = response.find("<bitcoin_market_analysis>") {
+        let content_start = start_idx + "<bitcoin_market_analysis>".len();
+        
+        if let Some(end_idx) = response[content_start..].find("</bitcoin_market_analysis>") {
+            // Return just the content within the tags
+            return response[content_start..(content_start + end_idx)].trim().to_string();
+        }
+    }
+    
+    // If no tags found or format is incorrect, return the full response
+    response.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_data_points_in_any_currency_and_locale() {
+        let prompt = "<historical_data>\n\
+            2025-05-01 00:00:00: O=$94,210.50 H=$95,000.00 L=$93,000.00 C=$94,500.00 V=1.00\n\
+            2025-05-02 00:00:00: O=87.500,00 € H=88.000,00 € L=86.000,00 € C=87.900,00 € V=1,00\n\
+            2025-05-03 00:00:00: O=£74,000.00 H=£75,000.00 L=£73,000.00 C=£74,500.00 V=1.00\n\
+            2025-05-04 00:00:00: Price=3\u{202f}400\u{202f}000,00 ฿\n\
+            </historical_data>";
+        let points = extract_last_3_data_points(prompt);
+        assert!(!points.contains("2025-05-01"));
+        assert!(points.contains("2025-05-02 00:00:00: O=87.500,00 €"));
+        assert!(points.contains("2025-05-03"));
+        assert!(points.contains("2025-05-04"));
+        assert_eq!(points.lines().count(), 3);
+    }
+
+    #[test]
+    fn last_data_points_with_the_sign_after_the_number() {
+        let prompt = "<historical_data>\n\
+            2025-05-01 00:00:00: O=94.210,50 $ H=95.000,00 $ L=93.000,00 $ C=94.500,00 $ V=1,00\n\
+            </historical_data>";
+        assert_eq!(extract_last_3_data_points(prompt),
+            "2025-05-01 00:00:00: O=94.210,50 $ H=95.000,00 $ L=93.000,00 $ C=94.500,00 $ V=1,00\n");
+    }
+
+    #[test]
+    fn no_data_points_without_rows() {
+        assert_eq!(extract_last_3_data_points("<historical_data>\n</historical_data>"), "No data points found in the prompt.");
+    }
+}
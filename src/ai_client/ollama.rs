@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::env;
+use std::error::Error;
+use super::AnalysisProvider;
+use super::openai::parse_chat_response;
+
+/// Ollama's default local endpoint; a llama.cpp server works too (usually on port 8080)
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// Default model when OLLAMA_MODEL is not set
+const DEFAULT_MODEL: &str = "llama3.1";
+
+/// Local model server speaking the OpenAI-compatible chat endpoint (Ollama, llama.cpp); no API key needed
+pub struct OllamaProvider {
+    base_url: String,
+    model: String,
+}
+
+impl OllamaProvider {
+    pub fn from_env() -> Self {
+        OllamaProvider {
+            base_url: env::var("OLLAMA_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+            model: env::var("OLLAMA_MODEL").unwrap_or_else(|_| DEFAULT_MODEL.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl AnalysisProvider for OllamaProvider {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    async fn check(&self) -> Result<(), Box<dyn Error>> {
+        let url = format!("{}/v1/models", self.base_url.trim_end_matches('/'));
+        super::check_request(super::http_client(false).get(&url)).await
+            .map_err(|e| format!("Could not reach the local model server at {}: {}", self.base_url, e).into())
+    }
+
+    async fn analyze(&self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        let url = format!("{}/v1/chat/completions", self.base_url.trim_end_matches('/'));
+        let request_body = json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "stream": false,
+        });
+
+        let response = super::http_client(false)
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| format!("Could not reach the local model server at {}: {}", self.base_url, e))?;
+
+        parse_chat_response(response).await
+    }
+}
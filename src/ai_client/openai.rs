@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::env;
+use std::error::Error;
+use super::AnalysisProvider;
+
+/// OpenAI's own API; OpenAI-compatible servers are configured with their base URL
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Default chat model when OPENAI_MODEL is not set
+const DEFAULT_MODEL: &str = "gpt-4o";
+
+// Structure for chat completions responses
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+/// OpenAI Chat Completions API, or any server that implements it (vLLM, LM Studio, OpenRouter, ...)
+pub struct OpenAiProvider {
+    name: &'static str,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl OpenAiProvider {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let api_key = env::var("OPENAI_API_KEY")
+            .map_err(|_| "OPENAI_API_KEY must be set when AI_PROVIDER=openai")?;
+        Ok(OpenAiProvider {
+            name: "openai",
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: Some(api_key),
+            model: env::var("OPENAI_MODEL").unwrap_or_else(|_| DEFAULT_MODEL.to_string()),
+        })
+    }
+
+    /// Any OpenAI-compatible server; the API key is optional since local servers often need none
+    pub fn compatible_from_env() -> Result<Self, Box<dyn Error>> {
+        let required = |name: &str| env::var(name)
+            .map_err(|_| format!("{} must be set when AI_PROVIDER=openai-compatible", name));
+
+        Ok(OpenAiProvider {
+            name: "openai-compatible",
+            base_url: required("OPENAI_COMPATIBLE_BASE_URL")?,
+            api_key: env::var("OPENAI_COMPATIBLE_API_KEY").ok(),
+            model: required("OPENAI_COMPATIBLE_MODEL")?,
+        })
+    }
+}
+
+#[async_trait]
+impl AnalysisProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn check(&self) -> Result<(), Box<dyn Error>> {
+        let mut request = super::http_client(false).get(format!("{}/models", self.base_url.trim_end_matches('/')));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        super::check_request(request).await
+    }
+
+    async fn analyze(&self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        let url = format!("{}/chat/completions", self.base_url.trim_end_matches('/'));
+        let request_body = json!({
+            "model": self.model,
+            "max_tokens": 4096,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+
+        let mut request = super::http_client(false).post(&url).json(&request_body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        parse_chat_response(request.send().await?).await
+    }
+}
+
+/// Text of the first choice of a chat completions response
+pub(super) async fn parse_chat_response(response: reqwest::Response) -> Result<String, Box<dyn Error>> {
+    if !response.status().is_success() {
+        return Err(format!("API request failed with status: {}", response.status()).into());
+    }
+
+    let response_data: ChatResponse = response.json().await?;
+    response_data.choices.into_iter().next()
+        .and_then(|choice| choice.message.content)
+        .ok_or_else(|| "No content in the response".into())
+}
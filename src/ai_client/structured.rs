@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::signals::Action;
+
+/// Name of the tool the model calls to record its conclusions
+pub const TOOL_NAME: &str = "record_analysis";
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outlook {
+    Bullish,
+    Bearish,
+    Neutral,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Long,
+    Short,
+    Flat,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl Outlook {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outlook::Bullish => "Bullish",
+            Outlook::Bearish => "Bearish",
+            Outlook::Neutral => "Neutral",
+        }
+    }
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Long => "Long",
+            Direction::Short => "Short",
+            Direction::Flat => "Flat",
+        }
+    }
+
+    /// The trade the direction calls for
+    pub fn action(&self) -> Action {
+        match self {
+            Direction::Long => Action::Buy,
+            Direction::Short => Action::Sell,
+            Direction::Flat => Action::Hold,
+        }
+    }
+
+    /// The market view the direction implies
+    pub fn outlook(&self) -> Outlook {
+        match self {
+            Direction::Long => Outlook::Bullish,
+            Direction::Short => Outlook::Bearish,
+            Direction::Flat => Outlook::Neutral,
+        }
+    }
+}
+
+impl RiskLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "Low",
+            RiskLevel::Medium => "Medium",
+            RiskLevel::High => "High",
+        }
+    }
+}
+
+/// Machine-readable conclusions of an analysis, so alerting and tracking don't have to parse the prose
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AnalysisResult {
+    pub outlook: Outlook,
+    pub confidence: f64,           // 0-100
+    pub recommendation: Action,
+    #[serde(default)]
+    pub entries: Vec<f64>,
+    #[serde(default)]
+    pub stop_losses: Vec<f64>,
+    #[serde(default)]
+    pub take_profits: Vec<f64>,
+    pub risk_level: RiskLevel,
+    #[serde(default)]
+    pub summary: String,
+}
+
+/// The fenced JSON block the prompt asks every model to end its reply with, so any backend yields a typed call
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub direction: Direction,
+    pub confidence: f64,           // 0-100
+    pub entry: Option<f64>,
+    pub stop: Option<f64>,
+    #[serde(default)]
+    pub targets: Vec<f64>,
+    #[serde(default)]
+    pub horizon: String,
+}
+
+/// The last ```json block in a reply; None when there is none or it doesn't match `Recommendation`
+pub fn parse_recommendation(response: &str) -> Option<Recommendation> {
+    let start = response.rfind("```json")? + "```json".len();
+    let end = response[start..].find("```")?;
+    match serde_json::from_str(response[start..start + end].trim()) {
+        Ok(recommendation) => Some(recommendation),
+        Err(e) => {
+            log::warn!("could not parse the recommendation block: {}", e);
+            None
+        }
+    }
+}
+
+/// Tool definition whose input schema mirrors `AnalysisResult`
+pub fn tool_definition() -> Value {
+    let prices = |description: &str| json!({
+        "type": "array",
+        "items": { "type": "number" },
+        "description": description,
+    });
+
+    json!({
+        "name": TOOL_NAME,
+        "description": "Record the key conclusions of the Bitcoin market analysis in structured form.",
+        "input_schema": {
+            "type": "object",
+            "properties": {
+                "outlook": { "type": "string", "enum": ["bullish", "bearish", "neutral"] },
+                "confidence": { "type": "number", "minimum": 0, "maximum": 100, "description": "Confidence in the outlook, 0-100" },
+                "recommendation": { "type": "string", "enum": ["buy", "sell", "hold"] },
+                "entries": prices("Suggested entry prices in USD"),
+                "stop_losses": prices("Suggested stop-loss prices in USD"),
+                "take_profits": prices("Suggested take-profit prices in USD"),
+                "risk_level": { "type": "string", "enum": ["low", "medium", "high"] },
+                "summary": { "type": "string", "description": "One or two sentence summary of the recommendation" },
+            },
+            "required": ["outlook", "confidence", "recommendation", "risk_level"],
+        },
+    })
+}
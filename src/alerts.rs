@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use crate::data_fetcher;
+use crate::output::Outputs;
+use crate::report::TechnicalReport;
+use crate::strategy::Condition;
+use crate::technical_analysis;
+
+/// Default location of the alert definitions
+const DEFAULT_ALERTS_FILE: &str = "alerts.toml";
+
+/// Default location of the persisted alert states
+const DEFAULT_STATE_FILE: &str = "alerts_state.json";
+
+/// Built-in indicator events: name, the state whose start marks the event, and the notification
+const EVENTS: [(&str, &str, &str); 4] = [
+    ("Golden cross", "sma50 > sma200", "SMA50 crossed above SMA200"),
+    ("Death cross", "sma50 < sma200", "SMA50 crossed below SMA200"),
+    ("MACD bullish crossover", "macd > macd_signal", "MACD crossed above its signal line"),
+    ("MACD bearish crossover", "macd < macd_signal", "MACD crossed below its signal line"),
+];
+
+#[derive(Deserialize)]
+struct AlertFile {
+    #[serde(default)]
+    alert: Vec<AlertConfig>,
+}
+
+#[derive(Deserialize)]
+struct AlertConfig {
+    name: String,
+    when: String,
+    message: Option<String>,
+}
+
+/// A user-defined alert: notify when its condition starts holding
+pub struct Alert {
+    pub name: String,
+    pub condition: String,
+    pub message: Option<String>,
+    rule: Condition,
+}
+
+/// Whether each alert's condition held on the previous check and when it last fired, persisted between runs
+#[derive(Default, Serialize, Deserialize)]
+pub struct AlertState {
+    pub active: HashMap<String, bool>,
+    #[serde(default)]
+    pub fired: HashMap<String, DateTime<Utc>>,
+}
+
+impl AlertState {
+    fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        if !Path::new(path).exists() {
+            return Ok(AlertState::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record that an alert triggered; false if it already fired within the cooldown and should be suppressed
+    fn fire(&mut self, key: &str, now: DateTime<Utc>, cooldown: TimeDelta) -> bool {
+        if self.fired.get(key).is_some_and(|last| now - *last < cooldown) {
+            return false;
+        }
+        self.fired.insert(key.to_string(), now);
+        true
+    }
+}
+
+/// Load alerts from ALERTS_FILE (default alerts.toml); a missing file means no alerts
+pub fn load_alerts() -> Result<Vec<Alert>, Box<dyn Error>> {
+    let path = env::var("ALERTS_FILE").unwrap_or_else(|_| DEFAULT_ALERTS_FILE.to_string());
+    if !Path::new(&path).exists() {
+        return Ok(Vec::new());
+    }
+
+    let file: AlertFile = toml::from_str(&fs::read_to_string(&path)?)
+        .map_err(|e| format!("Invalid alerts file {}: {}", path, e))?;
+
+    file.alert.into_iter()
+        .map(|config| {
+            let rule = Condition::parse(&config.when)
+                .map_err(|e| format!("Alert '{}': {}", config.name, e))?;
+            Ok(Alert { name: config.name, condition: config.when, message: config.message, rule })
+        })
+        .collect()
+}
+
+/// Messages for alerts whose condition holds now but did not on the previous check (e.g. price crossing a level)
+pub fn check_alerts(alerts: &[Alert], report: &TechnicalReport, state: &mut AlertState, cooldown: TimeDelta) -> Vec<String> {
+    let mut messages = Vec::new();
+    let now = Utc::now();
+
+    for alert in alerts {
+        let active = alert.rule.matches(report);
+        let was_active = state.active.insert(alert.name.clone(), active).unwrap_or(false);
+        if active && !was_active && state.fire(&alert.name, now, cooldown) {
+            messages.push(format!("🔔 {}: {} (BTC {})",
+                alert.name, alert.message.as_deref().unwrap_or(&alert.condition), report.currency.price(report.last_price())));
+        }
+    }
+
+    messages
+}
+
+/// Messages for indicator events since the previous check; nothing fires until a previous state is known
+pub fn check_events(report: &TechnicalReport, state: &mut AlertState, squeeze_pct: f64, cooldown: TimeDelta) -> Vec<String> {
+    let mut messages = Vec::new();
+    let now = Utc::now();
+    let mut record = |key: &str, holds: bool, message: String| {
+        let key = format!("event:{}", key);
+        if state.active.insert(key.clone(), holds) == Some(false) && holds && state.fire(&key, now, cooldown) {
+            messages.push(format!("⚡ {} (BTC {})", message, report.currency.price(report.last_price())));
+        }
+    };
+
+    for (name, expression, message) in EVENTS {
+        if let Ok(condition) = Condition::parse(expression) {
+            record(name, condition.matches(report), message.to_string());
+        }
+    }
+
+    if let Some((holds, direction)) = squeeze_break(report, squeeze_pct) {
+        record("Bollinger squeeze break", holds,
+            format!("Price broke {} the Bollinger Bands after a squeeze (bandwidth < {}%)", direction, squeeze_pct));
+    }
+
+    messages
+}
+
+/// Whether price closed outside the bands right after a period of narrow bandwidth, and in which direction
+fn squeeze_break(report: &TechnicalReport, squeeze_pct: f64) -> Option<(bool, &'static str)> {
+    let bollinger = report.indicator("bollinger")?;
+    let (upper, lower) = (bollinger.latest("Upper Band")?, bollinger.latest("Lower Band")?);
+    let previous_width = (bollinger.previous("Upper Band")? - bollinger.previous("Lower Band")?)
+        / bollinger.previous("Middle Band (SMA)")? * 100.0;
+
+    let price = report.last_price();
+    let direction = if price > upper { "above" } else { "below" };
+    Some((previous_width < squeeze_pct && (price > upper || price < lower), direction))
+}
+
+/// ALERTS_STATE_FILE, default alerts_state.json
+fn state_file() -> String {
+    env::var("ALERTS_STATE_FILE").unwrap_or_else(|_| DEFAULT_STATE_FILE.to_string())
+}
+
+/// The persisted alert states, with when each alert last fired
+pub fn load_state() -> Result<AlertState, Box<dyn Error>> {
+    AlertState::load(&state_file())
+}
+
+/// Check alerts every ALERTS_INTERVAL_MINUTES (default 15); with `once` a single check is made
+pub async fn run_alerts(data_provider_api_key: &String, api_base_url: &String, once: bool, outputs: &Outputs) -> Result<(), Box<dyn Error>> {
+    let alerts = load_alerts()?;
+    let events = env::var("ALERTS_EVENTS").map(|value| value != "false").unwrap_or(true);
+    if alerts.is_empty() && !events {
+        return Err("No alerts configured; copy alerts.example.toml to alerts.toml or set ALERTS_FILE".into());
+    }
+    let squeeze_pct = if events {
+        Some(env::var("ALERTS_SQUEEZE_PCT").ok().and_then(|value| value.trim().parse().ok()).unwrap_or(5.0))
+    } else {
+        None
+    };
+
+    let state_file = state_file();
+    let minutes = env::var("ALERTS_INTERVAL_MINUTES").ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(15);
+    let cooldown_hours = env::var("ALERTS_COOLDOWN_HOURS").ok()
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .unwrap_or(12.0);
+    let cooldown = TimeDelta::seconds((cooldown_hours * 3600.0) as i64);
+
+    loop {
+        let result = alert_step(data_provider_api_key, api_base_url, &alerts, squeeze_pct, cooldown, &state_file, outputs).await;
+        if once {
+            return result;
+        }
+
+        // Keep watching through transient API failures
+        if let Err(e) = result {
+            log::warn!("Alert check failed: {}", e);
+        }
+        tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+    }
+}
+
+/// Fetch fresh data and notify about alerts and, when `squeeze_pct` is set, indicator events
+async fn alert_step(data_provider_api_key: &String, api_base_url: &String, alerts: &[Alert], squeeze_pct: Option<f64>,
+                    cooldown: TimeDelta, state_file: &str, outputs: &Outputs) -> Result<(), Box<dyn Error>> {
+    let (btc_data, fear_and_greed_data) = tokio::join!(
+        data_fetcher::fetch_bitcoin_trading_data(data_provider_api_key, api_base_url),
+        data_fetcher::load_fear_greed(),
+    );
+    let report = technical_analysis::build_technical_report(&btc_data?, &fear_and_greed_data);
+
+    let mut state = AlertState::load(state_file)?;
+    let mut messages = check_alerts(alerts, &report, &mut state, cooldown);
+    if let Some(squeeze_pct) = squeeze_pct {
+        messages.extend(check_events(&report, &mut state, squeeze_pct, cooldown));
+    }
+    state.save(state_file)?;
+
+    for message in &messages {
+        outputs.send_alert(message).await?;
+    }
+    log::info!("Checked {} alerts and indicator events, {} triggered", alerts.len(), messages.len());
+    Ok(())
+}
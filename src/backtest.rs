@@ -0,0 +1,374 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::ops::Range;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::data_fetcher::Candle;
+use crate::indicators::{IndicatorParams, IndicatorRegistry};
+use crate::report::TechnicalReport;
+use crate::signal_score;
+use crate::signals::{self, Action, SignalRules, TradeSignal};
+use crate::strategy::Strategy;
+use crate::technical_analysis;
+
+/// Simulation costs, starting capital and indicator settings
+#[derive(Clone)]
+pub struct BacktestConfig {
+    pub initial_balance: f64,
+    pub fee_pct: f64,          // Charged on each fill's notional
+    pub slippage_pct: f64,     // Applied against us on every fill
+    pub indicators: IndicatorParams,
+}
+
+impl BacktestConfig {
+    /// Defaults with overrides from BACKTEST_* environment variables; the starting balance must be positive
+    pub fn from_env() -> Result<Self, String> {
+        let initial_balance = env_f64("BACKTEST_BALANCE", 10_000.0);
+        if initial_balance.is_nan() || initial_balance <= 0.0 {
+            return Err(format!("BACKTEST_BALANCE must be a positive amount, got {}", initial_balance));
+        }
+        Ok(BacktestConfig {
+            initial_balance,
+            fee_pct: env_f64("BACKTEST_FEE_PCT", 0.1),
+            slippage_pct: env_f64("BACKTEST_SLIPPAGE_PCT", 0.05),
+            indicators: IndicatorParams::from_env(),
+        })
+    }
+}
+
+fn env_f64(name: &str, default: f64) -> f64 {
+    env::var(name).ok().and_then(|value| value.trim().parse().ok()).unwrap_or(default)
+}
+
+/// Where trade signals come from during the replay
+pub enum SignalSource<'a> {
+    Rules(&'a [Strategy]),     // Composite score rules plus any strategies
+    Strategy(&'a Strategy),    // A single strategy on its own
+}
+
+impl<'a> SignalSource<'a> {
+    /// A named strategy on its own, or the rule engine with all strategies when no name is given
+    pub fn select(strategies: &'a [Strategy], name: Option<&str>) -> Result<Self, String> {
+        match name {
+            Some(name) => strategies.iter()
+                .find(|strategy| strategy.name == name)
+                .map(SignalSource::Strategy)
+                .ok_or_else(|| format!("Unknown strategy '{}'", name)),
+            None => Ok(SignalSource::Rules(strategies)),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            SignalSource::Rules(_) => "Rule-based signal engine".to_string(),
+            SignalSource::Strategy(strategy) => format!("Strategy '{}'", strategy.name),
+        }
+    }
+
+    fn signal(&self, report: &TechnicalReport, rules: &SignalRules) -> TradeSignal {
+        match self {
+            SignalSource::Rules(strategies) => signals::generate_signal(report, rules, strategies),
+            SignalSource::Strategy(strategy) => signals::strategy_signal(report, rules, strategy),
+        }
+    }
+}
+
+/// One completed round-trip trade
+#[derive(Serialize)]
+pub struct Trade {
+    pub entry_time: DateTime<Utc>,
+    pub exit_time: DateTime<Utc>,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub quantity: f64,
+    pub pnl: f64,
+    pub return_pct: f64,
+    pub exit_reason: String,
+}
+
+/// Summary and trade log of a backtest run
+#[derive(Serialize)]
+pub struct BacktestResult {
+    pub source: String,
+    pub candles: usize,
+    pub initial_balance: f64,
+    pub final_balance: f64,
+    pub total_return_pct: f64,
+    pub win_rate: f64,
+    pub max_drawdown_pct: f64,
+    pub fees_paid: f64,
+    pub trades: Vec<Trade>,
+    pub equity_curve: Vec<(DateTime<Utc>, f64)>,
+}
+
+/// An open long position
+struct Position {
+    entry_time: DateTime<Utc>,
+    entry_price: f64,
+    quantity: f64,
+    cost: f64,                 // Cash spent including the entry fee
+    stop_loss: Option<f64>,
+    take_profit: Option<f64>,
+}
+
+/// Replay candles through the signal engine, simulating long-only spot trades
+pub fn run_backtest(candles: &[Candle], source: &SignalSource, rules: &SignalRules, config: &BacktestConfig) -> BacktestResult {
+    run_backtest_range(candles, 0..candles.len(), source, rules, config)
+}
+
+/// Backtest only the candles in `range`; earlier candles just warm up the indicators
+pub fn run_backtest_range(candles: &[Candle], range: Range<usize>, source: &SignalSource, rules: &SignalRules,
+                          config: &BacktestConfig) -> BacktestResult {
+    let mut registry = IndicatorRegistry::from_env_with(config.indicators);
+    for candle in &candles[..range.start] {
+        registry.update(candle);
+    }
+    let candles = &candles[range];
+
+    let weights = signal_score::load_weights();
+
+    let mut cash = config.initial_balance;
+    let mut position: Option<Position> = None;
+    let mut trades = Vec::new();
+    let mut fees_paid = 0.0;
+    let mut equity_curve = Vec::new();
+    let mut peak = config.initial_balance;
+    let mut max_drawdown_pct: f64 = 0.0;
+
+    for candle in candles {
+        let time = to_datetime(candle.timestamp);
+
+        // Intrabar exits first; assume the stop is hit before the target when both are touched
+        if let Some(open) = &position {
+            let exit = match (open.stop_loss, open.take_profit) {
+                (Some(stop), _) if candle.low <= stop => Some((stop, "Stop loss")),
+                (_, Some(target)) if candle.high >= target => Some((target, "Take profit")),
+                _ => None,
+            };
+            if let Some((price, reason)) = exit {
+                let open = position.take().unwrap();
+                cash = close_position(open, price, time, reason, config, &mut trades, &mut fees_paid);
+            }
+        }
+
+        registry.update(candle);
+
+        if registry.is_ready() {
+            let report = technical_analysis::snapshot_report(&registry, candle, &weights);
+            let signal = source.signal(&report, rules);
+
+            match (signal.action, &position) {
+                (Action::Buy, None) => {
+                    let price = candle.close * (1.0 + config.slippage_pct / 100.0);
+                    let fee = cash * config.fee_pct / 100.0;
+                    fees_paid += fee;
+                    position = Some(Position {
+                        entry_time: time,
+                        entry_price: price,
+                        quantity: (cash - fee) / price,
+                        cost: cash,
+                        stop_loss: signal.stop_loss,
+                        take_profit: signal.take_profit,
+                    });
+                    cash = 0.0;
+                }
+                (Action::Sell, Some(_)) => {
+                    let open = position.take().unwrap();
+                    cash = close_position(open, candle.close, time, "Sell signal", config, &mut trades, &mut fees_paid);
+                }
+                _ => {}
+            }
+        }
+
+        // Mark to market at the close
+        let equity = cash + position.as_ref().map(|open| open.quantity * candle.close).unwrap_or(0.0);
+        peak = peak.max(equity);
+        max_drawdown_pct = max_drawdown_pct.max((peak - equity) / peak * 100.0);
+        equity_curve.push((time, equity));
+    }
+
+    // Close anything still open at the last price
+    if let (Some(open), Some(last)) = (position.take(), candles.last()) {
+        cash = close_position(open, last.close, to_datetime(last.timestamp), "End of data", config, &mut trades, &mut fees_paid);
+    }
+
+    let wins = trades.iter().filter(|trade| trade.pnl > 0.0).count();
+    let win_rate = if trades.is_empty() { 0.0 } else { wins as f64 / trades.len() as f64 * 100.0 };
+
+    BacktestResult {
+        source: source.describe(),
+        candles: candles.len(),
+        initial_balance: config.initial_balance,
+        final_balance: cash,
+        total_return_pct: (cash - config.initial_balance) / config.initial_balance * 100.0,
+        win_rate,
+        max_drawdown_pct,
+        fees_paid,
+        trades,
+        equity_curve,
+    }
+}
+
+/// Sell the position with slippage and fees, record the trade and return the resulting cash
+fn close_position(open: Position, price: f64, time: DateTime<Utc>, reason: &str, config: &BacktestConfig,
+                  trades: &mut Vec<Trade>, fees_paid: &mut f64) -> f64 {
+    let exit_price = price * (1.0 - config.slippage_pct / 100.0);
+    let proceeds = open.quantity * exit_price;
+    let fee = proceeds * config.fee_pct / 100.0;
+    *fees_paid += fee;
+    let cash = proceeds - fee;
+
+    trades.push(Trade {
+        entry_time: open.entry_time,
+        exit_time: time,
+        entry_price: open.entry_price,
+        exit_price,
+        quantity: open.quantity,
+        pnl: cash - open.cost,
+        return_pct: (cash - open.cost) / open.cost * 100.0,
+        exit_reason: reason.to_string(),
+    });
+
+    cash
+}
+
+/// Summary statistics written alongside the trade log and equity curve
+#[derive(Serialize)]
+struct BacktestSummary<'a> {
+    source: &'a str,
+    candles: usize,
+    initial_balance: f64,
+    final_balance: f64,
+    total_return_pct: f64,
+    trades: usize,
+    win_rate: f64,
+    max_drawdown_pct: f64,
+    fees_paid: f64,
+}
+
+/// Write equity_curve.csv, trades.csv and summary.json into `dir`
+pub fn export_results(result: &BacktestResult, dir: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+
+    let mut equity = String::from("time,equity\n");
+    for (time, value) in &result.equity_curve {
+        equity.push_str(&format!("{},{:.2}\n", time.to_rfc3339(), value));
+    }
+    fs::write(dir.join("equity_curve.csv"), equity)?;
+
+    let mut trades = String::from("entry_time,exit_time,entry_price,exit_price,quantity,pnl,return_pct,exit_reason\n");
+    for trade in &result.trades {
+        trades.push_str(&format!("{},{},{:.2},{:.2},{:.8},{:.2},{:.4},{}\n",
+            trade.entry_time.to_rfc3339(), trade.exit_time.to_rfc3339(), trade.entry_price, trade.exit_price,
+            trade.quantity, trade.pnl, trade.return_pct, trade.exit_reason));
+    }
+    fs::write(dir.join("trades.csv"), trades)?;
+
+    let summary = BacktestSummary {
+        source: &result.source,
+        candles: result.candles,
+        initial_balance: result.initial_balance,
+        final_balance: result.final_balance,
+        total_return_pct: result.total_return_pct,
+        trades: result.trades.len(),
+        win_rate: result.win_rate,
+        max_drawdown_pct: result.max_drawdown_pct,
+        fees_paid: result.fees_paid,
+    };
+    fs::write(dir.join("summary.json"), serde_json::to_string_pretty(&summary)?)?;
+
+    Ok(())
+}
+
+/// One train/test split of a walk-forward run
+#[derive(Serialize)]
+pub struct WalkForwardWindow {
+    pub train_start: DateTime<Utc>,
+    pub test_start: DateTime<Utc>,
+    pub test_end: DateTime<Utc>,
+    pub rules: SignalRules,                // Best in-sample parameters, applied to the test window
+    pub in_sample_return_pct: f64,
+    pub out_of_sample_return_pct: f64,
+    pub out_of_sample_trades: usize,
+    pub out_of_sample_max_drawdown_pct: f64,
+}
+
+/// Out-of-sample performance across all walk-forward windows
+#[derive(Serialize)]
+pub struct WalkForwardResult {
+    pub source: String,
+    pub windows: Vec<WalkForwardWindow>,
+    pub average_in_sample_return_pct: f64,
+    pub average_out_of_sample_return_pct: f64,
+    pub consistency_pct: f64,              // Share of test windows that were profitable
+}
+
+/// Split the history into `folds + 1` segments; pick parameters on each segment and test them on the next
+pub fn run_walk_forward(candles: &[Candle], folds: usize, source: &SignalSource, base_rules: &SignalRules,
+                        config: &BacktestConfig) -> Result<WalkForwardResult, String> {
+    let segment = candles.len() / (folds + 1);
+    if folds == 0 || segment < 50 {
+        return Err(format!("Not enough candles ({}) for {} walk-forward windows", candles.len(), folds));
+    }
+
+    let candidates = candidate_rules(base_rules);
+    let mut windows = Vec::new();
+
+    for fold in 0..folds {
+        let train = fold * segment..(fold + 1) * segment;
+        let test = (fold + 1) * segment..((fold + 2) * segment).min(candles.len());
+
+        // Choose the candidate with the best in-sample return
+        let (rules, in_sample) = candidates.iter()
+            .map(|rules| (rules, run_backtest_range(candles, train.clone(), source, rules, config)))
+            .max_by(|(_, a), (_, b)| a.total_return_pct.total_cmp(&b.total_return_pct))
+            .unwrap();
+
+        let out_of_sample = run_backtest_range(candles, test.clone(), source, rules, config);
+
+        windows.push(WalkForwardWindow {
+            train_start: to_datetime(candles[train.start].timestamp),
+            test_start: to_datetime(candles[test.start].timestamp),
+            test_end: to_datetime(candles[test.end - 1].timestamp),
+            rules: rules.clone(),
+            in_sample_return_pct: in_sample.total_return_pct,
+            out_of_sample_return_pct: out_of_sample.total_return_pct,
+            out_of_sample_trades: out_of_sample.trades.len(),
+            out_of_sample_max_drawdown_pct: out_of_sample.max_drawdown_pct,
+        });
+    }
+
+    let count = windows.len() as f64;
+    let profitable = windows.iter().filter(|window| window.out_of_sample_return_pct > 0.0).count();
+
+    Ok(WalkForwardResult {
+        source: source.describe(),
+        average_in_sample_return_pct: windows.iter().map(|window| window.in_sample_return_pct).sum::<f64>() / count,
+        average_out_of_sample_return_pct: windows.iter().map(|window| window.out_of_sample_return_pct).sum::<f64>() / count,
+        consistency_pct: profitable as f64 / count * 100.0,
+        windows,
+    })
+}
+
+/// Parameter sets tried on each training window: score thresholds crossed with stop/target distances
+fn candidate_rules(base: &SignalRules) -> Vec<SignalRules> {
+    let mut candidates = Vec::new();
+    for buy_score in [10.0, 15.0, 25.0] {
+        for (stop_atr, target_atr) in [(1.0, 2.0), (1.5, 3.0), (2.0, 4.0)] {
+            candidates.push(SignalRules {
+                buy_score,
+                sell_score: -buy_score,
+                stop_atr,
+                target_atr,
+                ..base.clone()
+            });
+        }
+    }
+    candidates
+}
+
+fn to_datetime(timestamp: f64) -> DateTime<Utc> {
+    DateTime::<Utc>::from_timestamp((timestamp as i64) / 1000, 0).unwrap()
+}
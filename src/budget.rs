@@ -0,0 +1,249 @@
+use std::env;
+use crate::report::TechnicalReport;
+
+/// Prompt size limit when PROMPT_TOKEN_BUDGET is not set
+const DEFAULT_TOKEN_BUDGET: usize = 50_000;
+
+/// Price rows and indicator periods always kept, however tight the budget
+const MIN_PRICE_ROWS: usize = 12;
+const MIN_INDICATOR_PERIODS: usize = 2;
+
+/// Report sections that can be trimmed, from PROMPT_SECTION_PRIORITY names
+#[derive(Clone, Copy, PartialEq)]
+pub enum Section {
+    Indicators,
+    Prices,
+    Ohlcv,
+    Market,
+    FearGreed,
+    Journal,
+    Portfolio,
+    News,
+}
+
+impl Section {
+    const ALL: [Section; 8] = [Section::Indicators, Section::Prices, Section::Ohlcv, Section::Market,
+        Section::FearGreed, Section::Journal, Section::Portfolio, Section::News];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Section::Indicators => "indicators",
+            Section::Prices => "prices",
+            Section::Ohlcv => "ohlcv",
+            Section::Market => "market",
+            Section::FearGreed => "fear_greed",
+            Section::Journal => "journal",
+            Section::Portfolio => "portfolio",
+            Section::News => "news",
+        }
+    }
+}
+
+/// Priority used when PROMPT_SECTION_PRIORITY is not set, most important first
+const DEFAULT_PRIORITY: &str = "portfolio,journal,fear_greed,market,indicators,news,prices,ohlcv";
+
+/// Prompt size limit and the order in which report sections are kept
+pub struct PromptBudget {
+    pub tokens: usize,
+    pub priority: Vec<Section>,    // Most important first; the last is trimmed first
+}
+
+impl PromptBudget {
+    /// Token limit from PROMPT_TOKEN_BUDGET (default 50000) and section priorities from
+    /// PROMPT_SECTION_PRIORITY (comma-separated, most important first; unlisted sections rank last)
+    pub fn from_env() -> Self {
+        let tokens = env::var("PROMPT_TOKEN_BUDGET").ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_TOKEN_BUDGET);
+
+        let names = env::var("PROMPT_SECTION_PRIORITY").unwrap_or_else(|_| DEFAULT_PRIORITY.to_string());
+        let mut priority = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            match Section::ALL.into_iter().find(|section| section.as_str() == name) {
+                Some(section) if !priority.contains(&section) => priority.push(section),
+                Some(_) => {}
+                None => log::warn!("ignoring unknown prompt section '{}' in PROMPT_SECTION_PRIORITY", name),
+            }
+        }
+        for section in DEFAULT_PRIORITY.split(',').filter_map(|name| Section::ALL.into_iter().find(|section| section.as_str() == name)) {
+            if !priority.contains(&section) {
+                priority.push(section);
+            }
+        }
+
+        PromptBudget { tokens, priority }
+    }
+}
+
+/// Longest reply a model is asked for (the backends' max_tokens), used as the worst case for cost estimates
+const MAX_OUTPUT_TOKENS: usize = 4096;
+
+/// Estimated cost in USD of sending a prompt of `prompt_tokens` to `requests` models and getting the longest reply back,
+/// at AI_INPUT_COST_PER_MTOK and AI_OUTPUT_COST_PER_MTOK (USD per million tokens); None unless both prices are set
+pub fn estimate_cost(prompt_tokens: usize, requests: usize) -> Option<f64> {
+    let price = |name: &str| env::var(name).ok().and_then(|value| value.trim().parse::<f64>().ok());
+    let (input, output) = (price("AI_INPUT_COST_PER_MTOK")?, price("AI_OUTPUT_COST_PER_MTOK")?);
+    Some((prompt_tokens as f64 * input + MAX_OUTPUT_TOKENS as f64 * output) / 1_000_000.0 * requests as f64)
+}
+
+/// Rough token count (about four characters per token for English text and numbers)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Build the prompt, trimming the report until it fits the budget, lowest-priority section first.
+/// Price rows and indicator history are shortened to their minimum; the other sections are removed.
+/// Returns the prompt and a description of each trimmed part.
+pub fn fit_prompt(report: &mut TechnicalReport, budget: &PromptBudget, build_prompt: impl Fn(&TechnicalReport) -> String) -> (String, Vec<String>) {
+    let mut trimmed = Vec::new();
+    let fits = |report: &TechnicalReport| estimate_tokens(&build_prompt(report)) <= budget.tokens;
+
+    for section in budget.priority.iter().rev() {
+        if fits(report) {
+            break;
+        }
+        match section {
+            Section::Ohlcv => {
+                let rows = report.recent_candles.len();
+                while !fits(report) && report.recent_candles.len() > MIN_PRICE_ROWS {
+                    let drop = (report.recent_candles.len() / 4).clamp(1, report.recent_candles.len() - MIN_PRICE_ROWS);
+                    report.recent_candles.drain(..drop);
+                }
+                if report.recent_candles.len() < rows {
+                    trimmed.push(format!("OHLCV data to the last {} of {} rows", report.recent_candles.len(), rows));
+                }
+            }
+            Section::Prices => {
+                let points = report.price_points.len();
+                while !fits(report) && report.price_points.len() > MIN_PRICE_ROWS {
+                    let drop = (report.price_points.len() / 4).clamp(1, report.price_points.len() - MIN_PRICE_ROWS);
+                    report.price_points.drain(..drop);
+                }
+                if report.price_points.len() < points {
+                    trimmed.push(format!("price data to the last {} of {} points", report.price_points.len(), points));
+                }
+            }
+            Section::Indicators => {
+                let periods = report.indicators.iter().map(|indicator| indicator.periods.len()).max().unwrap_or(0);
+                let mut kept = periods;
+                while !fits(report) && kept > MIN_INDICATOR_PERIODS {
+                    kept -= 1;
+                    for indicator in &mut report.indicators {
+                        let excess = indicator.periods.len().saturating_sub(kept);
+                        indicator.periods.drain(..excess);
+                    }
+                }
+                if kept < periods {
+                    trimmed.push(format!("indicator history to the last {} periods", kept));
+                }
+            }
+            Section::Market => {
+                if report.market.take().is_some() {
+                    trimmed.push("the historical data summary".to_string());
+                }
+            }
+            Section::FearGreed => {
+                if report.fear_greed.len() > 1 {
+                    report.fear_greed.truncate(1);
+                    trimmed.push("the Fear & Greed history to the latest reading".to_string());
+                }
+            }
+            Section::Journal => {
+                if report.journal.take().is_some() {
+                    trimmed.push("the trade journal summary".to_string());
+                }
+            }
+            Section::Portfolio => {
+                if report.portfolio.take().is_some() {
+                    trimmed.push("the portfolio".to_string());
+                }
+            }
+            Section::News => {
+                if !report.news.is_empty() {
+                    report.news.clear();
+                    trimmed.push("the news headlines".to_string());
+                }
+            }
+        }
+    }
+
+    (build_prompt(report), trimmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use chrono::Utc;
+    use crate::data_fetcher::Candle;
+    use crate::indicators::IndicatorRegistry;
+    use crate::report::{CandleRow, SentimentReading};
+    use crate::technical_analysis;
+
+    /// A report with `rows` OHLCV rows and `readings` Fear & Greed readings, oldest first
+    fn report(rows: usize, readings: usize) -> TechnicalReport {
+        let candle = Candle { timestamp: 0.0, open: 100.0, high: 100.0, low: 100.0, close: 100.0, volume: 1.0 };
+        let mut report = technical_analysis::snapshot_report(&IndicatorRegistry::new(), &candle, &HashMap::new());
+        report.recent_candles = (0..rows)
+            .map(|i| CandleRow { time: Utc::now(), open: 100.0, high: 100.0, low: 100.0, close: 100.0 + i as f64, volume: 1.0 })
+            .collect();
+        report.fear_greed = (0..readings)
+            .map(|_| SentimentReading { date: Utc::now(), value: 50, classification: "Neutral".to_string() })
+            .collect();
+        report
+    }
+
+    /// Ten tokens per OHLCV row and per Fear & Greed reading
+    fn prompt(report: &TechnicalReport) -> String {
+        "row.".repeat(10 * (report.recent_candles.len() + report.fear_greed.len()))
+    }
+
+    fn budget(tokens: usize) -> PromptBudget {
+        PromptBudget { tokens, priority: vec![Section::FearGreed, Section::Ohlcv] }
+    }
+
+    #[test]
+    fn tokens_are_about_four_characters() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn a_prompt_within_budget_is_left_alone() {
+        let mut report = report(24, 5);
+        let (prompt, trimmed) = fit_prompt(&mut report, &budget(290), prompt);
+        assert_eq!(estimate_tokens(&prompt), 290);
+        assert!(trimmed.is_empty());
+        assert_eq!((report.recent_candles.len(), report.fear_greed.len()), (24, 5));
+    }
+
+    #[test]
+    fn the_lowest_priority_section_is_trimmed_first_and_only_as_far_as_needed() {
+        let mut report = report(24, 5);
+        let (prompt, trimmed) = fit_prompt(&mut report, &budget(250), prompt);
+        assert!(estimate_tokens(&prompt) <= 250);
+        assert_eq!(trimmed, ["OHLCV data to the last 18 of 24 rows"]);
+        assert_eq!(report.fear_greed.len(), 5);
+        // The newest rows are the ones kept
+        assert_eq!(report.recent_candles[0].close, 106.0);
+    }
+
+    #[test]
+    fn rows_stop_at_their_minimum_before_the_next_section_goes() {
+        let mut report = report(24, 5);
+        let (prompt, trimmed) = fit_prompt(&mut report, &budget(130), prompt);
+        assert_eq!(report.recent_candles.len(), MIN_PRICE_ROWS);
+        assert_eq!(report.fear_greed.len(), 1);
+        assert_eq!(trimmed, ["OHLCV data to the last 12 of 24 rows", "the Fear & Greed history to the latest reading"]);
+        assert_eq!(estimate_tokens(&prompt), 130);
+    }
+
+    #[test]
+    fn an_impossible_budget_still_keeps_the_minimum() {
+        let mut report = report(24, 5);
+        let (prompt, _) = fit_prompt(&mut report, &budget(10), prompt);
+        assert_eq!(report.recent_candles.len(), MIN_PRICE_ROWS);
+        assert!(estimate_tokens(&prompt) > 10);
+    }
+}
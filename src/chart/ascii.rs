@@ -0,0 +1,88 @@
+use crate::data_fetcher::Candle;
+use crate::i18n;
+use crate::locale;
+
+/// Number of most recent candles drawn in the terminal
+const ASCII_CANDLES: usize = 60;
+
+/// Rows of the candle grid
+const ROWS: usize = 14;
+
+const SPARK: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const WICK: char = '│';
+const UP_BODY: char = '█';
+const DOWN_BODY: char = '░';
+const LEVEL: char = '╌';
+
+/// A sparkline of closes and a candle grid of the last candles, with the support and resistance rows marked,
+/// for reading the report over ssh. None with fewer than two candles.
+pub fn render_ascii(candles: &[Candle], support: f64, resistance: f64) -> Option<String> {
+    let candles = &candles[candles.len().saturating_sub(ASCII_CANDLES)..];
+    if candles.len() < 2 {
+        return None;
+    }
+
+    let low = candles.iter().map(|candle| candle.low).fold(f64::MAX, f64::min);
+    let high = candles.iter().map(|candle| candle.high).fold(f64::MIN, f64::max);
+    let range = (high - low).max(f64::EPSILON);
+    let row_of = |price: f64| (((high - price) / range) * (ROWS - 1) as f64).round().clamp(0.0, (ROWS - 1) as f64) as usize;
+
+    let mut grid = vec![vec![' '; candles.len()]; ROWS];
+    for (column, candle) in candles.iter().enumerate() {
+        for row in grid.iter_mut().take(row_of(candle.low) + 1).skip(row_of(candle.high)) {
+            row[column] = WICK;
+        }
+        let body = if candle.close >= candle.open { UP_BODY } else { DOWN_BODY };
+        for row in grid.iter_mut().take(row_of(candle.open.min(candle.close)) + 1).skip(row_of(candle.open.max(candle.close))) {
+            row[column] = body;
+        }
+    }
+
+    // Levels outside the drawn range are listed below the chart instead
+    let number = |price: f64| locale::current().number(price, 2);
+    let mut labels = vec![String::new(); ROWS];
+    let mut outside = Vec::new();
+    for (name, price) in [(i18n::t("R"), resistance), (i18n::t("S"), support)] {
+        if (low..=high).contains(&price) {
+            let row = row_of(price);
+            for cell in grid[row].iter_mut().filter(|cell| **cell == ' ') {
+                *cell = LEVEL;
+            }
+            labels[row] = format!("{} {}", name, number(price));
+        } else {
+            outside.push(format!("{} {} ({})", name, number(price), i18n::t(if price > high { "above chart" } else { "below chart" })));
+        }
+    }
+
+    let first = candles[0].close;
+    let last = candles[candles.len() - 1].close;
+    let change = if first != 0.0 { (last - first) / first * 100.0 } else { 0.0 };
+    let mut text = format!("=== {} ===\n", i18n::tf("PRICE CHART (last {count} candles)", &[("count", &candles.len())]));
+    text.push_str(&format!("{:>16}  {} {} -> {} ({:+.2}%)\n", i18n::t("Close"), sparkline(candles, low, range), number(first), number(last), change));
+
+    for (row, cells) in grid.iter().enumerate() {
+        // Prices on the top, middle and bottom rows
+        let axis = if row == 0 || row == ROWS / 2 || row == ROWS - 1 {
+            number(high - range * row as f64 / (ROWS - 1) as f64)
+        } else {
+            String::new()
+        };
+        let line = format!("{:>16} ┤{} {}", axis, cells.iter().collect::<String>(), labels[row]);
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    if !outside.is_empty() {
+        text.push_str(&format!("{:>16}  {}\n", "", outside.join(", ")));
+    }
+    Some(text)
+}
+
+/// One block per candle for its close, scaled to the same range as the grid
+fn sparkline(candles: &[Candle], low: f64, range: f64) -> String {
+    candles.iter()
+        .map(|candle| {
+            let level = ((candle.close - low) / range * (SPARK.len() - 1) as f64).round() as usize;
+            SPARK[level.min(SPARK.len() - 1)]
+        })
+        .collect()
+}
@@ -0,0 +1,191 @@
+mod ascii;
+
+use std::error::Error;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use crate::data_fetcher::Candle;
+use crate::currency::Currency;
+use crate::i18n;
+use crate::indicators::{IndicatorParams, IndicatorSeries, SERIES_EMA_PERIOD};
+
+pub use ascii::render_ascii;
+
+/// Number of most recent candles drawn
+const CHART_CANDLES: usize = 120;
+
+const WIDTH: u32 = 1200;
+const HEIGHT: u32 = 880;
+const MARGIN: u32 = 20;
+const PRICE_HEIGHT: u32 = 480;      // Candle panel; the RSI, MACD and volume panels fill the rest
+const PANEL_HEIGHT: u32 = 120;      // Including the gap above each panel
+const GRID_LINES: usize = 5;
+
+const BACKGROUND: RGBColor = RGBColor(19, 23, 34);
+const GRID: RGBColor = RGBColor(54, 60, 78);
+const UP: RGBColor = RGBColor(38, 166, 154);
+const DOWN: RGBColor = RGBColor(239, 83, 80);
+const SMA: RGBColor = RGBColor(255, 167, 38);
+const EMA: RGBColor = RGBColor(236, 64, 122);
+const BANDS: RGBColor = RGBColor(66, 135, 245);
+const RSI: RGBColor = RGBColor(171, 71, 188);
+const MACD: RGBColor = RGBColor(41, 98, 255);
+const MACD_SIGNAL: RGBColor = RGBColor(255, 109, 0);
+
+/// A rendered candlestick chart and a description of what it shows, for models reading the image
+pub struct Chart {
+    pub png: Vec<u8>,
+    pub svg: String,
+    pub caption: String,
+    pub title: String,      // One line on what is drawn, for image captions shown to people
+}
+
+/// Candlesticks with the Bollinger Bands and their SMA and a 50-period EMA above RSI, MACD and volume panels,
+/// with the report's indicator settings, drawn with plotters as a PNG and as SVG
+pub fn render_chart(candles: &[Candle], currency: Currency) -> Result<Chart, Box<dyn Error>> {
+    if candles.len() < 2 {
+        return Err("Not enough candles to draw a chart".into());
+    }
+
+    // Indicators are computed over all candles so the first visible values already have their history
+    let params = IndicatorParams::from_env();
+    let start = candles.len().saturating_sub(CHART_CANDLES);
+    let visible = &candles[start..];
+    let series = IndicatorSeries::new(candles, &params)?.split_off(start);
+
+    let mut pixels = vec![0; (WIDTH * HEIGHT * 3) as usize];
+    let grid_prices = {
+        let root = BitMapBackend::with_buffer(&mut pixels, (WIDTH, HEIGHT)).into_drawing_area();
+        let grid_prices = draw(&root, visible, &series)?;
+        root.present()?;
+        grid_prices
+    };
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (WIDTH, HEIGHT)).into_drawing_area();
+        draw(&root, visible, &series)?;
+        root.present()?;
+    }
+    // Fill the width of the container in the HTML report rather than a fixed size
+    let svg = svg.replacen(&format!("width=\"{}\" height=\"{}\"", WIDTH, HEIGHT), "width=\"100%\"", 1);
+
+    let grid = grid_prices.iter().rev().map(|price| currency.round(*price)).collect::<Vec<_>>().join(", ");
+    let caption = format!(
+        "The attached chart shows the last {} candles of the data above. Green candles closed higher and red candles lower, \
+        the orange line is the {}-period SMA, the pink line the {}-period EMA and the blue lines are the Bollinger Bands ({} standard deviations). \
+        Below the candles are three panels: the {}-period RSI (purple, with grid lines at 30 and 70), MACD({},{},{}) \
+        (blue MACD line, orange signal line and the histogram, around a zero line) and volume. \
+        Horizontal grid lines mark {} from top to bottom. Use it for visual pattern analysis alongside the numeric data.",
+        visible.len(), params.bollinger_period, SERIES_EMA_PERIOD, params.bollinger_std_dev, params.rsi_period,
+        params.macd_fast, params.macd_slow, params.macd_signal, grid);
+    let title = i18n::tf("Last {count} candles with SMA {sma}, EMA {ema}, Bollinger Bands ({std_dev}σ), RSI {rsi}, MACD({fast},{slow},{signal}) and volume",
+        &[("count", &visible.len()), ("sma", &params.bollinger_period), ("ema", &SERIES_EMA_PERIOD), ("std_dev", &params.bollinger_std_dev),
+            ("rsi", &params.rsi_period), ("fast", &params.macd_fast), ("slow", &params.macd_slow), ("signal", &params.macd_signal)]);
+
+    Ok(Chart { png: encode_png(&pixels)?, svg, caption, title })
+}
+
+/// The RGB pixels plotters drew into, as a PNG
+fn encode_png(pixels: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, WIDTH, HEIGHT);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)?;
+    Ok(png)
+}
+
+/// Draw every panel, returning the prices of the horizontal grid lines
+fn draw<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>, candles: &[Candle], series: &IndicatorSeries)
+    -> Result<Vec<f64>, DrawingAreaErrorKind<DB::ErrorType>> {
+    root.fill(&BACKGROUND)?;
+    let area = root.margin(MARGIN, MARGIN, MARGIN, MARGIN);
+    let (price_area, panels) = area.split_vertically(PRICE_HEIGHT);
+    let (rsi_area, panels) = panels.split_vertically(PANEL_HEIGHT);
+    let (macd_area, volume_area) = panels.split_vertically(PANEL_HEIGHT);
+    let [rsi_area, macd_area, volume_area] = [rsi_area, macd_area, volume_area].map(|panel| panel.margin(MARGIN, 0, 0, 0));
+
+    // Candle i is centered on x = i
+    let x_range = -0.5..candles.len() as f64 - 0.5;
+    let slot = (WIDTH - 2 * MARGIN) as f64 / candles.len() as f64;
+    let half_body = 0.35;
+    let points = |values: &mut dyn Iterator<Item = Option<f64>>| -> Vec<(f64, f64)> {
+        values.enumerate().filter_map(|(i, value)| Some((i as f64, value?))).collect()
+    };
+    let horizontal = |y: f64| PathElement::new(vec![(-0.5, y), (candles.len() as f64 - 0.5, y)], GRID);
+    let color = |up: bool| if up { UP } else { DOWN };
+
+    let low = candles.iter().map(|candle| candle.low)
+        .chain(series.bands.iter().flatten().map(|bands| bands.lower))
+        .fold(f64::INFINITY, f64::min);
+    let high = candles.iter().map(|candle| candle.high)
+        .chain(series.bands.iter().flatten().map(|bands| bands.upper))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let high = if high > low { high } else { low + 1.0 };
+    let grid_prices: Vec<f64> = (0..GRID_LINES)
+        .map(|i| low + (high - low) * i as f64 / (GRID_LINES - 1) as f64)
+        .collect();
+
+    let mut price = ChartBuilder::on(&price_area).build_cartesian_2d(x_range.clone(), low..high)?;
+    price.draw_series(grid_prices.iter().map(|&y| horizontal(y)))?;
+    price.draw_series(candles.iter().enumerate().map(|(i, candle)| {
+        CandleStick::new(i as f64, candle.open, candle.high, candle.low, candle.close,
+            UP.filled(), DOWN.filled(), ((slot * 2.0 * half_body) as u32).max(1))
+    }))?;
+    price.draw_series(LineSeries::new(points(&mut series.bands.iter().map(|bands| Some(bands.as_ref()?.upper))), BANDS.stroke_width(2)))?;
+    price.draw_series(LineSeries::new(points(&mut series.bands.iter().map(|bands| Some(bands.as_ref()?.average))), SMA.stroke_width(2)))?;
+    price.draw_series(LineSeries::new(points(&mut series.bands.iter().map(|bands| Some(bands.as_ref()?.lower))), BANDS.stroke_width(2)))?;
+    price.draw_series(LineSeries::new(points(&mut series.ema.iter().copied()), EMA.stroke_width(2)))?;
+
+    let mut rsi = ChartBuilder::on(&rsi_area).build_cartesian_2d(x_range.clone(), 0.0..100.0)?;
+    rsi.draw_series([30.0, 70.0].map(horizontal))?;
+    rsi.draw_series(LineSeries::new(points(&mut series.rsi.iter().copied()), RSI.stroke_width(2)))?;
+
+    let macd_range = series.macd.iter().flatten()
+        .flat_map(|macd| [macd.macd.abs(), macd.signal.abs(), macd.histogram.abs()])
+        .fold(0.0, f64::max);
+    let macd_range = if macd_range > 0.0 { macd_range } else { 1.0 };
+    let mut macd = ChartBuilder::on(&macd_area).build_cartesian_2d(x_range.clone(), -macd_range..macd_range)?;
+    macd.draw_series([horizontal(0.0)])?;
+    macd.draw_series(series.macd.iter().enumerate().filter_map(|(i, macd)| {
+        let histogram = macd.as_ref()?.histogram;
+        Some(Rectangle::new([(i as f64 - half_body, 0.0), (i as f64 + half_body, histogram)], color(histogram >= 0.0).filled()))
+    }))?;
+    macd.draw_series(LineSeries::new(points(&mut series.macd.iter().map(|macd| Some(macd.as_ref()?.macd))), MACD.stroke_width(2)))?;
+    macd.draw_series(LineSeries::new(points(&mut series.macd.iter().map(|macd| Some(macd.as_ref()?.signal))), MACD_SIGNAL.stroke_width(2)))?;
+
+    let max_volume = candles.iter().map(|candle| candle.volume).fold(0.0, f64::max);
+    let mut volume = ChartBuilder::on(&volume_area).build_cartesian_2d(x_range, 0.0..max_volume.max(f64::EPSILON))?;
+    volume.draw_series(candles.iter().enumerate().map(|(i, candle)| {
+        Rectangle::new([(i as f64 - half_body, 0.0), (i as f64 + half_body, candle.volume)], color(candle.close >= candle.open).filled())
+    }))?;
+
+    Ok(grid_prices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles() -> Vec<Candle> {
+        (0..200).map(|i| {
+            let close = 100000.0 + (i as f64 / 8.0).sin() * 4000.0 + i as f64 * 20.0;
+            let open = close - (i as f64 / 3.0).cos() * 800.0;
+            Candle { timestamp: i as f64 * 86_400_000.0, open, high: open.max(close) + 500.0, low: open.min(close) - 500.0, close,
+                volume: 100.0 + (i % 7) as f64 * 30.0 }
+        }).collect()
+    }
+
+    #[test]
+    fn renders_png_and_svg() {
+        let chart = render_chart(&candles(), Currency::USD).unwrap();
+        assert!(chart.png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert!(chart.svg.starts_with("<svg width=\"100%\" viewBox=\"0 0 1200 880\""));
+        assert!(chart.caption.starts_with("The attached chart shows the last 120 candles"));
+        assert_eq!(chart.title, "Last 120 candles with SMA 20, EMA 50, Bollinger Bands (2σ), RSI 14, MACD(12,26,9) and volume");
+    }
+
+    #[test]
+    fn needs_two_candles() {
+        assert!(render_chart(&candles()[..1], Currency::USD).is_err());
+    }
+}
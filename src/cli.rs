@@ -0,0 +1,336 @@
+use std::env;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use clap::builder::PossibleValuesParser;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use crate::ai_client;
+use crate::currency;
+use crate::data_fetcher;
+use crate::locale;
+use crate::prompt_generator;
+use crate::secrets;
+
+/// AI-assisted Bitcoin market analysis from technical indicators, sent to the terminal, chat apps and files
+#[derive(Parser)]
+#[command(name = "crypto-forecast", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Options for the analysis, which runs when no command is given
+    #[command(flatten)]
+    pub analyze: AnalyzeArgs,
+
+    /// Print only warnings and errors on stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print more on stderr: -v adds request details, -vv everything
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Never color the terminal output (NO_COLOR works too)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Text for reading, or JSON for scripts (without the AI analysis)
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// Show prices in this currency, converted from USD (default QUOTE_CURRENCY, otherwise USD)
+    #[arg(long, global = true, ignore_case = true, value_parser = PossibleValuesParser::new(currency::CURRENCIES.map(|(code, _)| code)))]
+    pub currency: Option<String>,
+
+    /// Write numbers with this locale's separators, e.g. de-DE for 102.753,63 € (default NUMBER_LOCALE, otherwise en-US)
+    #[arg(long, global = true, ignore_case = true, value_parser = PossibleValuesParser::new(locale::LOCALES.map(|locale| locale.code)))]
+    pub locale: Option<String>,
+
+    /// Write the report and ask the model to answer in this language: de, or any messages/<lang>.toml catalog
+    /// (default REPORT_LANGUAGE, otherwise English)
+    #[arg(long, global = true, value_name = "LANG")]
+    pub lang: Option<String>,
+
+    /// Apply this profile from crypto-forecast.toml on top of its base settings
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+}
+
+impl Cli {
+    /// -1 with --quiet, otherwise the number of -v flags
+    pub fn verbosity(&self) -> i8 {
+        if self.quiet { -1 } else { self.verbose.min(2) as i8 }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the AI market analysis and send it to the chosen outputs (the default)
+    Analyze(AnalyzeArgs),
+    /// Fetch the market data and print the technical report, without asking a model
+    Fetch(MarketArgs),
+    /// Replay the signal rules or a strategy over the fetched candles
+    Backtest {
+        #[command(flatten)]
+        market: MarketArgs,
+        /// Strategy from strategies.toml instead of the built-in rules
+        #[arg(long)]
+        strategy: Option<String>,
+        /// Test on this many windows, each tuned on the segment before it
+        #[arg(long, value_name = "WINDOWS", value_parser = clap::value_parser!(u64).range(1..))]
+        walk_forward: Option<u64>,
+        /// Write the equity curve, trades and summary to this directory
+        #[arg(long, value_name = "DIR")]
+        export: Option<PathBuf>,
+    },
+    /// Search strategy parameters for the best backtest result
+    Optimize {
+        #[command(flatten)]
+        market: MarketArgs,
+        /// Only optimize this strategy
+        #[arg(long)]
+        strategy: Option<String>,
+    },
+    /// Trade the signals on a simulated account
+    Paper {
+        /// Make a single step instead of running until stopped
+        #[arg(long)]
+        once: bool,
+    },
+    /// Watch alert conditions and indicator events and send notifications
+    Alerts {
+        /// Make a single check instead of running until stopped
+        #[arg(long)]
+        once: bool,
+        /// Where notifications go, comma-separated (e.g. telegram,pushover)
+        outputs: Option<String>,
+    },
+    /// Analyze every symbol in WATCHLIST
+    Watchlist(ReportArgs),
+    /// On-chain activity report
+    OnchainReport(ReportArgs),
+    /// Bitcoin against stocks, the dollar, yields and gold
+    MacroReport(ReportArgs),
+    /// Review of the past week and of the week's calls
+    WeeklyReview(ReportArgs),
+    /// Record and review trades: open, close, list or summary
+    Journal {
+        /// The journal command and its values (e.g. `open long 60000 0.1 breakout`)
+        #[arg(allow_negative_numbers = true)]
+        args: Vec<String>,
+    },
+    /// List and show past analysis runs
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+    /// Score past calls against the price after their horizon, per model and prompt version
+    Accuracy {
+        /// Hours after a call to check the price (default ACCURACY_HORIZON_HOURS, otherwise 24)
+        #[arg(long)]
+        horizon: Option<u32>,
+        /// Only score calls made in the last this many days
+        #[arg(long, default_value_t = 30)]
+        days: i64,
+    },
+    /// Full-screen dashboard with live price, indicators, the latest analysis and alerts
+    Tui,
+    /// Serve the technical report and signal over HTTP
+    Serve {
+        /// Address to listen on (default SERVER_ADDR, otherwise 127.0.0.1:8080)
+        #[arg(long)]
+        addr: Option<SocketAddr>,
+        /// Also serve the gRPC service on this address (default GRPC_ADDR, otherwise not served)
+        #[arg(long)]
+        grpc_addr: Option<SocketAddr>,
+    },
+    /// Offer the market data, indicators and analysis as tools to agent hosts over MCP (stdin/stdout)
+    Mcp(MarketArgs),
+    /// Run the profiles in crypto-forecast.toml on their cron schedules until stopped
+    Schedule {
+        /// Only run these profiles (default every profile with a schedule)
+        profiles: Vec<String>,
+    },
+    /// Store credentials in the OS keyring, read with SECRETS_KEYRING=true
+    Secrets {
+        #[command(subcommand)]
+        command: SecretsCommand,
+    },
+    /// Run a chat bot
+    Bot {
+        #[command(subcommand)]
+        platform: BotPlatform,
+    },
+}
+
+#[derive(Clone, Copy, Subcommand)]
+pub enum BotPlatform {
+    /// Handle the buttons under Telegram analyses
+    Telegram,
+    /// Answer the /analyze, /signals and /price slash commands on Discord
+    Discord,
+}
+
+#[derive(Clone, Subcommand)]
+pub enum HistoryCommand {
+    /// The most recent runs, newest first
+    List {
+        /// Only runs for this symbol
+        #[arg(long, value_parser = parse_symbol)]
+        symbol: Option<String>,
+        /// How many runs to list
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// One run in full: its indicators, recommendation and analysis
+    Show {
+        id: i64,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum SecretsCommand {
+    /// Store a secret, read from stdin (e.g. `secrets set ANTHROPIC_API_KEY`)
+    Set {
+        #[arg(value_parser = PossibleValuesParser::new(secrets::SECRETS.iter().copied()))]
+        name: String,
+    },
+    /// Remove a stored secret
+    Delete {
+        #[arg(value_parser = PossibleValuesParser::new(secrets::SECRETS.iter().copied()))]
+        name: String,
+    },
+}
+
+/// Which market to fetch
+#[derive(Clone, Args)]
+pub struct MarketArgs {
+    /// Binance symbol (default SYMBOL, otherwise BTCUSDT)
+    #[arg(long, value_parser = parse_symbol)]
+    pub symbol: Option<String>,
+
+    /// Candle interval (default CANDLE_INTERVAL, otherwise 4h)
+    #[arg(long, value_parser = PossibleValuesParser::new(data_fetcher::INTERVALS.map(|(name, _)| name)))]
+    pub interval: Option<String>,
+}
+
+impl MarketArgs {
+    /// The symbol from --symbol, SYMBOL or the BTCUSDT default
+    pub fn symbol(&self) -> Result<String, String> {
+        match (&self.symbol, env::var("SYMBOL")) {
+            (Some(symbol), _) => Ok(symbol.clone()),
+            (None, Ok(symbol)) => parse_symbol(&symbol).map_err(|e| format!("Invalid SYMBOL '{}': {}", symbol, e)),
+            (None, Err(_)) => Ok("BTCUSDT".to_string()),
+        }
+    }
+}
+
+/// Options for the analysis
+#[derive(Clone, Args)]
+pub struct AnalyzeArgs {
+    #[command(flatten)]
+    pub market: MarketArgs,
+
+    #[command(flatten)]
+    pub prompt_options: PromptArgs,
+
+    /// Print the prompt instead of sending it to a model
+    #[arg(long)]
+    pub only_prompt: bool,
+
+    /// Print the rule-based signal with entry, stop and targets, without a model
+    #[arg(long, conflicts_with = "only_prompt")]
+    pub only_signals: bool,
+
+    /// Send the full technical summary and composite score to the outputs, without a model
+    #[arg(long, conflicts_with_all = ["only_prompt", "only_signals", "ab"])]
+    pub only_indicators: bool,
+
+    /// Fetch the data and build the prompt, then print what would be sent where (prompt size, models, outputs,
+    /// estimated cost) without asking a model or sending anything
+    #[arg(long, conflicts_with_all = ["only_prompt", "only_signals", "only_indicators", "ab", "watch"])]
+    pub dry_run: bool,
+
+    /// Place the signal as a Binance order (with --only-signals)
+    #[arg(long, requires = "only_signals")]
+    pub execute: bool,
+
+    /// Use the compact prompt
+    #[arg(long, conflicts_with = "prompt")]
+    pub brief: bool,
+
+    /// Compare two prompt versions or models on the same data (e.g. default,brief)
+    #[arg(long, value_name = "A,B", conflicts_with_all = ["only_prompt", "only_signals"])]
+    pub ab: Option<String>,
+
+    /// Print only the call (BUY, SELL, HOLD or NONE) on stdout
+    #[arg(long)]
+    pub porcelain: bool,
+
+    /// Exit with 10 for BUY, 11 for SELL, 12 for HOLD or 13 without a call
+    #[arg(long)]
+    pub exit_code: bool,
+
+    /// Keep checking every WATCH_INTERVAL_MINUTES and only analyze again when the signal or key levels change
+    #[arg(long, conflicts_with = "exit_code")]
+    pub watch: bool,
+
+    /// Where the report goes, comma-separated (e.g. telegram,file); default OUTPUTS, then outputs.toml, otherwise the terminal
+    pub outputs: Option<String>,
+}
+
+/// Options for the commands that write a report other than the analysis
+#[derive(Args)]
+pub struct ReportArgs {
+    #[command(flatten)]
+    pub prompt_options: PromptArgs,
+
+    /// Print the prompt instead of sending it to a model
+    #[arg(long)]
+    pub only_prompt: bool,
+
+    /// Where the report goes, comma-separated (e.g. telegram,file); default OUTPUTS, then outputs.toml, otherwise the terminal
+    pub outputs: Option<String>,
+}
+
+/// How the prompt is written and which model answers it
+#[derive(Clone, Args)]
+pub struct PromptArgs {
+    /// Write the analysis for a kind of trader
+    #[arg(long, value_parser = PossibleValuesParser::new(prompt_generator::PERSONAS.map(|(name, _)| name)))]
+    pub persona: Option<String>,
+
+    /// Also answer this question about the current data
+    #[arg(long, value_name = "QUESTION")]
+    pub ask: Option<String>,
+
+    /// Prompt version: default, brief or prompts/<name>.tera
+    #[arg(long, value_name = "VERSION")]
+    pub prompt: Option<String>,
+
+    /// AI backends, comma-separated (default AI_PROVIDER, otherwise anthropic)
+    #[arg(long, value_parser = parse_providers)]
+    pub provider: Option<String>,
+}
+
+pub fn parse_symbol(value: &str) -> Result<String, String> {
+    let symbol = value.trim().to_uppercase();
+    if symbol.len() < 5 || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("expected a Binance symbol such as BTCUSDT".to_string());
+    }
+    Ok(symbol)
+}
+
+fn parse_providers(value: &str) -> Result<String, String> {
+    for name in value.split(',').map(str::trim) {
+        if !ai_client::PROVIDERS.contains(&name) {
+            return Err(format!("unknown provider '{}', expected {}", name, ai_client::PROVIDERS.join(", ")));
+        }
+    }
+    Ok(value.to_string())
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+
+/// Default location of the configuration file
+const DEFAULT_CONFIG_FILE: &str = "crypto-forecast.toml";
+
+/// crypto-forecast.toml: settings for every run, and named profiles that override them
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(flatten)]
+    base: Settings,
+    #[serde(default)]
+    profiles: BTreeMap<String, Settings>,
+}
+
+/// One layer of settings, each standing for an environment variable
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    provider: Option<String>,           // AI_PROVIDER
+    symbol: Option<String>,             // SYMBOL
+    symbols: Option<Vec<String>>,       // WATCHLIST
+    interval: Option<String>,           // CANDLE_INTERVAL
+    prompt: Option<String>,             // PROMPT_VERSION
+    prompt_template: Option<String>,    // PROMPT_TEMPLATE
+    outputs: Option<Vec<String>>,       // OUTPUTS
+    schedule: Option<String>,           // Cron expression for the `schedule` command, in profiles
+    run: Option<Vec<String>>,           // Command and options for scheduled runs (default the analysis)
+    #[serde(default)]
+    indicators: Indicators,
+    #[serde(default)]
+    env: BTreeMap<String, String>,      // Any other setting, by its variable name
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Indicators {
+    rsi_period: Option<usize>,          // RSI_PERIOD
+    macd_fast: Option<usize>,           // MACD_FAST
+    macd_slow: Option<usize>,           // MACD_SLOW
+    macd_signal: Option<usize>,         // MACD_SIGNAL
+    bollinger_period: Option<usize>,    // BOLLINGER_PERIOD
+    bollinger_std_dev: Option<f64>,     // BOLLINGER_STD_DEV
+    disabled: Option<Vec<String>>,      // DISABLED_INDICATORS
+}
+
+impl Settings {
+    /// The settings as environment variables and their values
+    fn variables(&self) -> Vec<(String, String)> {
+        let indicators = &self.indicators;
+        let mut variables: Vec<(&str, Option<String>)> = vec![
+            ("AI_PROVIDER", self.provider.clone()),
+            ("SYMBOL", self.symbol.clone()),
+            ("WATCHLIST", self.symbols.as_ref().map(|symbols| symbols.join(","))),
+            ("CANDLE_INTERVAL", self.interval.clone()),
+            ("PROMPT_VERSION", self.prompt.clone()),
+            ("PROMPT_TEMPLATE", self.prompt_template.clone()),
+            ("OUTPUTS", self.outputs.as_ref().map(|outputs| outputs.join(","))),
+            ("RSI_PERIOD", indicators.rsi_period.map(|value| value.to_string())),
+            ("MACD_FAST", indicators.macd_fast.map(|value| value.to_string())),
+            ("MACD_SLOW", indicators.macd_slow.map(|value| value.to_string())),
+            ("MACD_SIGNAL", indicators.macd_signal.map(|value| value.to_string())),
+            ("BOLLINGER_PERIOD", indicators.bollinger_period.map(|value| value.to_string())),
+            ("BOLLINGER_STD_DEV", indicators.bollinger_std_dev.map(|value| value.to_string())),
+            ("DISABLED_INDICATORS", indicators.disabled.as_ref().map(|disabled| disabled.join(","))),
+        ];
+        variables.extend(self.env.iter().map(|(name, value)| (name.as_str(), Some(value.clone()))));
+        variables.into_iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value?)))
+            .collect()
+    }
+}
+
+/// A profile run by the `schedule` command
+#[derive(Clone)]
+pub struct ScheduledRun {
+    pub profile: String,
+    pub schedule: String,
+    pub args: Vec<String>,
+}
+
+/// The path of CONFIG_FILE (default crypto-forecast.toml) and its contents, if it exists
+fn load() -> Result<(String, Option<ConfigFile>), Box<dyn Error>> {
+    let path = env::var("CONFIG_FILE").unwrap_or_else(|_| DEFAULT_CONFIG_FILE.to_string());
+    if !Path::new(&path).exists() {
+        return Ok((path, None));
+    }
+    let file = toml::from_str(&fs::read_to_string(&path)?)
+        .map_err(|e| format!("Invalid configuration file {}: {}", path, e))?;
+    Ok((path, Some(file)))
+}
+
+/// Load CONFIG_FILE (default crypto-forecast.toml) and export its settings, with the profile's on top, as environment
+/// variables. Variables already set (including from .env) are left alone, so the environment overrides the file.
+/// Returns the names of the variables that were set.
+pub fn apply(profile: Option<&str>) -> Result<Vec<String>, Box<dyn Error>> {
+    let (path, file) = load()?;
+    let Some(mut file) = file else {
+        return match profile {
+            Some(profile) => Err(format!("Profile '{}' was requested but there is no configuration file {}", profile, path).into()),
+            None => Ok(Vec::new()),
+        };
+    };
+
+    let mut layers = Vec::new();
+    if let Some(name) = profile {
+        let profile = file.profiles.remove(name).ok_or_else(|| {
+            let known: Vec<&str> = file.profiles.keys().map(String::as_str).collect();
+            format!("Unknown profile '{}' in {}, expected one of: {}", name, path, known.join(", "))
+        })?;
+        layers.push(profile);
+    }
+    layers.push(file.base);
+
+    let mut exported = Vec::new();
+    for (name, value) in layers.iter().flat_map(Settings::variables) {
+        if env::var_os(&name).is_none() {
+            // SAFETY: runs at startup, before any task is spawned that could read the environment
+            unsafe { env::set_var(&name, &value) };
+            exported.push(name);
+        }
+    }
+    log::debug!("Loaded configuration from {}{}", path, profile.map(|profile| format!(" with profile {}", profile)).unwrap_or_default());
+    Ok(exported)
+}
+
+/// The profiles in the configuration file that have a schedule
+pub fn scheduled_runs() -> Result<Vec<ScheduledRun>, Box<dyn Error>> {
+    let (path, file) = load()?;
+    let file = file.ok_or_else(|| format!("The schedule command needs a configuration file, but {} does not exist", path))?;
+    Ok(file.profiles.into_iter()
+        .filter_map(|(profile, settings)| Some(ScheduledRun {
+            profile,
+            schedule: settings.schedule?,
+            args: settings.run.unwrap_or_default(),
+        }))
+        .collect())
+}
@@ -0,0 +1,155 @@
+use std::env;
+use std::error::Error;
+use std::sync::OnceLock;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::data_fetcher::{self, CryptoData};
+use crate::locale;
+
+/// Currencies a report can be priced in, with the sign shown before prices
+pub const CURRENCIES: [(&str, &str); 4] = [("USD", "$"), ("EUR", "€"), ("GBP", "£"), ("THB", "฿")];
+
+static QUOTE: OnceLock<Currency> = OnceLock::new();
+
+/// The currency prices are shown in; market data comes in USDT, which counts as USD
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Currency {
+    pub code: &'static str,
+    pub sign: &'static str,
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::USD
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code)
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Currency::named(&code).ok_or_else(|| serde::de::Error::custom(format!("unknown currency '{}'", code)))
+    }
+}
+
+impl Currency {
+    pub const USD: Currency = Currency { code: "USD", sign: "$" };
+
+    /// One of CURRENCIES by its code, in any case
+    pub fn named(code: &str) -> Option<Self> {
+        CURRENCIES.iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(code))
+            .map(|&(code, sign)| Currency { code, sign })
+    }
+
+    /// A price to the cent in the number locale, e.g. "$94,210.50" or "94.210,50 €"
+    pub fn price(&self, value: f64) -> String {
+        locale::current().money(value, 2, self.sign)
+    }
+
+    /// A price rounded to whole units, for levels in short messages
+    pub fn round(&self, value: f64) -> String {
+        locale::current().money(value, 0, self.sign)
+    }
+}
+
+/// The rate of USD itself, for prices that were never converted or stored before rates were
+pub fn unit_rate() -> f64 {
+    1.0
+}
+
+/// Price reports in this currency (one of CURRENCIES), instead of QUOTE_CURRENCY
+pub fn set_quote(code: &str) {
+    if let Some(currency) = Currency::named(code) {
+        let _ = QUOTE.set(currency);
+    }
+}
+
+/// The currency from --currency or QUOTE_CURRENCY, USD by default
+pub fn quote() -> Currency {
+    if let Some(currency) = QUOTE.get() {
+        return *currency;
+    }
+    match env::var("QUOTE_CURRENCY") {
+        Ok(code) => Currency::named(&code).unwrap_or_else(|| {
+            log::warn!("unknown QUOTE_CURRENCY '{}', using USD", code);
+            Currency::USD
+        }),
+        Err(_) => Currency::USD,
+    }
+}
+
+/// How many units of the quote currency one US dollar buys; 1 without fetching for USD
+pub async fn usd_rate() -> Result<f64, Box<dyn Error>> {
+    match quote() {
+        Currency::USD => Ok(1.0),
+        currency => Ok(data_fetcher::fetch_usd_rate(currency.code).await
+            .map_err(|e| format!("Could not fetch the USD/{} rate: {}", currency.code, e))?),
+    }
+}
+
+/// Convert fetched USD prices at `rate` into the quote currency; volumes stay in the base asset
+pub fn convert(data: &mut CryptoData, rate: f64) {
+    for (_, price) in &mut data.prices {
+        *price *= rate;
+    }
+    for candle in &mut data.ohlc_data {
+        candle.open *= rate;
+        candle.high *= rate;
+        candle.low *= rate;
+        candle.close *= rate;
+    }
+    data.currency = quote();
+    data.usd_rate = rate;
+}
+
+/// Price data fetched in USD, converted into the quote currency with the rate fetched alongside it
+pub async fn priced(data: impl Future<Output = Result<CryptoData, Box<dyn Error>>>) -> Result<CryptoData, Box<dyn Error>> {
+    let (mut data, rate) = tokio::try_join!(data, usd_rate())?;
+    convert(&mut data, rate);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_fetcher::Candle;
+
+    #[test]
+    fn currencies_are_named_in_any_case() {
+        assert_eq!(Currency::named("eur").map(|currency| currency.sign), Some("€"));
+        assert_eq!(Currency::named("THB").map(|currency| currency.sign), Some("฿"));
+        assert_eq!(Currency::named("JPY"), None);
+    }
+
+    #[test]
+    fn currencies_round_trip_as_their_code() {
+        let gbp = Currency::named("GBP").unwrap();
+        assert_eq!(serde_json::to_string(&gbp).unwrap(), "\"GBP\"");
+        assert_eq!(serde_json::from_str::<Currency>("\"GBP\"").unwrap(), gbp);
+        assert!(serde_json::from_str::<Currency>("\"XYZ\"").is_err());
+    }
+
+    #[test]
+    fn conversion_scales_prices_but_not_volumes() {
+        let mut data = CryptoData {
+            prices: vec![(0.0, 100000.0)],
+            volumes: vec![(0.0, 12.5)],
+            ohlc_data: vec![Candle { timestamp: 0.0, open: 100000.0, high: 101000.0, low: 99000.0, close: 100500.0, volume: 12.5 }],
+            currency: Currency::USD,
+            usd_rate: 1.0,
+        };
+        convert(&mut data, 0.5);
+
+        assert_eq!(data.prices[0].1, 50000.0);
+        let candle = data.ohlc_data[0];
+        assert_eq!((candle.open, candle.high, candle.low, candle.close), (50000.0, 50500.0, 49500.0, 50250.0));
+        assert_eq!(candle.volume, 12.5);
+        assert_eq!(data.volumes[0].1, 12.5);
+        assert_eq!(data.usd_rate, 0.5);
+    }
+}
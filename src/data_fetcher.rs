@@ -1,6 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use std::env;
 use std::error::Error;
+use std::sync::OnceLock;
+use std::time::Duration;
 use serde_json::Value;
+use crate::currency::Currency;
 
 // Structure for cryptocurrency price data
 #[derive(Debug, Deserialize, Clone)]
@@ -9,14 +14,43 @@ pub struct CryptoData {
     #[serde(default)]
     pub volumes: Vec<(f64, f64)>,        // Timestamp and volume pairs
     #[serde(default)]
-    pub high_prices: Vec<(f64, f64)>,    // Timestamp and high price pairs
-    #[serde(default)]
-    pub low_prices: Vec<(f64, f64)>,     // Timestamp and low price pairs
-    #[serde(default)]
-    #[allow(dead_code)]
-    pub open_prices: Vec<(f64, f64)>,    // Timestamp and open price pairs
-    #[serde(default)]
-    pub ohlc_data: Vec<(f64, f64, f64, f64, f64, f64)>, // Timestamp, open, high, low, close, volume
+    pub ohlc_data: Vec<Candle>,
+    #[serde(skip)]
+    pub currency: Currency,              // What the prices are in: USD(T) as fetched, until converted
+    #[serde(skip, default = "crate::currency::unit_rate")]
+    pub usd_rate: f64,                   // Units of `currency` per US dollar the prices were converted at
+}
+
+/// A single OHLCV candle
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct Candle {
+    pub timestamp: f64,     // Open time in milliseconds
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl CryptoData {
+    /// Candles for indicator calculations, synthesized from close prices when OHLC data is unavailable
+    pub fn candles(&self) -> Vec<Candle> {
+        if !self.ohlc_data.is_empty() {
+            return self.ohlc_data.clone();
+        }
+
+        self.prices.iter()
+            .enumerate()
+            .map(|(i, &(timestamp, price))| Candle {
+                timestamp,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: self.volumes.get(i).map(|(_, volume)| *volume).unwrap_or(0.0),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,28 +66,131 @@ pub struct FearGreedData {
     pub timestamp: String,
 }
 
+/// A headline from the news API (CryptoCompare's news response format)
+#[derive(Debug, Deserialize)]
+pub struct NewsArticle {
+    pub published_on: i64,      // Unix seconds
+    pub title: String,
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub source_info: NewsSource,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct NewsSource {
+    #[serde(default)]
+    pub name: String,
+}
+
+/// Network statistics over the last 24 hours, from blockchain.info
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct OnchainStats {
+    pub hash_rate: f64,                          // GH/s
+    pub difficulty: f64,
+    pub n_tx: f64,
+    pub n_blocks_mined: f64,
+    pub minutes_between_blocks: f64,
+    pub total_fees_btc: f64,                     // Satoshis
+    pub miners_revenue_usd: f64,
+    pub estimated_btc_sent: f64,                 // Satoshis
+    pub estimated_transaction_volume_usd: f64,
+    pub market_price_usd: f64,
+}
+
+/// Mempool backlog and recommended fee rates, from mempool.space
+#[derive(Debug, Deserialize)]
+pub struct MempoolStats {
+    pub count: u64,                              // Unconfirmed transactions
+    pub vsize: u64,                              // Virtual bytes waiting
+    pub fastest_fee: f64,                        // sat/vB
+    pub hour_fee: f64,
+    pub economy_fee: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewsResponse {
+    #[serde(rename = "Data", default)]
+    data: Vec<NewsArticle>,
+}
+
 #[derive(Debug, Deserialize)]
 struct FearGreedMetadata {
     error: Option<String>,
 }
 
-/// Fetch Bitcoin price data from Binance API
-async fn fetch_bitcoin_data(data_provider_api_key: &String, api_base_url: &String, days: u32) -> Result<CryptoData, Box<dyn Error>> {
-    // Calculate the start time (current time - days in milliseconds)
+/// HTTP client that gives up after DATA_TIMEOUT_SECONDS (default 30) instead of hanging on a stalled connection
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(data_timeout())
+        .build()
+        .unwrap_or_default()
+}
+
+fn data_timeout() -> Duration {
+    Duration::from_secs(env::var("DATA_TIMEOUT_SECONDS").ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(30))
+}
+
+/// Turn a timed-out request into a message naming the endpoint and the setting to raise
+fn request_error(url: &str, e: reqwest::Error) -> Box<dyn Error> {
+    if e.is_timeout() {
+        let endpoint = url.split('?').next().unwrap_or(url);
+        format!("{} did not respond within {}s (DATA_TIMEOUT_SECONDS)", endpoint, data_timeout().as_secs()).into()
+    } else {
+        e.into()
+    }
+}
+
+/// Binance candle intervals that can be analyzed, with their length in minutes
+pub const INTERVALS: [(&str, u64); 8] = [
+    ("15m", 15), ("30m", 30), ("1h", 60), ("2h", 120), ("4h", 240), ("6h", 360), ("12h", 720), ("1d", 1440),
+];
+
+/// Candles fetched for the analysis: four months of 4-hour candles
+const HISTORY_CANDLES: u64 = 1080;
+
+/// Set by --interval
+static INTERVAL: OnceLock<&'static str> = OnceLock::new();
+
+/// Use this candle interval (one of INTERVALS) for every fetch, instead of CANDLE_INTERVAL
+pub fn set_interval(interval: &str) {
+    if let Some((name, _)) = INTERVALS.iter().find(|(name, _)| *name == interval) {
+        let _ = INTERVAL.set(name);
+    }
+}
+
+/// The candle interval from --interval or CANDLE_INTERVAL, 4h by default, with its length in minutes
+pub fn candle_interval() -> (&'static str, u64) {
+    let name = INTERVAL.get().copied().map(str::to_string)
+        .or_else(|| env::var("CANDLE_INTERVAL").ok())
+        .unwrap_or_else(|| "4h".to_string());
+    INTERVALS.iter().find(|(known, _)| *known == name.trim()).copied().unwrap_or_else(|| {
+        log::warn!("unknown CANDLE_INTERVAL '{}', using 4h", name);
+        ("4h", 240)
+    })
+}
+
+/// Fetch price data for a symbol (e.g. BTCUSDT) from Binance API
+async fn fetch_symbol_data(data_provider_api_key: &String, api_base_url: &String, symbol: &str) -> Result<CryptoData, Box<dyn Error>> {
+    // The same number of candles whatever the interval, so the indicators have enough history
+    let (interval, minutes) = candle_interval();
     let end_time = chrono::Utc::now().timestamp_millis() as u64;
-    let start_time = end_time - (days as u64 * 24 * 60 * 60 * 1000);
+    let start_time = end_time - HISTORY_CANDLES * minutes * 60 * 1000;
     
-    println!("Fetching data from {} to {}", 
+    log::info!("Fetching data from {} to {}", 
         chrono::DateTime::<chrono::Utc>::from_timestamp((start_time / 1000) as i64, 0).unwrap().format("%Y-%m-%d %H:%M:%S"),
         chrono::DateTime::<chrono::Utc>::from_timestamp((end_time / 1000) as i64, 0).unwrap().format("%Y-%m-%d %H:%M:%S"));
     
-    // Binance API endpoint - BTCUSDT 4h candles with explicit limit
+    // Binance API endpoint with explicit limit
     let url = format!(
-        "{}/api/v3/klines?symbol=BTCUSDT&interval=4h&startTime={}&endTime={}&limit=1000",
-        api_base_url, start_time, end_time
+        "{}/api/v3/klines?symbol={}&interval={}&startTime={}&endTime={}&limit=1000",
+        api_base_url, symbol, interval, start_time, end_time
     );
     
-    let client = reqwest::Client::new();
+    let client = http_client();
     let mut request = client.get(&url);
     
     // Only add the API key header if it's provided and not empty
@@ -61,11 +198,11 @@ async fn fetch_bitcoin_data(data_provider_api_key: &String, api_base_url: &Strin
         request = request.header("x-api-key", data_provider_api_key);
     }
     
-    let response = request.send().await?;
+    let response = request.send().await.map_err(|e| request_error(&url, e))?;
     
     if response.status().is_success() {
         let klines: Vec<Vec<Value>> = response.json().await?;
-        println!("Retrieved {} candles in first request", klines.len());
+        log::debug!("Retrieved {} candles in first request", klines.len());
         
         // If we got the maximum number of candles (1000) and need more,
         // perform additional requests to get the complete dataset
@@ -74,55 +211,55 @@ async fn fetch_bitcoin_data(data_provider_api_key: &String, api_base_url: &Strin
         if all_klines.len() == 1000 {
             // We need to make additional requests
             // Get the timestamp of the last candle we received
-            if let Some(last_candle) = all_klines.last() {
-                if last_candle.len() > 6 {
-                    // Use the close time (index 6) of the last candle as the new startTime
-                    // Add 1 millisecond to avoid duplicating the last candle
-                    let mut new_start_time = parse_to_f64(&last_candle[6]) as u64 + 1;
+            if let Some(last_candle) = all_klines.last()
+                && last_candle.len() > 6 {
+                // Use the close time (index 6) of the last candle as the new startTime
+                // Add 1 millisecond to avoid duplicating the last candle
+                let mut new_start_time = parse_to_f64(&last_candle[6]) as u64 + 1;
+                
+                // Keep fetching until we reach the end time or get no new data
+                let mut request_count = 1;
+                while new_start_time < end_time {
+                    let pagination_url = format!(
+                        "{}/api/v3/klines?symbol={}&interval={}&startTime={}&endTime={}&limit=1000",
+                        api_base_url, symbol, interval, new_start_time, end_time
+                    );
+                    
+                    let pagination_response = client.get(&pagination_url)
+                        .header("x-api-key", data_provider_api_key)
+                        .send()
+                        .await
+                        .map_err(|e| request_error(&pagination_url, e))?;
                     
-                    // Keep fetching until we reach the end time or get no new data
-                    let mut request_count = 1;
-                    while new_start_time < end_time {
-                        let pagination_url = format!(
-                            "{}/api/v3/klines?symbol=BTCUSDT&interval=4h&startTime={}&endTime={}&limit=1000",
-                            api_base_url, new_start_time, end_time
-                        );
+                    if pagination_response.status().is_success() {
+                        let additional_klines: Vec<Vec<Value>> = pagination_response.json().await?;
+                        log::debug!("Pagination request {}: Retrieved {} additional candles", 
+                            request_count, additional_klines.len());
                         
-                        let pagination_response = client.get(&pagination_url)
-                            .header("x-api-key", data_provider_api_key)
-                            .send()
-                            .await?;
+                        // If we got no new data, break the loop
+                        if additional_klines.is_empty() {
+                            break;
+                        }
                         
-                        if pagination_response.status().is_success() {
-                            let additional_klines: Vec<Vec<Value>> = pagination_response.json().await?;
-                            println!("Pagination request {}: Retrieved {} additional candles", 
-                                request_count, additional_klines.len());
-                            
-                            // If we got no new data, break the loop
-                            if additional_klines.is_empty() {
-                                break;
-                            }
-                            
-                            // Update the start time for the next request
-                            if let Some(next_last_candle) = additional_klines.last() {
-                                if next_last_candle.len() > 6 {
-                                    new_start_time = parse_to_f64(&next_last_candle[6]) as u64 + 1;
-                                } else {
-                                    break; // Incomplete candle data
-                                }
+                        // Update the start time for the next request
+                        if let Some(next_last_candle) = additional_klines.last() {
+                            if next_last_candle.len() > 6 {
+                                new_start_time = parse_to_f64(&next_last_candle[6]) as u64 + 1;
                             } else {
-                                break; // No more candles
+                                break; // Incomplete candle data
                             }
-                            
-                            // Append the new data
-                            all_klines.extend(additional_klines);
-                            request_count += 1;
                         } else {
-                            // If request failed, just use what we have
-                            println!("Pagination request {} failed with status: {}", 
-                                request_count, pagination_response.status());
-                            break;
+                            break; // No more candles
                         }
+                        
+                        // Append the new data
+                        all_klines.extend(additional_klines);
+                        request_count += 1;
+                    } else {
+                        // If request failed, just use what we have
+                        log::warn!("Pagination request {} failed with status: {}", 
+                            request_count, pagination_response.status());
+                        break;
                     }
                 }
             }
@@ -130,7 +267,7 @@ async fn fetch_bitcoin_data(data_provider_api_key: &String, api_base_url: &Strin
         
         // Sort the data by timestamp to ensure chronological order
         all_klines.sort_by(|a, b| {
-            if a.len() > 0 && b.len() > 0 {
+            if !a.is_empty() && !b.is_empty() {
                 let time_a = parse_to_f64(&a[0]);
                 let time_b = parse_to_f64(&b[0]);
                 time_a.partial_cmp(&time_b).unwrap()
@@ -146,10 +283,10 @@ async fn fetch_bitcoin_data(data_provider_api_key: &String, api_base_url: &Strin
             let first_timestamp = data.prices.first().unwrap().0;
             let last_timestamp = data.prices.last().unwrap().0;
             
-            println!("Data retrieved from {} to {}", 
+            log::debug!("Data retrieved from {} to {}", 
                 chrono::DateTime::<chrono::Utc>::from_timestamp((first_timestamp / 1000.0) as i64, 0).unwrap().format("%Y-%m-%d %H:%M:%S"),
                 chrono::DateTime::<chrono::Utc>::from_timestamp((last_timestamp / 1000.0) as i64, 0).unwrap().format("%Y-%m-%d %H:%M:%S"));
-            println!("Total candles: {}", data.prices.len());
+            log::debug!("Total candles: {}", data.prices.len());
         }
         
         Ok(data)
@@ -171,9 +308,6 @@ fn parse_to_f64(value: &Value) -> f64 {
 fn convert_binance_data(klines: Vec<Vec<Value>>) -> CryptoData {
     let mut prices = Vec::new();
     let mut volumes = Vec::new();
-    let mut high_prices = Vec::new();
-    let mut low_prices = Vec::new();
-    let mut open_prices = Vec::new();
     let mut ohlc_data = Vec::new();
 
     for kline in klines {
@@ -190,28 +324,23 @@ fn convert_binance_data(klines: Vec<Vec<Value>>) -> CryptoData {
             // Store all the data
             prices.push((open_time, close));
             volumes.push((open_time, volume));
-            high_prices.push((open_time, high));
-            low_prices.push((open_time, low));
-            open_prices.push((open_time, open));
-            ohlc_data.push((open_time, open, high, low, close, volume));
+            ohlc_data.push(Candle { timestamp: open_time, open, high, low, close, volume });
         }
     }
 
     CryptoData {
         prices,
         volumes,
-        high_prices,
-        low_prices,
-        open_prices,
         ohlc_data,
+        currency: Currency::USD,
+        usd_rate: 1.0,
     }
 }
 
 async fn fetch_fear_greed_index(limit: i32) -> Result<FearGreedResponse, Box<dyn Error>> {
     // Fetch the Fear & Greed Index data from the API
     let url = format!("https://api.alternative.me/fng/?limit={}", limit);
-    let client = reqwest::Client::new();
-    let response = client.get(&url).send().await?;
+    let response = http_client().get(&url).send().await.map_err(|e| request_error(&url, e))?;
     
     if response.status().is_success() {
         let data: FearGreedResponse = response.json().await?;
@@ -225,8 +354,8 @@ pub async fn fetch_fear_greed_index_data() -> Result<Vec<FearGreedData>, Box<dyn
     // Fetch the latest Fear & Greed Index data
     match fetch_fear_greed_index(4).await {
         Ok(data) => {
-            if data.metadata.error.is_some() {
-                Err(format!("Error fetching Fear & Greed Index: {}", data.metadata.error.unwrap()).into())
+            if let Some(error) = data.metadata.error {
+                Err(format!("Error fetching Fear & Greed Index: {}", error).into())
             } else {
                 Ok(data.data)
             }
@@ -234,8 +363,142 @@ pub async fn fetch_fear_greed_index_data() -> Result<Vec<FearGreedData>, Box<dyn
         Err(e) => Err(format!("Error fetching Fear & Greed Index: {}", e).into()),
     }
 }
-/// Fetch Bitcoin price data for a 4-month period with 4-hour candles
+
+/// The Fear & Greed index, or no readings when it can't be fetched, so an outage leaves the section unavailable
+/// rather than failing the run
+pub async fn load_fear_greed() -> Vec<FearGreedData> {
+    fetch_fear_greed_index_data().await.unwrap_or_else(|e| {
+        log::warn!("could not fetch the Fear & Greed index, continuing without it: {}", e);
+        Vec::new()
+    })
+}
+
+/// Fetch the latest headlines from a CryptoCompare-compatible news endpoint
+pub async fn fetch_news(url: &str) -> Result<Vec<NewsArticle>, Box<dyn Error>> {
+    let response = http_client().get(url).send().await.map_err(|e| request_error(url, e))?;
+
+    if response.status().is_success() {
+        let news: NewsResponse = response.json().await?;
+        Ok(news.data)
+    } else {
+        Err(format!("API request failed with status: {}", response.status()).into())
+    }
+}
+
+/// Fetch Bitcoin network statistics
+pub async fn fetch_onchain_stats() -> Result<OnchainStats, Box<dyn Error>> {
+    let url = env::var("ONCHAIN_STATS_URL").unwrap_or_else(|_| "https://api.blockchain.info/stats".to_string());
+    let response = http_client().get(&url).send().await.map_err(|e| request_error(&url, e))?;
+
+    if response.status().is_success() {
+        Ok(response.json().await?)
+    } else {
+        Err(format!("API request failed with status: {}", response.status()).into())
+    }
+}
+
+/// Fetch the mempool size and recommended fees
+pub async fn fetch_mempool_stats() -> Result<MempoolStats, Box<dyn Error>> {
+    let base_url = env::var("MEMPOOL_API_URL").unwrap_or_else(|_| "https://mempool.space/api".to_string());
+    let get = |url: String| async move {
+        let response = http_client().get(&url).send().await.map_err(|e| request_error(&url, e))?;
+        if !response.status().is_success() {
+            return Err::<Value, Box<dyn Error>>(format!("API request failed with status: {}", response.status()).into());
+        }
+        Ok(response.json().await?)
+    };
+
+    let mempool = get(format!("{}/mempool", base_url)).await?;
+    let fees = get(format!("{}/v1/fees/recommended", base_url)).await?;
+    Ok(MempoolStats {
+        count: mempool["count"].as_u64().unwrap_or(0),
+        vsize: mempool["vsize"].as_u64().unwrap_or(0),
+        fastest_fee: parse_to_f64(&fees["fastestFee"]),
+        hour_fee: parse_to_f64(&fees["hourFee"]),
+        economy_fee: parse_to_f64(&fees["economyFee"]),
+    })
+}
+
+/// Daily closes for a Yahoo Finance symbol (e.g. ^GSPC) over the last three months, oldest first
+pub async fn fetch_daily_closes(symbol: &str) -> Result<Vec<(DateTime<Utc>, f64)>, Box<dyn Error>> {
+    let url = format!("https://query1.finance.yahoo.com/v8/finance/chart/{}?range=3mo&interval=1d", urlencoding::encode(symbol));
+    // Yahoo rejects requests without a browser-like user agent
+    let response = http_client().get(&url).header("User-Agent", "Mozilla/5.0").send().await.map_err(|e| request_error(&url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("API request failed with status: {}", response.status()).into());
+    }
+
+    let chart: Value = response.json().await?;
+    let result = &chart["chart"]["result"][0];
+    let timestamps = result["timestamp"].as_array().ok_or_else(|| format!("No data for {}", symbol))?;
+    let closes = result["indicators"]["quote"][0]["close"].as_array().ok_or_else(|| format!("No closes for {}", symbol))?;
+
+    // Holidays and the current session can have null closes
+    Ok(timestamps.iter().zip(closes)
+        .filter_map(|(timestamp, close)| Some((DateTime::from_timestamp(timestamp.as_i64()?, 0)?, close.as_f64()?)))
+        .collect())
+}
+
+/// Bitcoin's share of the total crypto market cap, from CoinGecko
+pub async fn fetch_btc_dominance() -> Result<f64, Box<dyn Error>> {
+    let url = "https://api.coingecko.com/api/v3/global";
+    let response = http_client().get(url).send().await.map_err(|e| request_error(url, e))?;
+
+    if response.status().is_success() {
+        let global: Value = response.json().await?;
+        global["data"]["market_cap_percentage"]["btc"].as_f64().ok_or_else(|| "No BTC dominance in the response".into())
+    } else {
+        Err(format!("API request failed with status: {}", response.status()).into())
+    }
+}
+
+/// Fetch how many units of `currency` (e.g. EUR) one US dollar buys, from FX_API_URL (a Frankfurter-compatible
+/// endpoint, default the ECB reference rates at api.frankfurter.app)
+pub async fn fetch_usd_rate(currency: &str) -> Result<f64, Box<dyn Error>> {
+    let base_url = env::var("FX_API_URL").unwrap_or_else(|_| "https://api.frankfurter.app/latest".to_string());
+    let url = format!("{}?from=USD&to={}", base_url, currency);
+    let response = http_client().get(&url).send().await.map_err(|e| request_error(&url, e))?;
+
+    if response.status().is_success() {
+        let rates: Value = response.json().await?;
+        rates["rates"][currency].as_f64().ok_or_else(|| format!("No USD/{} rate in the FX response", currency).into())
+    } else {
+        Err(format!("FX rate request failed with status: {}", response.status()).into())
+    }
+}
+
+/// Fetch the latest spot price for a Binance symbol such as ETHUSDT
+pub async fn fetch_spot_price(api_base_url: &str, symbol: &str) -> Result<f64, Box<dyn Error>> {
+    let url = format!("{}/api/v3/ticker/price?symbol={}", api_base_url, symbol);
+    let response = http_client().get(&url).send().await.map_err(|e| request_error(&url, e))?;
+
+    if response.status().is_success() {
+        let ticker: Value = response.json().await?;
+        Ok(parse_to_f64(&ticker["price"]))
+    } else {
+        Err(format!("API request failed with status: {}", response.status()).into())
+    }
+}
+
+/// The close of the one-minute candle opening at `time` on Binance; None when there is none yet
+pub async fn fetch_price_at(api_base_url: &str, symbol: &str, time: DateTime<Utc>) -> Result<Option<f64>, Box<dyn Error>> {
+    let url = format!("{}/api/v3/klines?symbol={}&interval=1m&startTime={}&limit=1", api_base_url, symbol, time.timestamp_millis());
+    let response = http_client().get(&url).send().await.map_err(|e| request_error(&url, e))?;
+
+    if response.status().is_success() {
+        let klines: Vec<Vec<Value>> = response.json().await?;
+        Ok(klines.first().and_then(|kline| kline.get(4)).map(parse_to_f64))
+    } else {
+        Err(format!("API request failed with status: {}", response.status()).into())
+    }
+}
+
+/// Fetch Bitcoin price data, by default four months of 4-hour candles
 pub async fn fetch_bitcoin_trading_data(data_provider_api_key: &String, api_base_url: &String) -> Result<CryptoData, Box<dyn Error>> {
-    // 4 months = 120 days
-    fetch_bitcoin_data(data_provider_api_key, api_base_url, 180).await
+    fetch_trading_data(data_provider_api_key, api_base_url, "BTCUSDT").await
+}
+
+/// Trading data for any Binance symbol, over the same window as Bitcoin
+pub async fn fetch_trading_data(data_provider_api_key: &String, api_base_url: &String, symbol: &str) -> Result<CryptoData, Box<dyn Error>> {
+    fetch_symbol_data(data_provider_api_key, api_base_url, symbol).await
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+use std::env;
+use serde_json::Value;
+use crate::history::Run;
+use crate::i18n;
+use crate::report::TechnicalReport;
+use crate::signals::Action;
+
+/// Crossovers reported when they flip between runs: indicator, fast line, slow line, and the message for each direction
+const CROSSOVERS: [(&str, &str, &str, &str, &str); 2] = [
+    ("sma", "SMA (50-period)", "SMA (200-period)", "Golden cross: SMA50 crossed above SMA200", "Death cross: SMA50 crossed below SMA200"),
+    ("macd", "MACD Line", "Signal Line", "MACD crossed above its signal line", "MACD crossed below its signal line"),
+];
+
+/// Whether analyses open with what changed since the previous run; DELTA_REPORT=false turns it off
+pub fn enabled() -> bool {
+    env::var("DELTA_REPORT").map(|value| value != "false").unwrap_or(true)
+}
+
+/// The values compared between runs. Both sides are read from the report's JSON, as the history stores it.
+struct Snapshot {
+    score: Option<f64>,
+    rsi: Option<f64>,
+    fear_greed: Option<(f64, String)>,
+    crossovers: Vec<Option<bool>>,     // Whether each fast line is above its slow line, in CROSSOVERS order
+}
+
+impl Snapshot {
+    fn new(report: &Value) -> Self {
+        let latest = |key: &str, name: &str| report["indicators"].as_array()?
+            .iter().find(|indicator| indicator["key"] == key)?
+            ["periods"].as_array()?.last()?
+            ["values"].as_array()?.iter().find(|value| value["name"] == name)?
+            ["value"].as_f64();
+        let fear_greed = &report["fear_greed"][0];
+
+        Snapshot {
+            score: report["composite"]["score"].as_f64(),
+            rsi: latest("rsi", "RSI"),
+            fear_greed: fear_greed["value"].as_f64().zip(fear_greed["classification"].as_str().map(str::to_string)),
+            crossovers: CROSSOVERS.iter()
+                .map(|(key, fast, slow, ..)| Some(latest(key, fast)? > latest(key, slow)?))
+                .collect(),
+        }
+    }
+}
+
+/// What moved since the previous run: price, composite score, RSI, new crossovers, sentiment and the call
+pub fn changes(previous: &Run, report: &TechnicalReport, action: Option<Action>) -> Vec<String> {
+    let before = Snapshot::new(&previous.report);
+    let now = Snapshot::new(&serde_json::to_value(report).unwrap_or_default());
+    let mut changes = Vec::new();
+
+    // The previous run may have been priced in another currency
+    let price = report.last_price();
+    let previous_price = previous.usd_price() * report.usd_rate;
+    if previous_price > 0.0 {
+        changes.push(format!("{}: {} -> {} ({:+.2}%)", i18n::t("Price"), report.currency.price(previous_price), report.currency.price(price),
+            (price - previous_price) / previous_price * 100.0));
+    }
+    if let (Some(before), Some(now)) = (before.score, now.score) {
+        changes.push(format!("{}: {:+.1} -> {:+.1} ({:+.1})", i18n::t("Composite score"), before, now, now - before));
+    }
+    if let (Some(before), Some(now)) = (before.rsi, now.rsi) {
+        changes.push(format!("RSI: {:.1} -> {:.1} ({:+.1})", before, now, now - before));
+    }
+
+    for ((_, _, _, above, below), (was, is)) in CROSSOVERS.iter().zip(before.crossovers.iter().zip(&now.crossovers)) {
+        if let (Some(was), Some(is)) = (was, is)
+            && was != is {
+            changes.push(i18n::tf("New crossover: {crossover}", &[("crossover", &i18n::t(if *is { above } else { below }))]));
+        }
+    }
+
+    if let (Some((before_value, before_label)), Some((value, label))) = (&before.fear_greed, &now.fear_greed) {
+        if before_label != label {
+            changes.push(i18n::tf("Sentiment shifted: {before} ({before_value}) -> {now} ({value})", &[
+                ("before", before_label), ("before_value", &format!("{:.0}", before_value)), ("now", label), ("value", &format!("{:.0}", value)),
+            ]));
+        } else if before_value != value {
+            changes.push(i18n::tf("Sentiment: still {label}, {before} -> {now}", &[
+                ("label", label), ("before", &format!("{:.0}", before_value)), ("now", &format!("{:.0}", value)),
+            ]));
+        }
+    }
+
+    let action = action.map(|action| action.as_str());
+    match (previous.action.as_deref(), action) {
+        (Some(before), Some(now)) if before != now => changes.push(i18n::tf("Recommendation changed: {before} -> {now}", &[("before", &before), ("now", &now)])),
+        (Some(before), Some(_)) => changes.push(i18n::tf("Recommendation unchanged: {call}", &[("call", &before)])),
+        (None, Some(now)) => changes.push(i18n::tf("Recommendation: {call} (no call last time)", &[("call", &now)])),
+        (Some(before), None) => changes.push(i18n::tf("No call this time (was {call})", &[("call", &before)])),
+        (None, None) => {}
+    }
+
+    changes
+}
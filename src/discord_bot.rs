@@ -0,0 +1,266 @@
+use std::env;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use futures::{SinkExt, StreamExt};
+use reqwest::Client;
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::Message;
+use crate::currency::Currency;
+use crate::data_fetcher;
+use crate::html;
+
+const API: &str = "https://discord.com/api/v10";
+
+/// Longest message Discord accepts
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// What every interaction handler needs
+struct Bot {
+    client: Client,
+    token: String,
+    application_id: String,
+    channels: Vec<String>,   // DISCORD_CHANNELS; empty means every channel the bot is in
+    api_base_url: String,
+}
+
+impl Bot {
+    fn auth(&self) -> String {
+        format!("Bot {}", self.token)
+    }
+}
+
+/// Register the /analyze, /signals and /price slash commands and answer them over the Discord gateway,
+/// reconnecting whenever the connection drops
+pub async fn run_bot(api_base_url: &str) -> Result<(), Box<dyn Error>> {
+    let token = env::var("DISCORD_BOT_TOKEN").map_err(|_| "DISCORD_BOT_TOKEN must be set for the `bot discord` command")?;
+    let client = Client::new();
+    let application: Value = client.get(format!("{}/applications/@me", API))
+        .header("Authorization", format!("Bot {}", token))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let application_id = application["id"].as_str()
+        .ok_or_else(|| format!("Discord rejected the bot token: {}", application["message"]))?
+        .to_string();
+    let bot = Arc::new(Bot {
+        client,
+        token,
+        application_id,
+        channels: env::var("DISCORD_CHANNELS").unwrap_or_default()
+            .split(',')
+            .map(|channel| channel.trim().to_string())
+            .filter(|channel| !channel.is_empty())
+            .collect(),
+        api_base_url: api_base_url.to_string(),
+    });
+    register_commands(&bot).await?;
+
+    loop {
+        if let Err(e) = run_session(&bot).await {
+            log::warn!("Discord gateway connection lost ({}), reconnecting in 5s", e);
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+/// Replace the bot's slash commands; with DISCORD_GUILD_ID they are registered for that server only,
+/// which takes effect immediately, otherwise globally
+async fn register_commands(bot: &Bot) -> Result<(), Box<dyn Error>> {
+    let commands = json!([
+        {
+            "name": "analyze",
+            "description": "Run the AI market analysis",
+            "options": [{ "type": 3, "name": "question", "description": "A question for the analysis to answer", "required": false }],
+        },
+        { "name": "signals", "description": "Rule-based signal with entry, stop and targets" },
+        {
+            "name": "price",
+            "description": "Latest spot price",
+            "options": [{ "type": 3, "name": "symbol", "description": "Binance symbol (default BTCUSDT)", "required": false }],
+        },
+    ]);
+    let url = match env::var("DISCORD_GUILD_ID") {
+        Ok(guild) => format!("{}/applications/{}/guilds/{}/commands", API, bot.application_id, guild),
+        Err(_) => format!("{}/applications/{}/commands", API, bot.application_id),
+    };
+    let response = bot.client.put(&url).header("Authorization", bot.auth()).json(&commands).send().await?;
+    if !response.status().is_success() {
+        return Err(format!("Could not register Discord commands: {}", response.text().await?).into());
+    }
+    log::info!("Registered Discord commands /analyze, /signals and /price");
+    Ok(())
+}
+
+/// One gateway connection: identify, keep the heartbeat and hand each slash command to its own task
+async fn run_session(bot: &Arc<Bot>) -> Result<(), Box<dyn Error>> {
+    let gateway: Value = bot.client.get(format!("{}/gateway/bot", API))
+        .header("Authorization", bot.auth())
+        .send()
+        .await?
+        .json()
+        .await?;
+    let url = gateway["url"].as_str().ok_or_else(|| format!("No Discord gateway URL: {}", gateway["message"]))?;
+    let (socket, _) = tokio_tungstenite::connect_async(format!("{}/?v=10&encoding=json", url)).await?;
+    let (mut write, mut read) = socket.split();
+
+    // The gateway opens with Hello, which sets the heartbeat interval
+    let interval = loop {
+        let Some(message) = read.next().await else { return Err("Gateway closed before Hello".into()) };
+        if let Message::Text(text) = message? {
+            let payload: Value = serde_json::from_str(&text)?;
+            if let Some(interval) = payload["d"]["heartbeat_interval"].as_u64().filter(|_| payload["op"] == 10) {
+                break interval;
+            }
+        }
+    };
+    let identify = json!({
+        "op": 2,
+        "d": {
+            "token": bot.token,
+            "intents": 0,   // Interactions arrive without any gateway intents
+            "properties": { "os": env::consts::OS, "browser": "crypto-forecast", "device": "crypto-forecast" },
+        },
+    });
+    write.send(Message::Text(identify.to_string())).await?;
+    log::info!("Discord bot connected, waiting for slash commands...");
+
+    let mut heartbeat = tokio::time::interval(Duration::from_millis(interval));
+    let mut sequence: Option<i64> = None;
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                write.send(Message::Text(json!({ "op": 1, "d": sequence }).to_string())).await?;
+            }
+            message = read.next() => {
+                let payload: Value = match message.ok_or("Gateway closed the connection")?? {
+                    Message::Text(text) => serde_json::from_str(&text)?,
+                    Message::Close(_) => return Err("Gateway closed the connection".into()),
+                    _ => continue,
+                };
+                if let Some(number) = payload["s"].as_i64() {
+                    sequence = Some(number);
+                }
+                match payload["op"].as_i64() {
+                    Some(0) if payload["t"] == "INTERACTION_CREATE" => {
+                        let bot = Arc::clone(bot);
+                        tokio::spawn(async move {
+                            if let Err(e) = answer(&bot, &payload["d"]).await {
+                                log::warn!("could not answer Discord command: {}", e);
+                            }
+                        });
+                    }
+                    Some(1) => write.send(Message::Text(json!({ "op": 1, "d": sequence }).to_string())).await?,
+                    Some(7) | Some(9) => return Err("Gateway asked to reconnect".into()),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Acknowledge the command at once (Discord allows three seconds), then edit in the result when it is ready
+async fn answer(bot: &Bot, interaction: &Value) -> Result<(), reqwest::Error> {
+    let id = interaction["id"].as_str().unwrap_or_default();
+    let token = interaction["token"].as_str().unwrap_or_default();
+    bot.client.post(format!("{}/interactions/{}/{}/callback", API, id, token))
+        .json(&json!({ "type": 5 }))
+        .send()
+        .await?;
+
+    let channel = interaction["channel_id"].as_str().unwrap_or_default();
+    let reply = if !bot.channels.is_empty() && !bot.channels.iter().any(|allowed| allowed == channel) {
+        "This channel is not in DISCORD_CHANNELS".to_string()
+    } else {
+        run_command(bot, &interaction["data"]).await
+    };
+
+    // The first part replaces the "thinking" placeholder; any others follow as new messages
+    let webhook = format!("{}/webhooks/{}/{}", API, bot.application_id, token);
+    for (i, part) in split_message(&reply).iter().enumerate() {
+        let request = if i == 0 {
+            bot.client.patch(format!("{}/messages/@original", webhook))
+        } else {
+            bot.client.post(&webhook)
+        };
+        request.json(&json!({ "content": part })).send().await?;
+    }
+    Ok(())
+}
+
+/// The command's reply, or the reason it failed
+async fn run_command(bot: &Bot, data: &Value) -> String {
+    let option = |name: &str| data["options"].as_array()
+        .and_then(|options| options.iter().find(|option| option["name"] == name))
+        .and_then(|option| option["value"].as_str())
+        .map(str::to_string);
+
+    match data["name"].as_str().unwrap_or_default() {
+        "price" => {
+            let symbol = option("symbol").unwrap_or_else(|| "BTCUSDT".to_string()).to_uppercase();
+            match data_fetcher::fetch_spot_price(&bot.api_base_url, &symbol).await {
+                Ok(price) => format!("**{}**: {}", symbol, Currency::USD.price(price)),
+                Err(e) => format!("Could not fetch the {} price: {}", symbol, e),
+            }
+        }
+        "signals" => run_self(&["--only-signals".to_string()]).await,
+        "analyze" => {
+            let mut args = Vec::new();
+            if let Some(question) = option("question") {
+                args.extend(["--ask".to_string(), question]);
+            }
+            args.push("text".to_string());
+            run_self(&args).await
+        }
+        other => format!("Unknown command /{}", other),
+    }
+}
+
+/// Run this program with the given arguments and return what it printed; outputs.toml is ignored so the result
+/// comes back here rather than going to the configured destinations
+async fn run_self(args: &[String]) -> String {
+    let executable = match env::current_exe() {
+        Ok(executable) => executable,
+        Err(e) => return format!("Could not start the analysis: {}", e),
+    };
+    let output = tokio::process::Command::new(executable)
+        .args(args)
+        .env("OUTPUTS_FILE", "")
+        .env("AI_STREAM", "false")
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => html::strip_bare_tags(String::from_utf8_lossy(&output.stdout).trim()),
+        Ok(output) => {
+            let errors = String::from_utf8_lossy(&output.stderr);
+            format!("The run failed: {}", errors.lines().last().unwrap_or("no error output"))
+        }
+        Err(e) => format!("Could not start the analysis: {}", e),
+    }
+}
+
+/// Split text into messages Discord accepts, between lines where possible
+fn split_message(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        if current.chars().count() + line.chars().count() + 1 > MAX_MESSAGE_CHARS && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        // A single line longer than a message is cut into pieces
+        let mut line: Vec<char> = line.chars().collect();
+        while line.len() > MAX_MESSAGE_CHARS {
+            parts.push(line.drain(..MAX_MESSAGE_CHARS).collect());
+        }
+        current.push_str(&line.into_iter().collect::<String>());
+        current.push('\n');
+    }
+    if !current.trim().is_empty() {
+        parts.push(current);
+    }
+    if parts.is_empty() {
+        parts.push("(no output)".to_string());
+    }
+    parts
+}
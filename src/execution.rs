@@ -0,0 +1,147 @@
+use std::env;
+use std::error::Error;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use crate::currency::Currency;
+use crate::journal;
+use crate::signals::{Action, TradeSignal};
+
+/// Orders go to the Binance spot testnet unless EXECUTION_BASE_URL says otherwise
+const TESTNET_BASE_URL: &str = "https://testnet.binance.vision";
+
+/// Opt-in order execution settings, read from EXECUTION_* environment variables
+pub struct ExecutionConfig {
+    pub live: bool,                // Orders are only sent with EXECUTION_LIVE=true; otherwise dry run
+    pub base_url: String,
+    pub symbol: String,
+    pub api_key: String,
+    pub api_secret: String,
+    pub order_usdt: f64,           // Notional per order
+    pub max_order_usdt: f64,       // Hard cap, never exceeded regardless of order_usdt
+    pub min_score: f64,            // Minimum |composite score| for a signal to count as confirmed
+}
+
+impl ExecutionConfig {
+    pub fn from_env() -> Self {
+        let env_f64 = |name: &str, default: f64| env::var(name).ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(default);
+
+        ExecutionConfig {
+            live: env::var("EXECUTION_LIVE").map(|value| value == "true").unwrap_or(false),
+            base_url: env::var("EXECUTION_BASE_URL").unwrap_or_else(|_| TESTNET_BASE_URL.to_string()),
+            symbol: env::var("EXECUTION_SYMBOL").unwrap_or_else(|_| "BTCUSDT".to_string()),
+            api_key: env::var("BINANCE_API_KEY").unwrap_or_default(),
+            api_secret: env::var("BINANCE_API_SECRET").unwrap_or_default(),
+            order_usdt: env_f64("EXECUTION_ORDER_USDT", 50.0),
+            max_order_usdt: env_f64("EXECUTION_MAX_ORDER_USDT", 100.0),
+            min_score: env_f64("EXECUTION_MIN_SCORE", 30.0),
+        }
+    }
+
+    /// Refuse configurations that could place unintended real orders
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.base_url != TESTNET_BASE_URL && env::var("EXECUTION_ALLOW_MAINNET").map(|value| value != "true").unwrap_or(true) {
+            return Err("Refusing to trade outside the testnet without EXECUTION_ALLOW_MAINNET=true".into());
+        }
+        if self.api_key.is_empty() || self.api_secret.is_empty() {
+            return Err("BINANCE_API_KEY and BINANCE_API_SECRET must be set for live execution".into());
+        }
+        if self.order_usdt <= 0.0 || self.max_order_usdt <= 0.0 {
+            return Err("EXECUTION_ORDER_USDT and EXECUTION_MAX_ORDER_USDT must be positive".into());
+        }
+        Ok(())
+    }
+}
+
+/// Place a market order for a confirmed signal; dry run unless live execution is enabled
+pub async fn execute_signal(signal: &TradeSignal, config: &ExecutionConfig) -> Result<(), Box<dyn Error>> {
+    let side = match signal.action {
+        Action::Buy => "BUY",
+        Action::Sell => "SELL",
+        Action::Hold => {
+            log::info!("Execution: HOLD signal, no order placed");
+            return Ok(());
+        }
+    };
+
+    if signal.score.abs() < config.min_score {
+        log::info!("Execution: signal not confirmed (|score| {:.1} < {:.1}), no order placed", signal.score.abs(), config.min_score);
+        return Ok(());
+    }
+
+    // Orders are sized in USDT, so the signal's levels must be too
+    if signal.currency != Currency::USD {
+        return Err(format!("Execution needs prices in USD, not {}; unset QUOTE_CURRENCY or --currency", signal.currency.code).into());
+    }
+
+    let notional = config.order_usdt.min(config.max_order_usdt);
+    let quantity = notional / signal.entry;
+
+    // Buys spend a fixed quote amount; sells size the base quantity from the entry price
+    let order = match signal.action {
+        Action::Buy => format!("symbol={}&side={}&type=MARKET&quoteOrderQty={:.2}", config.symbol, side, notional),
+        _ => format!("symbol={}&side={}&type=MARKET&quantity={:.5}", config.symbol, side, quantity),
+    };
+
+    if !config.live {
+        log::info!("Execution (dry run): would place {} {} for ~${:.2} ({:.5} @ ${:.2}) on {}",
+            side, config.symbol, notional, quantity, signal.entry, config.base_url);
+        return Ok(());
+    }
+
+    config.validate()?;
+
+    let query = format!("{}&timestamp={}&recvWindow=5000", order, chrono::Utc::now().timestamp_millis());
+    let url = format!("{}/api/v3/order?{}&signature={}", config.base_url, query, sign(&query, &config.api_secret)?);
+
+    let response = reqwest::Client::new()
+        .post(&url)
+        .header("X-MBX-APIKEY", &config.api_key)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let body: Value = response.json().await?;
+    if !status.is_success() {
+        return Err(format!("Order rejected with status {}: {}", status, body).into());
+    }
+
+    log::info!("Execution: {} order placed on {} (order id {}, status {})",
+        side, config.symbol, body["orderId"], body["status"].as_str().unwrap_or("unknown"));
+
+    record_fill(signal, &body)
+}
+
+/// Journal the filled order: buys open a long, sells close the most recent open long
+fn record_fill(signal: &TradeSignal, body: &Value) -> Result<(), Box<dyn Error>> {
+    let number = |field: &str| body[field].as_str().and_then(|value| value.parse::<f64>().ok()).unwrap_or(0.0);
+    let filled = number("executedQty");
+    if filled <= 0.0 {
+        return Ok(());
+    }
+    let price = number("cummulativeQuoteQty") / filled;
+
+    let connection = journal::open()?;
+    match signal.action {
+        Action::Buy => {
+            let id = journal::open_trade(&connection, "long", price, filled, &signal.reasons.join("; "))?;
+            log::info!("Execution: journaled as trade #{}", id);
+        }
+        _ => {
+            if let Some(id) = journal::latest_open(&connection, "long")? {
+                let pnl = journal::close_trade(&connection, id, price)?;
+                log::info!("Execution: closed journal trade #{} (PnL ${:.2})", id, pnl);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// HMAC-SHA256 signature of the query string, hex encoded as Binance expects
+fn sign(query: &str, secret: &str) -> Result<String, Box<dyn Error>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(query.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
@@ -0,0 +1,173 @@
+use std::env;
+use std::error::Error;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use crate::ai_client::Analysis;
+use crate::cli::HistoryCommand;
+use crate::currency::Currency;
+use crate::data_fetcher;
+use crate::renderer;
+use crate::report::TechnicalReport;
+
+/// Default location of the run history database
+const DEFAULT_HISTORY_DB: &str = "history.db";
+
+/// One stored analysis run
+#[derive(Serialize)]
+pub struct Run {
+    pub id: i64,
+    pub run_at: DateTime<Utc>,
+    pub symbol: String,
+    pub interval: String,
+    pub prompt_version: String,
+    pub providers: String,                          // Comma-separated backend names
+    pub prompt_hash: String,                        // SHA-256 of the prompt, to spot runs that asked the same
+    pub price: f64,                                 // Last close, in `currency`
+    pub currency: Currency,
+    pub usd_rate: f64,                              // Units of `currency` per US dollar at the time of the run
+    pub action: Option<String>,                     // BUY, SELL or HOLD when the model made a call
+    pub result: Option<serde_json::Value>,          // The structured conclusions, when the backend returned them
+    pub recommendation: Option<serde_json::Value>,  // The recommendation block, when the reply ended with one
+    pub report: serde_json::Value,                  // The technical report the prompt was built from, as JSON
+    pub analysis: String,                           // The delivered text; the rule-based summary when the model failed
+}
+
+impl Run {
+    /// The price in US dollars, to compare with USDT prices or runs priced in another currency
+    pub fn usd_price(&self) -> f64 {
+        self.price / self.usd_rate
+    }
+}
+
+/// What the pipeline knows about a finished run, for `record`
+pub struct RunInput<'a> {
+    pub symbol: &'a str,
+    pub prompt_version: &'a str,
+    pub providers: Vec<&'a str>,
+    pub prompt: &'a str,
+    pub report: &'a TechnicalReport,
+    pub analysis: Option<&'a Analysis>,
+    pub text: &'a str,
+}
+
+/// Path of the history database, from HISTORY_DB (default history.db)
+fn history_path() -> String {
+    env::var("HISTORY_DB").unwrap_or_else(|_| DEFAULT_HISTORY_DB.to_string())
+}
+
+/// Whether runs are stored; HISTORY=false turns it off
+pub fn enabled() -> bool {
+    env::var("HISTORY").map(|value| value != "false").unwrap_or(true)
+}
+
+/// Open the history, creating its table on first use
+pub fn open() -> Result<Connection, Box<dyn Error>> {
+    let connection = Connection::open(history_path())?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_at TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            interval TEXT NOT NULL,
+            prompt_version TEXT NOT NULL,
+            providers TEXT NOT NULL,
+            prompt_hash TEXT NOT NULL,
+            price REAL NOT NULL,
+            action TEXT,
+            result TEXT,
+            recommendation TEXT,
+            report TEXT NOT NULL,
+            analysis TEXT NOT NULL,
+            currency TEXT NOT NULL DEFAULT 'USD',
+            usd_rate REAL NOT NULL DEFAULT 1.0
+        )",
+        [],
+    )?;
+    Ok(connection)
+}
+
+/// Store a run and return its id
+pub fn record(connection: &Connection, run: &RunInput) -> Result<i64, Box<dyn Error>> {
+    let analysis = run.analysis;
+    let result = analysis.and_then(|analysis| analysis.result.as_ref()).map(serde_json::to_string).transpose()?;
+    let recommendation = analysis.and_then(|analysis| analysis.recommendation.as_ref()).map(serde_json::to_string).transpose()?;
+    connection.execute(
+        "INSERT INTO runs (run_at, symbol, interval, prompt_version, providers, prompt_hash, price, action, result, recommendation, report, analysis, currency, usd_rate)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        params![Utc::now(), run.symbol, data_fetcher::candle_interval().0, run.prompt_version, run.providers.join(","),
+            hex::encode(Sha256::digest(run.prompt)), run.report.last_price(),
+            analysis.and_then(|analysis| analysis.action()).map(|action| action.as_str()),
+            result, recommendation, renderer::render_json(run.report)?, run.text, run.report.currency.code, run.report.usd_rate],
+    )?;
+    Ok(connection.last_insert_rowid())
+}
+
+const COLUMNS: &str = "id, run_at, symbol, interval, prompt_version, providers, prompt_hash, price, action, result, recommendation, report, analysis, \
+    currency, usd_rate";
+
+fn read_run(row: &rusqlite::Row) -> rusqlite::Result<Run> {
+    let json = |index: usize| -> rusqlite::Result<Option<serde_json::Value>> {
+        Ok(row.get::<_, Option<String>>(index)?.and_then(|text| serde_json::from_str(&text).ok()))
+    };
+    Ok(Run {
+        id: row.get(0)?,
+        run_at: row.get(1)?,
+        symbol: row.get(2)?,
+        interval: row.get(3)?,
+        prompt_version: row.get(4)?,
+        providers: row.get(5)?,
+        prompt_hash: row.get(6)?,
+        price: row.get(7)?,
+        currency: Currency::named(&row.get::<_, String>(13)?).unwrap_or_default(),
+        usd_rate: row.get(14)?,
+        action: row.get(8)?,
+        result: json(9)?,
+        recommendation: json(10)?,
+        report: json(11)?.unwrap_or_default(),
+        analysis: row.get(12)?,
+    })
+}
+
+/// The most recent runs, newest first, optionally only for one symbol
+pub fn recent(connection: &Connection, symbol: Option<&str>, limit: usize) -> Result<Vec<Run>, Box<dyn Error>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT {} FROM runs WHERE ?1 IS NULL OR symbol = ?1 ORDER BY id DESC LIMIT ?2", COLUMNS))?;
+    let rows = statement.query_map(params![symbol, limit as i64], read_run)?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// The latest run for the symbol at the interval, to compare the next one against
+pub fn previous(connection: &Connection, symbol: &str, interval: &str) -> Result<Option<Run>, Box<dyn Error>> {
+    Ok(connection.query_row(&format!("SELECT {} FROM runs WHERE symbol = ?1 AND interval = ?2 ORDER BY id DESC LIMIT 1", COLUMNS),
+        params![symbol, interval], read_run).optional()?)
+}
+
+pub fn get(connection: &Connection, id: i64) -> Result<Option<Run>, Box<dyn Error>> {
+    Ok(connection.query_row(&format!("SELECT {} FROM runs WHERE id = ?1", COLUMNS), params![id], read_run).optional()?)
+}
+
+/// Handle `history list` and `history show <id>` and print the result
+pub fn run_command(command: &HistoryCommand, json_only: bool) -> Result<(), Box<dyn Error>> {
+    let connection = open()?;
+    match command {
+        HistoryCommand::List { symbol, limit } => {
+            let runs = recent(&connection, symbol.as_deref(), *limit)?;
+            if json_only {
+                println!("{}", serde_json::to_string_pretty(&runs)?);
+            } else {
+                println!("{}", renderer::render_history(&runs));
+            }
+        }
+        HistoryCommand::Show { id } => {
+            let run = get(&connection, *id)?.ok_or_else(|| format!("No run with id {}", id))?;
+            if json_only {
+                println!("{}", serde_json::to_string_pretty(&run)?);
+            } else {
+                println!("{}", renderer::render_history_run(&run));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use chrono::Utc;
+use pulldown_cmark::{Event, Options, Parser};
+use crate::chart::Chart;
+use crate::i18n;
+use crate::renderer;
+use crate::report::TechnicalReport;
+
+/// Styles inlined into every report so the file opens anywhere without other assets
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Roboto, sans-serif; background: #131722; color: #d1d4dc; margin: 0; line-height: 1.5; }
+main { max-width: 960px; margin: 0 auto; padding: 24px; }
+h1, h2, h3 { color: #ffffff; }
+h2 { border-bottom: 1px solid #363c4e; padding-bottom: 4px; margin-top: 32px; }
+table { border-collapse: collapse; width: 100%; margin: 12px 0; font-size: 14px; }
+th, td { border: 1px solid #363c4e; padding: 6px 10px; text-align: left; vertical-align: top; }
+th { background: #1e222d; }
+.summary { display: flex; gap: 16px; flex-wrap: wrap; }
+.summary div { background: #1e222d; border-radius: 6px; padding: 12px 16px; }
+.summary span { display: block; font-size: 12px; color: #787b86; }
+.bullish { color: #26a69a; }
+.bearish { color: #ef5350; }
+svg { max-width: 100%; border-radius: 6px; }
+figcaption { font-size: 12px; color: #787b86; }
+pre, code { background: #1e222d; border-radius: 4px; }
+pre { padding: 12px; overflow-x: auto; }
+";
+
+/// A styled single-file HTML report: summary, optional chart, the analysis as formatted Markdown,
+/// and tables for the indicators and the Fear & Greed history
+pub fn render_report(symbol: &str, analysis: &str, report: Option<&TechnicalReport>, chart: Option<&Chart>) -> String {
+    let generated_at = report.map(|report| report.generated_at).unwrap_or_else(Utc::now);
+    let mut body = format!("<h1>{} Analysis</h1>\n<p>Generated {}</p>\n", escape(symbol), generated_at.format("%Y-%m-%d %H:%M UTC"));
+
+    if let Some(report) = report {
+        body.push_str(&render_summary(report));
+    }
+    if let Some(chart) = chart {
+        body.push_str(&format!("<figure>{}<figcaption>{}</figcaption></figure>\n", chart.svg, escape(&chart.caption)));
+    }
+
+    body.push_str("<h2>Analysis</h2>\n");
+    body.push_str(&render_markdown(analysis));
+
+    if let Some(report) = report {
+        body.push_str(&render_indicators(report));
+        body.push_str(&render_fear_greed(report));
+    }
+
+    format!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+        <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{} Analysis - {}</title>\n\
+        <style>{}</style>\n</head>\n<body>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(symbol), generated_at.format("%Y-%m-%d %H:%M UTC"), STYLE, body)
+}
+
+fn render_summary(report: &TechnicalReport) -> String {
+    let composite = &report.composite;
+    format!("<div class=\"summary\">\n\
+        <div><span>Price</span>{}</div>\n\
+        <div><span>Composite score</span><b class=\"{}\">{:+.1}</b> ({})</div>\n\
+        <div><span>Support</span>{}</div>\n\
+        <div><span>Resistance</span>{}</div>\n\
+        </div>\n",
+        report.currency.price(report.last_price()), signal_class(composite.score), composite.score, composite.label(),
+        report.currency.price(report.levels.support), report.currency.price(report.levels.resistance))
+}
+
+/// The latest value of each indicator line, with its indication and directional reading
+fn render_indicators(report: &TechnicalReport) -> String {
+    let mut result = String::from("<h2>Indicators</h2>\n<table>\n<tr><th>Indicator</th><th>Latest values</th><th>Indication</th><th>Signal</th></tr>\n");
+    for indicator in &report.indicators {
+        let Some(period) = indicator.periods.last() else { continue };
+        let values: Vec<String> = period.values.iter()
+            .map(|value| format!("{}: {}", escape(i18n::term(&value.name)), renderer::format_value(value.value, value.unit, report.currency)))
+            .collect();
+        let signal = match &indicator.interpretation {
+            Some(interpretation) => format!("<span class=\"{}\">{:+.2}</span> {}",
+                signal_class(interpretation.signal), interpretation.signal, escape(&interpretation.reason)),
+            None => String::new(),
+        };
+        result.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&indicator.title), values.join("<br>"), escape(period.indication.as_deref().unwrap_or_default()), signal));
+    }
+    result.push_str("</table>\n");
+    result
+}
+
+fn render_fear_greed(report: &TechnicalReport) -> String {
+    if report.fear_greed.is_empty() {
+        return String::new();
+    }
+    let mut result = String::from("<h2>Fear &amp; Greed Index</h2>\n<table>\n<tr><th>Date</th><th>Value</th><th>Classification</th></tr>\n");
+    for reading in &report.fear_greed {
+        result.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            reading.date.format("%Y-%m-%d"), reading.value, escape(&reading.classification)));
+    }
+    result.push_str("</table>\n");
+    result
+}
+
+/// Markdown to HTML. Lines holding only a tag (e.g. `<bitcoin_market_analysis>`) are dropped and any other
+/// raw HTML in the model's reply is shown as text rather than interpreted.
+pub fn render_markdown(markdown: &str) -> String {
+    let markdown = strip_bare_tags(markdown);
+    let events = Parser::new_ext(&markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH)
+        .map(|event| match event {
+            Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+            event => event,
+        });
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events);
+    html
+}
+
+/// The text without lines holding only a tag, which Markdown renderers would treat as the start of an HTML block
+pub fn strip_bare_tags(markdown: &str) -> String {
+    markdown.lines()
+        .filter(|line| !is_bare_tag(line.trim()))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn is_bare_tag(line: &str) -> bool {
+    line.strip_prefix('<')
+        .and_then(|rest| rest.strip_suffix('>'))
+        .map(|name| name.strip_prefix('/').unwrap_or(name))
+        .is_some_and(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+}
+
+fn signal_class(value: f64) -> &'static str {
+    if value > 0.0 { "bullish" } else if value < 0.0 { "bearish" } else { "" }
+}
+
+/// Escape text for HTML and XML
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use serde::Deserialize;
+
+/// Catalogs shipped with the binary, used when messages/<lang>.toml is absent
+const BUILTIN_CATALOGS: [(&str, &str); 1] = [
+    ("de", include_str!("../messages/de.toml")),
+];
+
+/// Directory holding the message catalogs when MESSAGES_DIR is not set
+const DEFAULT_MESSAGES_DIR: &str = "messages";
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// A translation of the report's fixed text. As with gettext, the English text is the message id, so a message
+/// missing from the catalog is shown in English.
+#[derive(Default, Deserialize)]
+struct Catalog {
+    language: Option<String>,      // English name of the language, for telling the model what to write in
+    #[serde(default)]
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// messages/<lang>.toml (or under MESSAGES_DIR), otherwise the built-in catalog; English needs none
+    fn load(code: &str) -> Result<Self, Box<dyn Error>> {
+        if code.eq_ignore_ascii_case("en") {
+            return Ok(Catalog::default());
+        }
+        let dir = env::var("MESSAGES_DIR").unwrap_or_else(|_| DEFAULT_MESSAGES_DIR.to_string());
+        let path = Path::new(&dir).join(format!("{}.toml", code));
+        let builtin = BUILTIN_CATALOGS.iter().find(|(language, _)| language.eq_ignore_ascii_case(code)).map(|(_, source)| *source);
+        let source = match builtin {
+            Some(builtin) if !path.exists() => builtin.to_string(),
+            _ => fs::read_to_string(&path)
+                .map_err(|e| format!("Unknown language '{}': could not read {}: {}", code, path.display(), e))?,
+        };
+        Ok(toml::from_str(&source).map_err(|e| format!("Invalid message catalog {}: {}", path.display(), e))?)
+    }
+
+    /// The translation of `message`, or the message itself
+    fn get<'a>(&'a self, message: &'a str) -> &'a str {
+        self.messages.get(message).map(String::as_str).unwrap_or(message)
+    }
+}
+
+/// Write the report's fixed text in this language (a built-in catalog or messages/<lang>.toml), instead of REPORT_LANGUAGE
+pub fn set_language(code: &str) -> Result<(), Box<dyn Error>> {
+    let _ = CATALOG.set(Catalog::load(code)?);
+    Ok(())
+}
+
+/// The catalog from --lang or REPORT_LANGUAGE, loaded on first use; English when neither is set
+fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(|| match env::var("REPORT_LANGUAGE") {
+        Ok(code) => Catalog::load(&code).unwrap_or_else(|e| {
+            log::warn!("{}, using English", e);
+            Catalog::default()
+        }),
+        Err(_) => Catalog::default(),
+    })
+}
+
+/// The language the model is asked to answer in, or None for English
+pub fn language_name() -> Option<&'static str> {
+    catalog().language.as_deref()
+}
+
+/// The translation of an English message, or the message itself when the catalog has none
+pub fn t(message: &'static str) -> &'static str {
+    catalog().get(message)
+}
+
+/// The translation of a term that only exists at runtime, such as an indicator's value name
+pub fn term(text: &str) -> &str {
+    catalog().get(text)
+}
+
+/// A translated message with its `{name}` placeholders filled in, e.g.
+/// `tf("RSI {value} overbought", &[("value", &format!("{:.1}", rsi))])`
+pub fn tf(message: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = t(message).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_needs_no_catalog() {
+        let catalog = Catalog::load("EN").unwrap();
+        assert_eq!(catalog.language, None);
+        assert_eq!(catalog.get("TRADE JOURNAL"), "TRADE JOURNAL");
+    }
+
+    #[test]
+    fn german_is_built_in() {
+        let catalog = Catalog::load("de").unwrap();
+        assert_eq!(catalog.language.as_deref(), Some("German"));
+        assert_eq!(catalog.get("TRADE JOURNAL"), "HANDELSJOURNAL");
+    }
+
+    #[test]
+    fn missing_messages_stay_in_english() {
+        let catalog = Catalog::load("de").unwrap();
+        assert_eq!(catalog.get("Not in any catalog"), "Not in any catalog");
+    }
+
+    #[test]
+    fn unknown_languages_are_an_error() {
+        assert!(Catalog::load("xx").is_err());
+    }
+
+    #[test]
+    fn built_in_translations_keep_their_placeholders() {
+        let placeholders = |text: &str| {
+            let mut names: Vec<String> = text.split('{').skip(1).filter_map(|part| part.split_once('}')).map(|(name, _)| name.to_string()).collect();
+            names.sort();
+            names
+        };
+        for (language, source) in BUILTIN_CATALOGS {
+            let catalog: Catalog = toml::from_str(source).unwrap();
+            for (message, translation) in &catalog.messages {
+                assert_eq!(placeholders(message), placeholders(translation), "{} in the {} catalog", message, language);
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::data_fetcher::Candle;
+use super::{Indicator, Interpretation, RecentValues, period_time};
+use crate::i18n;
+use crate::report::{IndicatorPeriod, IndicatorReport, ValueUnit};
+use ta::indicators::AverageTrueRange;
+use ta::Next;
+
+/// Average True Range (14)
+pub struct AtrIndicator {
+    atr: AverageTrueRange,
+    count: usize,
+    recent: RecentValues<(f64, f64, f64)>,     // Timestamp, ATR value and close price
+}
+
+impl Default for AtrIndicator {
+    fn default() -> Self {
+        AtrIndicator {
+            atr: AverageTrueRange::new(14).unwrap(),
+            count: 0,
+            recent: RecentValues::default(),
+        }
+    }
+}
+
+impl Indicator for AtrIndicator {
+    fn key(&self) -> &'static str {
+        "atr"
+    }
+
+    fn update(&mut self, candle: &Candle) {
+        // True range uses the candle's high/low against the previous close
+        self.recent.push((candle.timestamp, self.atr.next(candle), candle.close));
+        self.count += 1;
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= 14
+    }
+
+    fn interpret(&self) -> Option<Interpretation> {
+        // Volatility has no directional bias
+        None
+    }
+
+    fn report(&self) -> IndicatorReport {
+        let mut report = IndicatorReport::new("atr", i18n::t("Average True Range (ATR)"), i18n::t("ATR Trend Analysis"));
+
+        for &(timestamp, atr_val, price) in self.recent.iter() {
+            let atr_percent = atr_val / price * 100.0;
+
+            let volatility = if atr_percent > 5.0 {
+                i18n::t("High volatility (ATR > 5% of price)")
+            } else if atr_percent > 3.0 {
+                i18n::t("Medium volatility (ATR 3-5% of price)")
+            } else {
+                i18n::t("Low volatility (ATR < 3% of price)")
+            };
+
+            report.periods.push(IndicatorPeriod::new(period_time(timestamp))
+                .value("ATR (14-day)", atr_val, ValueUnit::Price)
+                .value("ATR as % of price", atr_percent, ValueUnit::Percent)
+                .indication(volatility));
+        }
+
+        // Add ATR trend analysis if we have enough data
+        if let (Some(&(_, last_atr, _)), Some(&(_, prev_atr, _))) = (self.recent.last(), self.recent.previous()) {
+            report.analysis.push(if last_atr > prev_atr {
+                i18n::t("Volatility: Increasing (ATR rising)").to_string()
+            } else if last_atr < prev_atr {
+                i18n::t("Volatility: Decreasing (ATR falling)").to_string()
+            } else {
+                i18n::t("Volatility: Stable (ATR unchanged)").to_string()
+            });
+        }
+
+        report
+    }
+}
@@ -0,0 +1,102 @@
+use crate::data_fetcher::Candle;
+use super::{Indicator, Interpretation, RecentValues, period_time};
+use crate::i18n;
+use crate::report::{IndicatorPeriod, IndicatorReport, ValueUnit};
+use ta::indicators::{BollingerBands, BollingerBandsOutput};
+use ta::Next;
+
+/// Bollinger Bands (20, 2 by default)
+pub struct BollingerIndicator {
+    period: usize,
+    std_dev: f64,
+    bb: BollingerBands,
+    count: usize,
+    recent: RecentValues<(f64, BollingerBandsOutput, f64)>,    // Timestamp, bands and close price
+}
+
+impl BollingerIndicator {
+    pub fn new(period: usize, std_dev: f64) -> Self {
+        BollingerIndicator {
+            period,
+            std_dev,
+            bb: BollingerBands::new(period, std_dev).unwrap(),
+            count: 0,
+            recent: RecentValues::default(),
+        }
+    }
+}
+
+impl Indicator for BollingerIndicator {
+    fn key(&self) -> &'static str {
+        "bollinger"
+    }
+
+    fn update(&mut self, candle: &Candle) {
+        self.recent.push((candle.timestamp, self.bb.next(candle.close), candle.close));
+        self.count += 1;
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.period
+    }
+
+    fn interpret(&self) -> Option<Interpretation> {
+        // Mean-reversion reading of price position within the bands
+        let (_, bb, price) = self.recent.last()?;
+        let band_width = bb.upper - bb.lower;
+        let position = if band_width > 0.0 { (price - bb.lower) / band_width } else { 0.5 };
+        let (signal, reason) = if *price > bb.upper {
+            (-1.0, i18n::t("price above upper band").to_string())
+        } else if *price < bb.lower {
+            (1.0, i18n::t("price below lower band").to_string())
+        } else {
+            ((0.5 - position) * 0.5, i18n::tf("price at {position}% of band width", &[("position", &format!("{:.0}", position * 100.0))]))
+        };
+        Some(Interpretation { signal, reason })
+    }
+
+    fn report(&self) -> IndicatorReport {
+        let mut report = IndicatorReport::new("bollinger",
+            &i18n::tf("Bollinger Bands ({period}, {std_dev})", &[("period", &self.period), ("std_dev", &self.std_dev)]),
+            i18n::t("Bollinger Bands Volatility Analysis"));
+
+        for (timestamp, bb_val, price) in self.recent.iter() {
+            // Calculate price position within bands
+            let band_width = bb_val.upper - bb_val.lower;
+            let position = (price - bb_val.lower) / band_width * 100.0;
+
+            // Determine the interpretation
+            let interpretation = if *price > bb_val.upper {
+                i18n::t("Potentially overbought (price above upper band)")
+            } else if *price < bb_val.lower {
+                i18n::t("Potentially oversold (price below lower band)")
+            } else {
+                i18n::t("Within normal trading range")
+            };
+
+            report.periods.push(IndicatorPeriod::new(period_time(*timestamp))
+                .value("Upper Band", bb_val.upper, ValueUnit::Price)
+                .value("Middle Band (SMA)", bb_val.average, ValueUnit::Price)
+                .value("Lower Band", bb_val.lower, ValueUnit::Price)
+                .value("Price", *price, ValueUnit::Price)
+                .value("Position in band", position, ValueUnit::Percent)
+                .indication(interpretation));
+        }
+
+        // Add Bollinger Bands width analysis for volatility assessment
+        if let (Some((_, last_bb, _)), Some((_, prev_bb, _))) = (self.recent.last(), self.recent.previous()) {
+            let last_width = last_bb.upper - last_bb.lower;
+            let prev_width = prev_bb.upper - prev_bb.lower;
+
+            report.analysis.push(if last_width > prev_width {
+                i18n::t("Volatility: Increasing (Bands widening)").to_string()
+            } else if last_width < prev_width {
+                i18n::t("Volatility: Decreasing (Bands narrowing)").to_string()
+            } else {
+                i18n::t("Volatility: Stable (Band width unchanged)").to_string()
+            });
+        }
+
+        report
+    }
+}
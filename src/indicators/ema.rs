@@ -0,0 +1,105 @@
+use crate::data_fetcher::Candle;
+use super::{Indicator, Interpretation, RecentValues, period_time};
+use crate::i18n;
+use crate::report::{IndicatorPeriod, IndicatorReport, ValueUnit};
+use ta::indicators::ExponentialMovingAverage;
+use ta::Next;
+
+const PERIODS: [usize; 4] = [12, 26, 50, 200];
+
+/// Exponential Moving Averages (12/26, plus 50/200 when enough history is available)
+pub struct EmaIndicator {
+    emas: Vec<ExponentialMovingAverage>,
+    count: usize,
+    recent: RecentValues<(f64, [f64; 4])>,     // Timestamp and the value for each period
+}
+
+impl Default for EmaIndicator {
+    fn default() -> Self {
+        EmaIndicator {
+            emas: PERIODS.iter().map(|&period| ExponentialMovingAverage::new(period).unwrap()).collect(),
+            count: 0,
+            recent: RecentValues::default(),
+        }
+    }
+}
+
+impl EmaIndicator {
+    fn long_term(&self) -> bool {
+        self.count >= 200
+    }
+
+    fn latest(&self, idx: usize) -> f64 {
+        self.recent.last().map(|(_, values)| values[idx]).unwrap_or(0.0)
+    }
+}
+
+impl Indicator for EmaIndicator {
+    fn key(&self) -> &'static str {
+        "ema"
+    }
+
+    fn update(&mut self, candle: &Candle) {
+        let mut values = [0.0; 4];
+        for (value, ema) in values.iter_mut().zip(self.emas.iter_mut()) {
+            *value = ema.next(candle.close);
+        }
+        self.recent.push((candle.timestamp, values));
+        self.count += 1;
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= 20
+    }
+
+    fn interpret(&self) -> Option<Interpretation> {
+        let (ema12, ema26) = (self.latest(0), self.latest(1));
+        Some(Interpretation {
+            signal: if ema12 > ema26 { 1.0 } else { -1.0 },
+            reason: if ema12 > ema26 { i18n::t("EMA12 above EMA26") } else { i18n::t("EMA12 below EMA26") }.to_string(),
+        })
+    }
+
+    fn report(&self) -> IndicatorReport {
+        let mut report = IndicatorReport::new("ema", i18n::t("Exponential Moving Averages"), i18n::t("EMA Trend Analysis"));
+        let (unit, shown) = if self.long_term() { ("period", 4) } else { ("day", 2) };
+
+        for (timestamp, values) in self.recent.iter() {
+            let mut period = IndicatorPeriod::new(period_time(*timestamp));
+            for (length, value) in PERIODS.iter().zip(values.iter()).take(shown) {
+                period = period.value(&format!("EMA ({}-{})", length, unit), *value, ValueUnit::Price);
+            }
+            report.periods.push(period);
+        }
+
+        if self.long_term() {
+            // Add trend indications based on most recent EMA crossovers
+            let (ema12, ema26, ema50, ema200) = (self.latest(0), self.latest(1), self.latest(2), self.latest(3));
+
+            report.analysis.push(if ema12 > ema26 {
+                i18n::t("Short-term EMA Trend: Bullish (12 above 26)").to_string()
+            } else {
+                i18n::t("Short-term EMA Trend: Bearish (12 below 26)").to_string()
+            });
+
+            report.analysis.push(if ema50 > ema200 {
+                i18n::t("Long-term EMA Trend: Bullish (50 above 200)").to_string()
+            } else {
+                i18n::t("Long-term EMA Trend: Bearish (50 below 200)").to_string()
+            });
+
+            // Check for potential golden/death cross forming
+            if ema50 < ema200 && ema50 / ema200 > 0.995 {
+                report.analysis.push(i18n::t("Alert: Potential golden cross forming (50 EMA approaching 200 EMA from below)").to_string());
+            } else if ema50 > ema200 && ema50 / ema200 < 1.005 {
+                report.analysis.push(i18n::t("Alert: Potential death cross forming (50 EMA approaching 200 EMA from above)").to_string());
+            }
+        } else if self.latest(0) > self.latest(1) {
+            report.analysis.push(i18n::t("Trend: Bullish (Short-term EMA above Long-term EMA)").to_string());
+        } else {
+            report.analysis.push(i18n::t("Trend: Bearish (Short-term EMA below Long-term EMA)").to_string());
+        }
+
+        report
+    }
+}
@@ -0,0 +1,106 @@
+use crate::data_fetcher::Candle;
+use super::{Indicator, Interpretation, RecentValues, period_time};
+use crate::i18n;
+use crate::report::{IndicatorPeriod, IndicatorReport, ValueUnit};
+use ta::indicators::{MovingAverageConvergenceDivergence, MovingAverageConvergenceDivergenceOutput};
+use ta::Next;
+
+/// Moving Average Convergence Divergence (12, 26, 9 by default)
+pub struct MacdIndicator {
+    periods: (usize, usize, usize),    // Fast, slow and signal lengths
+    macd: MovingAverageConvergenceDivergence,
+    count: usize,
+    recent: RecentValues<(f64, MovingAverageConvergenceDivergenceOutput)>,    // Timestamp and MACD values
+}
+
+impl MacdIndicator {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        MacdIndicator {
+            periods: (fast, slow, signal),
+            macd: MovingAverageConvergenceDivergence::new(fast, slow, signal).unwrap(),
+            count: 0,
+            recent: RecentValues::default(),
+        }
+    }
+}
+
+impl Indicator for MacdIndicator {
+    fn key(&self) -> &'static str {
+        "macd"
+    }
+
+    fn update(&mut self, candle: &Candle) {
+        self.recent.push((candle.timestamp, self.macd.next(candle.close)));
+        self.count += 1;
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.periods.1 + self.periods.2 // Need at least slow + signal data points
+    }
+
+    fn interpret(&self) -> Option<Interpretation> {
+        let (_, last) = self.recent.last()?;
+        let (_, prev) = self.recent.previous()?;
+        let cross = if last.macd > last.signal { 0.5 } else { -0.5 };
+        let momentum = if last.histogram > prev.histogram { 0.5 } else { -0.5 };
+        Some(Interpretation {
+            signal: cross + momentum,
+            reason: i18n::tf("MACD {cross} signal, histogram {momentum}", &[
+                ("cross", &if last.macd > last.signal { i18n::t("above") } else { i18n::t("below") }),
+                ("momentum", &if last.histogram > prev.histogram { i18n::t("rising") } else { i18n::t("falling") }),
+            ]),
+        })
+    }
+
+    fn report(&self) -> IndicatorReport {
+        let mut report = IndicatorReport::new("macd",
+            &format!("MACD ({}, {}, {})", self.periods.0, self.periods.1, self.periods.2), i18n::t("MACD Trend Analysis"));
+
+        for (timestamp, macd_val) in self.recent.iter() {
+            // Add per-period MACD interpretation
+            let interpretation = if macd_val.macd > macd_val.signal {
+                if macd_val.macd > 0.0 && macd_val.signal > 0.0 {
+                    i18n::t("Bullish (MACD above Signal, both positive)")
+                } else {
+                    i18n::t("Potential bullish crossover (below zero)")
+                }
+            } else if macd_val.macd < 0.0 && macd_val.signal < 0.0 {
+                i18n::t("Bearish (MACD below Signal, both negative)")
+            } else {
+                i18n::t("Potential bearish crossover (above zero)")
+            };
+
+            report.periods.push(IndicatorPeriod::new(period_time(*timestamp))
+                .value("MACD Line", macd_val.macd, ValueUnit::Number)
+                .value("Signal Line", macd_val.signal, ValueUnit::Number)
+                .value("Histogram", macd_val.histogram, ValueUnit::Number)
+                .indication(interpretation));
+        }
+
+        // Add MACD trend analysis based on most recent values
+        if let (Some((_, last_macd)), Some((_, prev_macd))) = (self.recent.last(), self.recent.previous()) {
+            // Check if a crossover occurred in the last period
+            let current_above_signal = last_macd.macd > last_macd.signal;
+            let prev_above_signal = prev_macd.macd > prev_macd.signal;
+
+            report.analysis.push(if current_above_signal && !prev_above_signal {
+                i18n::t("Signal: Bullish crossover (MACD crossed above Signal)").to_string()
+            } else if !current_above_signal && prev_above_signal {
+                i18n::t("Signal: Bearish crossover (MACD crossed below Signal)").to_string()
+            } else if current_above_signal {
+                i18n::t("Signal: Bullish momentum continues").to_string()
+            } else {
+                i18n::t("Signal: Bearish momentum continues").to_string()
+            });
+
+            // Check histogram direction for momentum
+            report.analysis.push(if last_macd.histogram > prev_macd.histogram {
+                i18n::t("Momentum: Increasing (Histogram rising)").to_string()
+            } else {
+                i18n::t("Momentum: Decreasing (Histogram falling)").to_string()
+            });
+        }
+
+        report
+    }
+}
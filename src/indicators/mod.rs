@@ -0,0 +1,259 @@
+mod sma;
+mod ema;
+mod rsi;
+mod macd;
+mod bollinger;
+mod obv;
+mod atr;
+mod series;
+
+use std::collections::VecDeque;
+use std::env;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::data_fetcher::Candle;
+use crate::report::IndicatorReport;
+
+pub use sma::SmaIndicator;
+pub use ema::EmaIndicator;
+pub use rsi::RsiIndicator;
+pub use macd::MacdIndicator;
+pub use bollinger::BollingerIndicator;
+pub use obv::ObvIndicator;
+pub use atr::AtrIndicator;
+pub use series::{IndicatorSeries, SERIES_EMA_PERIOD};
+
+/// Number of recent periods shown for each indicator
+pub const RECENT_PERIODS: usize = 5;
+
+/// Directional reading of an indicator, used by the composite signal score
+#[derive(Serialize)]
+pub struct Interpretation {
+    pub signal: f64,       // -1.0 (fully bearish) to +1.0 (fully bullish)
+    pub reason: String,
+}
+
+/// Tunable indicator settings, swept by the optimizer
+#[derive(Clone, Copy, Serialize)]
+pub struct IndicatorParams {
+    pub rsi_period: usize,
+    pub macd_fast: usize,
+    pub macd_slow: usize,
+    pub macd_signal: usize,
+    pub bollinger_period: usize,
+    pub bollinger_std_dev: f64,
+}
+
+impl Default for IndicatorParams {
+    fn default() -> Self {
+        IndicatorParams {
+            rsi_period: 14,
+            macd_fast: 12,
+            macd_slow: 26,
+            macd_signal: 9,
+            bollinger_period: 20,
+            bollinger_std_dev: 2.0,
+        }
+    }
+}
+
+impl IndicatorParams {
+    /// Defaults with overrides from RSI_PERIOD, MACD_FAST, MACD_SLOW, MACD_SIGNAL, BOLLINGER_PERIOD and BOLLINGER_STD_DEV
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            env::var(name).ok().and_then(|value| value.trim().parse().ok()).unwrap_or(default)
+        }
+        let defaults = IndicatorParams::default();
+        IndicatorParams {
+            rsi_period: var("RSI_PERIOD", defaults.rsi_period),
+            macd_fast: var("MACD_FAST", defaults.macd_fast),
+            macd_slow: var("MACD_SLOW", defaults.macd_slow),
+            macd_signal: var("MACD_SIGNAL", defaults.macd_signal),
+            bollinger_period: var("BOLLINGER_PERIOD", defaults.bollinger_period),
+            bollinger_std_dev: var("BOLLINGER_STD_DEV", defaults.bollinger_std_dev),
+        }
+    }
+
+    /// Rejects settings the indicators cannot be built with: periods of 0 and a band width that is not positive
+    pub fn validate(&self) -> Result<(), String> {
+        let periods = [
+            ("RSI_PERIOD", self.rsi_period),
+            ("MACD_FAST", self.macd_fast),
+            ("MACD_SLOW", self.macd_slow),
+            ("MACD_SIGNAL", self.macd_signal),
+            ("BOLLINGER_PERIOD", self.bollinger_period),
+        ];
+        if let Some((name, _)) = periods.iter().find(|(_, period)| *period == 0) {
+            return Err(format!("{} must be a period of at least 1", name));
+        }
+        if self.bollinger_std_dev.is_nan() || self.bollinger_std_dev <= 0.0 {
+            return Err(format!("BOLLINGER_STD_DEV must be a positive number of standard deviations, got {}", self.bollinger_std_dev));
+        }
+        Ok(())
+    }
+}
+
+/// A streaming technical indicator, fed one candle at a time
+pub trait Indicator: Send {
+    /// Short identifier used in config (e.g. "rsi")
+    fn key(&self) -> &'static str;
+
+    /// Feed the next candle into the indicator
+    fn update(&mut self, candle: &Candle);
+
+    /// Whether enough candles have been seen for the values to be meaningful
+    fn is_ready(&self) -> bool;
+
+    /// Directional bias of the latest values, if the indicator has one
+    fn interpret(&self) -> Option<Interpretation>;
+
+    /// Recent values and trend analysis for the technical report
+    fn report(&self) -> IndicatorReport;
+}
+
+/// Ordered collection of indicators, updated together in a single pass over the candles
+#[derive(Default)]
+pub struct IndicatorRegistry {
+    indicators: Vec<Box<dyn Indicator>>,
+}
+
+impl IndicatorRegistry {
+    pub fn new() -> Self {
+        IndicatorRegistry { indicators: Vec::new() }
+    }
+
+    /// Registry with every built-in indicator in report order
+    pub fn with_params(params: IndicatorParams) -> Self {
+        let mut registry = IndicatorRegistry::new();
+        registry.register(Box::new(SmaIndicator::default()));
+        registry.register(Box::new(EmaIndicator::default()));
+        registry.register(Box::new(RsiIndicator::new(params.rsi_period)));
+        registry.register(Box::new(MacdIndicator::new(params.macd_fast, params.macd_slow, params.macd_signal)));
+        registry.register(Box::new(BollingerIndicator::new(params.bollinger_period, params.bollinger_std_dev)));
+        registry.register(Box::new(ObvIndicator::default()));
+        registry.register(Box::new(AtrIndicator::default()));
+        registry
+    }
+
+    /// Registry with the settings from the environment minus any indicators listed in DISABLED_INDICATORS (e.g. "obv,atr")
+    pub fn from_env() -> Self {
+        IndicatorRegistry::from_env_with(IndicatorParams::from_env())
+    }
+
+    /// Registry with the given settings minus any indicators listed in DISABLED_INDICATORS
+    pub fn from_env_with(params: IndicatorParams) -> Self {
+        let mut registry = IndicatorRegistry::with_params(params);
+
+        if let Ok(disabled) = env::var("DISABLED_INDICATORS") {
+            for key in disabled.split(',').map(|k| k.trim().to_lowercase()).filter(|k| !k.is_empty()) {
+                registry.disable(&key);
+            }
+        }
+
+        registry
+    }
+
+    pub fn register(&mut self, indicator: Box<dyn Indicator>) {
+        self.indicators.push(indicator);
+    }
+
+    /// Remove an indicator by key
+    pub fn disable(&mut self, key: &str) {
+        if let Some(idx) = self.indicators.iter().position(|i| i.key() == key) {
+            self.indicators.remove(idx);
+        } else {
+            log::warn!("Unknown indicator '{}' in DISABLED_INDICATORS", key);
+        }
+    }
+
+    /// Feed one new candle to every registered indicator
+    pub fn update(&mut self, candle: &Candle) {
+        for indicator in self.indicators.iter_mut() {
+            indicator.update(candle);
+        }
+    }
+
+    /// Stream a full candle history through all indicators in one pass
+    pub fn compute_all(&mut self, candles: &[Candle]) {
+        for candle in candles {
+            self.update(candle);
+        }
+    }
+
+    /// Whether every registered indicator has warmed up
+    pub fn is_ready(&self) -> bool {
+        self.indicators.iter().all(|indicator| indicator.is_ready())
+    }
+
+    /// Indicators that have seen enough candles to report values
+    pub fn ready(&self) -> impl Iterator<Item = &dyn Indicator> {
+        self.indicators.iter()
+            .filter(|indicator| indicator.is_ready())
+            .map(|indicator| indicator.as_ref())
+    }
+}
+
+/// Rolling window holding an indicator's most recent values
+pub struct RecentValues<T> {
+    capacity: usize,
+    items: VecDeque<T>,
+}
+
+impl<T> Default for RecentValues<T> {
+    fn default() -> Self {
+        RecentValues::with_capacity(RECENT_PERIODS)
+    }
+}
+
+impl<T> RecentValues<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        RecentValues { capacity, items: VecDeque::with_capacity(capacity + 1) }
+    }
+
+    /// Append a value, dropping the oldest once the window is full
+    pub fn push(&mut self, item: T) {
+        self.items.push_back(item);
+        if self.items.len() > self.capacity {
+            self.items.pop_front();
+        }
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// The value before the latest one
+    pub fn previous(&self) -> Option<&T> {
+        self.items.len().checked_sub(2).and_then(|idx| self.items.get(idx))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+}
+
+/// Convert a candle's millisecond timestamp into a UTC datetime
+pub fn period_time(timestamp: f64) -> Option<DateTime<Utc>> {
+    DateTime::<Utc>::from_timestamp((timestamp as i64) / 1000, 0)
+}
+
+// Let ta indicators consume candles directly (e.g. ATR's true range needs high, low and previous close)
+impl ta::Open for Candle {
+    fn open(&self) -> f64 { self.open }
+}
+
+impl ta::High for Candle {
+    fn high(&self) -> f64 { self.high }
+}
+
+impl ta::Low for Candle {
+    fn low(&self) -> f64 { self.low }
+}
+
+impl ta::Close for Candle {
+    fn close(&self) -> f64 { self.close }
+}
+
+impl ta::Volume for Candle {
+    fn volume(&self) -> f64 { self.volume }
+}
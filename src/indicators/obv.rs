@@ -0,0 +1,103 @@
+use crate::data_fetcher::Candle;
+use super::{Indicator, Interpretation, RECENT_PERIODS, RecentValues, period_time};
+use crate::i18n;
+use crate::report::{IndicatorPeriod, IndicatorReport, ValueUnit};
+
+/// On Balance Volume
+pub struct ObvIndicator {
+    obv: f64,
+    prev_close: Option<f64>,
+    count: usize,
+    recent: RecentValues<(f64, f64)>,      // Timestamp and OBV, one extra period for the change calculation
+}
+
+impl Default for ObvIndicator {
+    fn default() -> Self {
+        ObvIndicator {
+            obv: 0.0,
+            prev_close: None,
+            count: 0,
+            recent: RecentValues::with_capacity(RECENT_PERIODS + 1),
+        }
+    }
+}
+
+impl ObvIndicator {
+    /// OBV change over the last RECENT_PERIODS, as a percentage of the latest OBV
+    fn overall_change(&self) -> f64 {
+        let values: Vec<f64> = self.recent.iter().map(|&(_, obv)| obv).collect();
+        let last_obv = values[values.len() - 1];
+        let obv_5_period_ago = values[values.len() - RECENT_PERIODS];
+        (last_obv - obv_5_period_ago) / last_obv.abs().max(1.0) * 100.0
+    }
+}
+
+impl Indicator for ObvIndicator {
+    fn key(&self) -> &'static str {
+        "obv"
+    }
+
+    fn update(&mut self, candle: &Candle) {
+        // For OBV, we'll calculate it manually since the ta library implementation is causing issues
+        if let Some(prev_close) = self.prev_close {
+            if candle.close > prev_close {
+                self.obv += candle.volume;  // Price up, add volume
+            } else if candle.close < prev_close {
+                self.obv -= candle.volume;  // Price down, subtract volume
+            } // If price unchanged, obv remains the same
+        }
+
+        self.prev_close = Some(candle.close);
+        self.recent.push((candle.timestamp, self.obv));
+        self.count += 1;
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= RECENT_PERIODS
+    }
+
+    fn interpret(&self) -> Option<Interpretation> {
+        let change = self.overall_change();
+        Some(Interpretation {
+            signal: (change / 5.0).clamp(-1.0, 1.0),
+            reason: i18n::tf("OBV {change}% over 5 periods", &[("change", &format!("{:+.2}", change))]),
+        })
+    }
+
+    fn report(&self) -> IndicatorReport {
+        let mut report = IndicatorReport::new("obv", i18n::t("On Balance Volume (OBV)"), i18n::t("OBV 5-Period Trend Analysis"));
+        let values: Vec<(f64, f64)> = self.recent.iter().copied().collect();
+
+        for idx in (values.len() - RECENT_PERIODS)..values.len() {
+            let (timestamp, current_obv) = values[idx];
+            let prev_obv = if idx > 0 { values[idx - 1].1 } else { 0.0 };
+            let obv_change = ((current_obv - prev_obv) / current_obv.abs().max(1.0)) * 100.0;
+
+            let interpretation = if obv_change > 2.0 {
+                i18n::t("Strong buying pressure (OBV increasing)")
+            } else if obv_change < -2.0 {
+                i18n::t("Strong selling pressure (OBV decreasing)")
+            } else {
+                i18n::t("Neutral volume pressure")
+            };
+
+            report.periods.push(IndicatorPeriod::new(period_time(timestamp))
+                .value("OBV", current_obv, ValueUnit::Volume)
+                .value("Change", obv_change, ValueUnit::Percent)
+                .indication(interpretation));
+        }
+
+        // Add OBV trend analysis
+        let overall_change = self.overall_change();
+
+        report.analysis.push(if overall_change > 5.0 {
+            i18n::t("Strong buying pressure over last 5 periods (OBV trending up)").to_string()
+        } else if overall_change < -5.0 {
+            i18n::t("Strong selling pressure over last 5 periods (OBV trending down)").to_string()
+        } else {
+            i18n::t("Neutral volume pressure over last 5 periods").to_string()
+        });
+
+        report
+    }
+}
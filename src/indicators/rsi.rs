@@ -0,0 +1,85 @@
+use crate::data_fetcher::Candle;
+use super::{Indicator, Interpretation, RecentValues, period_time};
+use crate::i18n;
+use crate::report::{IndicatorPeriod, IndicatorReport, ValueUnit};
+use ta::indicators::RelativeStrengthIndex;
+use ta::Next;
+
+/// Relative Strength Index (14 by default)
+pub struct RsiIndicator {
+    period: usize,
+    rsi: RelativeStrengthIndex,
+    count: usize,
+    recent: RecentValues<(f64, f64)>,      // Timestamp and RSI value
+}
+
+impl RsiIndicator {
+    pub fn new(period: usize) -> Self {
+        RsiIndicator {
+            period,
+            rsi: RelativeStrengthIndex::new(period).unwrap(),
+            count: 0,
+            recent: RecentValues::default(),
+        }
+    }
+}
+
+impl Indicator for RsiIndicator {
+    fn key(&self) -> &'static str {
+        "rsi"
+    }
+
+    fn update(&mut self, candle: &Candle) {
+        self.recent.push((candle.timestamp, self.rsi.next(candle.close)));
+        self.count += 1;
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.period
+    }
+
+    fn interpret(&self) -> Option<Interpretation> {
+        // Overbought/oversold readings dominate, otherwise lean with momentum
+        let &(_, rsi) = self.recent.last()?;
+        let (signal, reason) = if rsi > 70.0 {
+            (-1.0, i18n::tf("RSI {value} overbought", &[("value", &format!("{:.1}", rsi))]))
+        } else if rsi < 30.0 {
+            (1.0, i18n::tf("RSI {value} oversold", &[("value", &format!("{:.1}", rsi))]))
+        } else {
+            ((rsi - 50.0) / 40.0, i18n::tf("RSI {value} neutral", &[("value", &format!("{:.1}", rsi))]))
+        };
+        Some(Interpretation { signal, reason })
+    }
+
+    fn report(&self) -> IndicatorReport {
+        let mut report = IndicatorReport::new("rsi", &i18n::tf("RSI With EMA ({period}-day)", &[("period", &self.period)]),
+            i18n::t("RSI Trend Analysis"));
+
+        for &(timestamp, rsi_val) in self.recent.iter() {
+            let rsi_interpretation = if rsi_val > 70.0 {
+                i18n::t("Overbought (>70)")
+            } else if rsi_val < 30.0 {
+                i18n::t("Oversold (<30)")
+            } else {
+                i18n::t("Neutral (30-70)")
+            };
+
+            report.periods.push(IndicatorPeriod::new(period_time(timestamp))
+                .value("RSI", rsi_val, ValueUnit::Number)
+                .indication(rsi_interpretation));
+        }
+
+        // Add RSI trend analysis
+        if let (Some(&(_, last_rsi)), Some(&(_, prev_rsi))) = (self.recent.last(), self.recent.previous()) {
+            report.analysis.push(if last_rsi > prev_rsi {
+                i18n::t("Trend: Rising (Increasing momentum)").to_string()
+            } else if last_rsi < prev_rsi {
+                i18n::t("Trend: Falling (Decreasing momentum)").to_string()
+            } else {
+                i18n::t("Trend: Flat (Stable momentum)").to_string()
+            });
+        }
+
+        report
+    }
+}
@@ -0,0 +1,85 @@
+use crate::data_fetcher::Candle;
+use super::IndicatorParams;
+use ta::errors::TaError;
+use ta::indicators::{BollingerBands, BollingerBandsOutput, ExponentialMovingAverage, MovingAverageConvergenceDivergence,
+    MovingAverageConvergenceDivergenceOutput, RelativeStrengthIndex};
+use ta::Next;
+
+/// Length of the EMA drawn over the candles
+pub const SERIES_EMA_PERIOD: usize = 50;
+
+/// Every candle's value of the indicators drawn on charts, from the same ta indicators and settings as the report.
+/// Each series is aligned with the candles and None until its indicator has warmed up.
+pub struct IndicatorSeries {
+    pub bands: Vec<Option<BollingerBandsOutput>>,   // The middle band is the SMA over the Bollinger period
+    pub ema: Vec<Option<f64>>,
+    pub rsi: Vec<Option<f64>>,
+    pub macd: Vec<Option<MovingAverageConvergenceDivergenceOutput>>,
+}
+
+impl IndicatorSeries {
+    pub fn new(candles: &[Candle], params: &IndicatorParams) -> Result<Self, TaError> {
+        Ok(IndicatorSeries {
+            bands: series(BollingerBands::new(params.bollinger_period, params.bollinger_std_dev)?, candles, params.bollinger_period),
+            ema: series(ExponentialMovingAverage::new(SERIES_EMA_PERIOD)?, candles, SERIES_EMA_PERIOD),
+            rsi: series(RelativeStrengthIndex::new(params.rsi_period)?, candles, params.rsi_period),
+            // Ready at the same point as the report's MACD
+            macd: series(MovingAverageConvergenceDivergence::new(params.macd_fast, params.macd_slow, params.macd_signal)?,
+                candles, params.macd_slow + params.macd_signal),
+        })
+    }
+
+    /// The series from `start` on, for drawing only the most recent candles
+    pub fn split_off(mut self, start: usize) -> Self {
+        IndicatorSeries {
+            bands: self.bands.split_off(start),
+            ema: self.ema.split_off(start),
+            rsi: self.rsi.split_off(start),
+            macd: self.macd.split_off(start),
+        }
+    }
+}
+
+/// Feed the closes through a ta indicator, keeping its value once it has seen `warmup` candles
+fn series<I: Next<f64>>(mut indicator: I, candles: &[Candle], warmup: usize) -> Vec<Option<I::Output>> {
+    candles.iter().enumerate()
+        .map(|(i, candle)| {
+            let value = indicator.next(candle.close);
+            (i + 1 >= warmup).then_some(value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles(closes: impl IntoIterator<Item = f64>) -> Vec<Candle> {
+        closes.into_iter().enumerate()
+            .map(|(i, close)| Candle { timestamp: i as f64 * 86_400_000.0, open: close, high: close + 1.0, low: close - 1.0, close, volume: 1.0 })
+            .collect()
+    }
+
+    #[test]
+    fn series_are_aligned_and_empty_until_warmed_up() {
+        let candles = candles((0..60).map(|i| 100.0 + i as f64));
+        let params = IndicatorParams::default();
+        let series = IndicatorSeries::new(&candles, &params).unwrap();
+
+        assert_eq!(series.bands.len(), candles.len());
+        assert!(series.bands[params.bollinger_period - 2].is_none());
+        assert_eq!(series.bands[params.bollinger_period - 1].as_ref().map(|bands| bands.average), Some(109.5));
+        assert!(series.ema[SERIES_EMA_PERIOD - 2].is_none() && series.ema[SERIES_EMA_PERIOD - 1].is_some());
+        assert!(series.macd[params.macd_slow + params.macd_signal - 2].is_none());
+        // Only gains: the RSI is at the top of its range
+        assert!(series.rsi[59].is_some_and(|rsi| rsi > 99.0));
+    }
+
+    #[test]
+    fn split_off_keeps_the_latest_values() {
+        let candles = candles((0..30).map(|i| 100.0 + i as f64));
+        let series = IndicatorSeries::new(&candles, &IndicatorParams::default()).unwrap().split_off(25);
+        assert_eq!(series.bands.len(), 5);
+        assert_eq!(series.bands[4].as_ref().map(|bands| bands.average), Some(119.5));
+    }
+}
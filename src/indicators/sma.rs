@@ -0,0 +1,127 @@
+use crate::data_fetcher::Candle;
+use super::{Indicator, Interpretation, RecentValues, period_time};
+use crate::i18n;
+use crate::report::{IndicatorPeriod, IndicatorReport, ValueUnit};
+use ta::indicators::SimpleMovingAverage;
+use ta::Next;
+
+const PERIODS: [usize; 4] = [7, 20, 50, 200];
+
+/// Simple Moving Averages (7/20, plus 50/200 when enough history is available)
+pub struct SmaIndicator {
+    smas: Vec<SimpleMovingAverage>,
+    count: usize,
+    recent: RecentValues<(f64, [f64; 4])>,     // Timestamp and the value for each period
+    current_price: f64,
+}
+
+impl Default for SmaIndicator {
+    fn default() -> Self {
+        SmaIndicator {
+            smas: PERIODS.iter().map(|&period| SimpleMovingAverage::new(period).unwrap()).collect(),
+            count: 0,
+            recent: RecentValues::default(),
+            current_price: 0.0,
+        }
+    }
+}
+
+impl SmaIndicator {
+    fn long_term(&self) -> bool {
+        self.count >= 200
+    }
+
+    fn latest(&self, idx: usize) -> f64 {
+        self.recent.last().map(|(_, values)| values[idx]).unwrap_or(0.0)
+    }
+}
+
+impl Indicator for SmaIndicator {
+    fn key(&self) -> &'static str {
+        "sma"
+    }
+
+    fn update(&mut self, candle: &Candle) {
+        let mut values = [0.0; 4];
+        for (value, sma) in values.iter_mut().zip(self.smas.iter_mut()) {
+            *value = sma.next(candle.close);
+        }
+        self.recent.push((candle.timestamp, values));
+        self.current_price = candle.close;
+        self.count += 1;
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= 20
+    }
+
+    fn interpret(&self) -> Option<Interpretation> {
+        if self.long_term() {
+            let (sma50, sma200) = (self.latest(2), self.latest(3));
+            let cross = if sma50 > sma200 { 0.5 } else { -0.5 };
+            let position = if self.current_price > sma200 { 0.5 } else { -0.5 };
+            Some(Interpretation {
+                signal: cross + position,
+                reason: i18n::tf("SMA50 {cross} SMA200, price {position} SMA200", &[
+                    ("cross", &if sma50 > sma200 { i18n::t("above") } else { i18n::t("below") }),
+                    ("position", &if self.current_price > sma200 { i18n::t("above") } else { i18n::t("below") }),
+                ]),
+            })
+        } else {
+            let (sma7, sma20) = (self.latest(0), self.latest(1));
+            Some(Interpretation {
+                signal: if sma7 > sma20 { 1.0 } else { -1.0 },
+                reason: if sma7 > sma20 { i18n::t("SMA7 above SMA20") } else { i18n::t("SMA7 below SMA20") }.to_string(),
+            })
+        }
+    }
+
+    fn report(&self) -> IndicatorReport {
+        let mut report = IndicatorReport::new("sma", i18n::t("Simple Moving Averages"), i18n::t("SMA Trend Analysis"));
+        // The 50/200 averages are only shown once they cover a full window
+        let (unit, shown) = if self.long_term() { ("period", 4) } else { ("day", 2) };
+
+        for (timestamp, values) in self.recent.iter() {
+            let mut period = IndicatorPeriod::new(period_time(*timestamp));
+            for (length, value) in PERIODS.iter().zip(values.iter()).take(shown) {
+                period = period.value(&format!("SMA ({}-{})", length, unit), *value, ValueUnit::Price);
+            }
+            report.periods.push(period);
+        }
+
+        if self.long_term() {
+            // Add trend indications based on most recent SMA crossovers
+            let (sma7, sma20, sma50, sma200) = (self.latest(0), self.latest(1), self.latest(2), self.latest(3));
+
+            report.analysis.push(if sma7 > sma20 {
+                i18n::t("Short-term Trend: Bullish (7 above 20)").to_string()
+            } else {
+                i18n::t("Short-term Trend: Bearish (7 below 20)").to_string()
+            });
+
+            report.analysis.push(if sma50 > sma200 {
+                i18n::t("Long-term Trend: Bullish (50 above 200, Golden Cross active)").to_string()
+            } else {
+                i18n::t("Long-term Trend: Bearish (50 below 200, Death Cross active)").to_string()
+            });
+
+            // Price position relative to major SMAs
+            let position = if self.current_price > sma200 && self.current_price > sma50 {
+                i18n::t("Strong bullish (Price above both 50 & 200 SMAs)")
+            } else if self.current_price > sma200 {
+                i18n::t("Moderately bullish (Price above 200 SMA but below 50 SMA)")
+            } else if self.current_price > sma50 {
+                i18n::t("Mixed signals (Price above 50 SMA but below 200 SMA)")
+            } else {
+                i18n::t("Bearish (Price below both 50 & 200 SMAs)")
+            };
+            report.analysis.push(i18n::tf("Price relative to SMAs: {position}", &[("position", &position)]));
+        } else if self.latest(0) > self.latest(1) {
+            report.analysis.push(i18n::t("Trend: Bullish (Short-term SMA above Long-term SMA)").to_string());
+        } else {
+            report.analysis.push(i18n::t("Trend: Bearish (Short-term SMA below Long-term SMA)").to_string());
+        }
+
+        report
+    }
+}
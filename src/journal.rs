@@ -0,0 +1,183 @@
+use std::env;
+use std::error::Error;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use crate::currency::Currency;
+use crate::renderer;
+
+/// Default location of the trade journal database
+const DEFAULT_JOURNAL_DB: &str = "journal.db";
+
+/// A recorded trade; exit fields are filled in when it is closed
+#[derive(Serialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub side: String,
+    pub entry: f64,
+    pub exit: Option<f64>,
+    pub size: f64,
+    pub rationale: String,
+    pub pnl: Option<f64>,
+}
+
+/// Performance of the closed trades, for self-review
+#[derive(Serialize)]
+pub struct JournalSummary {
+    pub closed_trades: usize,
+    pub open_trades: usize,
+    pub win_rate: f64,
+    pub average_win: f64,
+    pub average_loss: f64,
+    pub expectancy: f64,       // Average PnL per closed trade
+    pub total_pnl: f64,
+}
+
+/// Path of the journal database, from JOURNAL_DB (default journal.db)
+fn journal_path() -> String {
+    env::var("JOURNAL_DB").unwrap_or_else(|_| DEFAULT_JOURNAL_DB.to_string())
+}
+
+/// Open the journal, creating its table on first use
+pub fn open() -> Result<Connection, Box<dyn Error>> {
+    let connection = Connection::open(journal_path())?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS trades (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            opened_at TEXT NOT NULL,
+            closed_at TEXT,
+            side TEXT NOT NULL,
+            entry REAL NOT NULL,
+            exit REAL,
+            size REAL NOT NULL,
+            rationale TEXT NOT NULL,
+            pnl REAL
+        )",
+        [],
+    )?;
+    Ok(connection)
+}
+
+/// Record a newly opened trade and return its id
+pub fn open_trade(connection: &Connection, side: &str, entry: f64, size: f64, rationale: &str) -> Result<i64, Box<dyn Error>> {
+    connection.execute(
+        "INSERT INTO trades (opened_at, side, entry, size, rationale) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![Utc::now(), side, entry, size, rationale],
+    )?;
+    Ok(connection.last_insert_rowid())
+}
+
+/// Close an open trade at the exit price, returning its PnL
+pub fn close_trade(connection: &Connection, id: i64, exit: f64) -> Result<f64, Box<dyn Error>> {
+    let (side, entry, size): (String, f64, f64) = connection.query_row(
+        "SELECT side, entry, size FROM trades WHERE id = ?1 AND closed_at IS NULL",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).optional()?.ok_or_else(|| format!("No open trade with id {}", id))?;
+
+    let pnl = if side == "short" { (entry - exit) * size } else { (exit - entry) * size };
+    connection.execute(
+        "UPDATE trades SET closed_at = ?1, exit = ?2, pnl = ?3 WHERE id = ?4",
+        params![Utc::now(), exit, pnl, id],
+    )?;
+    Ok(pnl)
+}
+
+/// Most recently opened trade that is still open on the given side
+pub fn latest_open(connection: &Connection, side: &str) -> Result<Option<i64>, Box<dyn Error>> {
+    Ok(connection.query_row(
+        "SELECT id FROM trades WHERE side = ?1 AND closed_at IS NULL ORDER BY id DESC LIMIT 1",
+        params![side],
+        |row| row.get(0),
+    ).optional()?)
+}
+
+pub fn entries(connection: &Connection) -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+    let mut statement = connection.prepare(
+        "SELECT id, opened_at, closed_at, side, entry, exit, size, rationale, pnl FROM trades ORDER BY id")?;
+    let rows = statement.query_map([], |row| Ok(JournalEntry {
+        id: row.get(0)?,
+        opened_at: row.get(1)?,
+        closed_at: row.get(2)?,
+        side: row.get(3)?,
+        entry: row.get(4)?,
+        exit: row.get(5)?,
+        size: row.get(6)?,
+        rationale: row.get(7)?,
+        pnl: row.get(8)?,
+    }))?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Win rate and expectancy over all closed trades
+pub fn summarize(entries: &[JournalEntry]) -> JournalSummary {
+    let pnls: Vec<f64> = entries.iter().filter_map(|entry| entry.pnl).collect();
+    let wins: Vec<f64> = pnls.iter().copied().filter(|pnl| *pnl > 0.0).collect();
+    let losses: Vec<f64> = pnls.iter().copied().filter(|pnl| *pnl <= 0.0).collect();
+    let average = |values: &[f64]| if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 };
+
+    JournalSummary {
+        closed_trades: pnls.len(),
+        open_trades: entries.len() - pnls.len(),
+        win_rate: if pnls.is_empty() { 0.0 } else { wins.len() as f64 / pnls.len() as f64 * 100.0 },
+        average_win: average(&wins),
+        average_loss: average(&losses),
+        expectancy: average(&pnls),
+        total_pnl: pnls.iter().sum(),
+    }
+}
+
+/// Summary for the periodic report; None until a journal has been started
+pub fn load_summary() -> Result<Option<JournalSummary>, Box<dyn Error>> {
+    if !Path::new(&journal_path()).exists() {
+        return Ok(None);
+    }
+    Ok(Some(summarize(&entries(&open()?)?)))
+}
+
+/// Handle `journal open|close|list|summary` and print the result
+pub fn run_command(args: &[String], json_only: bool) -> Result<(), Box<dyn Error>> {
+    let connection = open()?;
+    let arg = |index: usize, name: &str| args.get(index).map(String::as_str)
+        .ok_or_else(|| format!("journal {} requires {}", args[0], name));
+    let number = |index: usize, name: &str| -> Result<f64, Box<dyn Error>> {
+        arg(index, name)?.parse().map_err(|_| format!("{} must be a number", name).into())
+    };
+
+    match args.first().map(String::as_str).unwrap_or("summary") {
+        "open" => {
+            let side = arg(1, "a side (long or short)")?;
+            if side != "long" && side != "short" {
+                return Err(format!("Unknown side '{}', expected long or short", side).into());
+            }
+            let id = open_trade(&connection, side, number(2, "an entry price")?, number(3, "a size")?, &args[4.min(args.len())..].join(" "))?;
+            println!("Opened trade #{}", id);
+        }
+        "close" => {
+            let id = arg(1, "a trade id")?.parse().map_err(|_| "trade id must be a number")?;
+            let pnl = close_trade(&connection, id, number(2, "an exit price")?)?;
+            println!("Closed trade #{}: PnL {}", id, Currency::USD.price(pnl));
+        }
+        "list" => {
+            let entries = entries(&connection)?;
+            if json_only {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else {
+                println!("{}", renderer::render_journal_entries(&entries));
+            }
+        }
+        "summary" => {
+            let summary = summarize(&entries(&connection)?);
+            if json_only {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else {
+                println!("{}", renderer::render_journal_summary(&summary));
+            }
+        }
+        other => return Err(format!("Unknown journal command '{}', expected open, close, list or summary", other).into()),
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! Crypto-Forecast: market data, technical analysis, AI-written reports and their delivery.
+//! `pipeline::Pipeline` runs the whole analysis; the command-line program in main.rs is a thin wrapper around these modules.
+
+pub mod data_fetcher;
+pub mod currency;
+pub mod technical_analysis;
+pub mod indicators;
+pub mod report;
+pub mod renderer;
+pub mod signal_score;
+pub mod risk;
+pub mod portfolio;
+pub mod signals;
+pub mod strategy;
+pub mod backtest;
+pub mod optimize;
+pub mod paper;
+pub mod execution;
+pub mod journal;
+pub mod history;
+pub mod delta;
+pub mod accuracy;
+pub mod unchanged;
+pub mod alerts;
+pub mod news;
+pub mod memory;
+pub mod chart;
+pub mod html;
+pub mod budget;
+pub mod watchlist;
+pub mod ab;
+pub mod reports;
+pub mod prompt_generator;
+pub mod ai_client;
+pub mod output;
+pub mod telegram_bot;
+pub mod discord_bot;
+pub mod terminal;
+pub mod locale;
+pub mod i18n;
+pub mod logging;
+pub mod tui;
+pub mod cli;
+pub mod config;
+pub mod secrets;
+pub mod scheduler;
+pub mod watch;
+pub mod pipeline;
+pub mod server;
+pub mod mcp;
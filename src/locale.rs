@@ -0,0 +1,127 @@
+use std::env;
+use std::sync::OnceLock;
+
+/// Locales numbers can be written in, by their conventions for grouping thousands and marking decimals
+pub const LOCALES: [Locale; 5] = [
+    Locale { code: "en-US", group: ",", decimal: ".", sign_after: false },
+    Locale { code: "en-GB", group: ",", decimal: ".", sign_after: false },
+    Locale { code: "de-DE", group: ".", decimal: ",", sign_after: true },
+    Locale { code: "fr-FR", group: "\u{202f}", decimal: ",", sign_after: true },
+    Locale { code: "th-TH", group: ",", decimal: ".", sign_after: false },
+];
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// How numbers and prices are written: $102,753.63 in en-US, 102.753,63 € in de-DE
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Locale {
+    pub code: &'static str,
+    group: &'static str,
+    decimal: &'static str,
+    sign_after: bool,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        LOCALES[0]
+    }
+}
+
+impl Locale {
+    /// One of LOCALES by its code, in any case and with `_` or `-` (e.g. "de_DE")
+    pub fn named(code: &str) -> Option<Self> {
+        let code = code.replace('_', "-");
+        LOCALES.iter().find(|locale| locale.code.eq_ignore_ascii_case(&code)).copied()
+    }
+
+    /// A number with grouped thousands and `decimals` decimal places, e.g. "102.753,63"
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push_str(self.group);
+            }
+            grouped.push(digit);
+        }
+        // Rounding can turn a tiny negative into zero, which shouldn't keep its sign
+        let sign = if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+        match fraction {
+            "" => format!("{}{}", sign, grouped),
+            fraction => format!("{}{}{}{}", sign, grouped, self.decimal, fraction),
+        }
+    }
+
+    /// An amount with the currency sign where this locale puts it, e.g. "$102,753.63" or "102.753,63 €"
+    pub fn money(&self, value: f64, decimals: usize, sign: &str) -> String {
+        let number = self.number(value, decimals);
+        match (self.sign_after, number.strip_prefix('-')) {
+            (true, _) => format!("{} {}", number, sign),
+            (false, Some(number)) => format!("-{}{}", sign, number),
+            (false, None) => format!("{}{}", sign, number),
+        }
+    }
+}
+
+/// Write numbers in this locale (one of LOCALES), instead of NUMBER_LOCALE
+pub fn set_locale(code: &str) {
+    if let Some(locale) = Locale::named(code) {
+        let _ = LOCALE.set(locale);
+    }
+}
+
+/// The locale from --locale or NUMBER_LOCALE, en-US by default
+pub fn current() -> Locale {
+    if let Some(locale) = LOCALE.get() {
+        return *locale;
+    }
+    match env::var("NUMBER_LOCALE") {
+        Ok(code) => Locale::named(&code).unwrap_or_else(|| {
+            log::warn!("unknown NUMBER_LOCALE '{}', using en-US", code);
+            Locale::default()
+        }),
+        Err(_) => Locale::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locale(code: &str) -> Locale {
+        Locale::named(code).unwrap()
+    }
+
+    #[test]
+    fn locales_are_named_with_either_separator() {
+        assert_eq!(locale("de_de").code, "de-DE");
+        assert_eq!(locale("FR-fr").code, "fr-FR");
+        assert_eq!(Locale::named("es-ES"), None);
+    }
+
+    #[test]
+    fn numbers_group_thousands_and_mark_decimals() {
+        assert_eq!(locale("en-US").number(102753.634, 2), "102,753.63");
+        assert_eq!(locale("de-DE").number(102753.634, 2), "102.753,63");
+        assert_eq!(locale("fr-FR").number(1234567.0, 0), "1\u{202f}234\u{202f}567");
+        assert_eq!(locale("en-GB").number(999.0, 1), "999.0");
+        assert_eq!(locale("en-US").number(-1234.5, 1), "-1,234.5");
+    }
+
+    #[test]
+    fn a_negative_rounded_to_zero_loses_its_sign() {
+        assert_eq!(locale("en-US").number(-0.004, 2), "0.00");
+        assert_eq!(locale("de-DE").number(-0.4, 0), "0");
+    }
+
+    #[test]
+    fn money_puts_the_sign_where_the_locale_does() {
+        assert_eq!(locale("en-US").money(94210.5, 2, "$"), "$94,210.50");
+        assert_eq!(locale("en-US").money(-12.5, 2, "$"), "-$12.50");
+        assert_eq!(locale("de-DE").money(94210.5, 2, "€"), "94.210,50 €");
+        assert_eq!(locale("de-DE").money(-12.5, 2, "€"), "-12,50 €");
+        assert_eq!(locale("th-TH").money(3400000.0, 0, "฿"), "฿3,400,000");
+    }
+}
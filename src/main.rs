@@ -1,71 +1,344 @@
-mod data_fetcher;
-mod technical_analysis;
-mod prompt_generator;
-mod ai_client;
-mod output;
-
+use clap::Parser;
+use crypto_forecast::{ab, accuracy, ai_client, alerts, backtest, cli, config, currency, data_fetcher, discord_bot, execution, history, i18n, indicators, journal, locale, logging, mcp, optimize, output, paper, pipeline, prompt_generator, renderer, reports, scheduler, secrets, server, signals, strategy, technical_analysis, telegram_bot, terminal, tui, watch, watchlist};
 use dotenv::dotenv;
 use std::env;
 use std::error::Error;
+use std::net::SocketAddr;
+use ai_client::AnalysisProvider;
+use cli::{AnalyzeArgs, BotPlatform, Command, MarketArgs, PromptArgs, ReportArgs};
+use pipeline::Pipeline;
+use prompt_generator::PromptTemplate;
+use reports::ReportKind;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     // Load environment variables from .env file
     dotenv().ok();
-    
-    // Check for command-line arguments
-    let args: Vec<String> = env::args().collect();
-    
-    // Parse arguments
-    let mut output_format = "text";
-    let mut only_prompt = false;
-    
-    if args.len() > 1 {
-        if args[1] == "--only-prompt" {
-            only_prompt = true;
-        } else {
-            output_format = &args[1];
-        }
+
+    let cli = cli::Cli::parse();
+    // Progress messages go to stderr through the logger, at the level chosen by -q/-v
+    logging::init(cli.verbosity());
+    // Settings from crypto-forecast.toml fill in whatever the environment leaves unset
+    let exported = config::apply(cli.profile.as_deref())?;
+    // Credentials left unset can come from *_FILE paths or the OS keyring
+    secrets::apply()?;
+    indicators::IndicatorParams::from_env().validate()?;
+    if cli.no_color {
+        terminal::disable_color();
     }
-    
-    // Get Anthropic API key from environment variables (only if we need it)
-    let api_key = if !only_prompt {
-        env::var("ANTHROPIC_API_KEY")
-            .expect("ANTHROPIC_API_KEY must be set in the .env file")
-    } else {
-        String::new()
-    };
+    if let Some(code) = &cli.currency {
+        currency::set_quote(code);
+    }
+    if let Some(code) = &cli.locale {
+        locale::set_locale(code);
+    }
+    if let Some(code) = &cli.lang {
+        i18n::set_language(code)?;
+    }
+    // The JSON snapshot is produced without the AI analysis
+    let json_only = cli.format == cli::Format::Json;
 
     let data_provider_api_key = env::var("DATA_PROVIDER_API_KEY")
         .unwrap_or_else(|_| String::new());
     
     let api_base_url = env::var("API_BASE_URL")
         .unwrap_or_else(|_| "https://api.binance.com".to_string());
-    
-    println!("Fetching Bitcoin price data from API...");
-    
-    // Get Bitcoin price data for trading analysis (4-hour candles over 4 months)
-    let btc_data = data_fetcher::fetch_bitcoin_trading_data(&data_provider_api_key, &api_base_url).await?;
-    let fear_and_greed_data = data_fetcher::fetch_fear_greed_index_data().await?;
 
-    println!("Analyzing Bitcoin price data with RSI(14), MACD(12,26,9), and other indicators...");
-    
-    // Prepare the data for analysis, including technical indicators
-    let formatted_data = technical_analysis::format_data_for_analysis(&btc_data, &fear_and_greed_data);
-    
-    // Generate trading recommendations prompt by default
-    println!("\nGenerating trading recommendations...");
-    let prompt = prompt_generator::generate_trading_recommendation_prompt(&formatted_data);
-    
+    // Without a command the analysis runs, with the options given before any command
+    match cli.command.unwrap_or(Command::Analyze(cli.analyze)) {
+        Command::Analyze(args) if args.watch => run_watch(args, json_only).await,
+        Command::Analyze(args) => run_analysis(args, json_only).await,
+        Command::Bot { platform: BotPlatform::Telegram } => telegram_bot::run_bot().await,
+        Command::Bot { platform: BotPlatform::Discord } => discord_bot::run_bot(&api_base_url).await,
+        Command::Tui => tui::run_tui(&data_provider_api_key, &api_base_url).await,
+        Command::Mcp(market) => mcp::run_mcp(&market.symbol()?, market.interval.as_deref()).await,
+        Command::Schedule { profiles } => scheduler::run_schedule(&profiles, &exported).await,
+        Command::Serve { addr, grpc_addr } => {
+            let addr = match (addr, env::var("SERVER_ADDR")) {
+                (Some(addr), _) => addr,
+                (None, Ok(addr)) => addr.parse().map_err(|e| format!("Invalid SERVER_ADDR '{}': {}", addr, e))?,
+                (None, Err(_)) => SocketAddr::from(([127, 0, 0, 1], 8080)),
+            };
+            let grpc_addr = match (grpc_addr, env::var("GRPC_ADDR")) {
+                (Some(addr), _) => Some(addr),
+                (None, Ok(addr)) => Some(addr.parse().map_err(|e| format!("Invalid GRPC_ADDR '{}': {}", addr, e))?),
+                (None, Err(_)) => None,
+            };
+            server::run_server(addr, grpc_addr, &data_provider_api_key, &api_base_url).await
+        }
+        Command::Journal { args } => journal::run_command(&args, json_only),
+        Command::History { command } => history::run_command(&command, json_only),
+        Command::Secrets { command } => secrets::run_command(&command),
+        Command::Accuracy { horizon, days } => {
+            let defaults = accuracy::AccuracyConfig::from_env();
+            let config = accuracy::AccuracyConfig { horizon_hours: horizon.unwrap_or(defaults.horizon_hours), days, ..defaults };
+            accuracy::run_accuracy(&api_base_url, &config, json_only).await
+        }
+        Command::Paper { once } => paper::run_paper(&data_provider_api_key, &api_base_url, once).await,
+        Command::Alerts { once, outputs } => {
+            let outputs = output::Outputs::load(outputs.as_deref())?;
+            alerts::run_alerts(&data_provider_api_key, &api_base_url, once, &outputs).await
+        }
+        Command::Watchlist(args) => {
+            // Set everything up before fetching data, so a missing key or broken template fails first
+            let providers = load_providers(&args.prompt_options)?;
+            let template = load_template(args.prompt_options.prompt.as_deref().unwrap_or("default"), &args.prompt_options)?;
+            let outputs = output::Outputs::load(args.outputs.as_deref())?;
+            watchlist::run_watchlist(&data_provider_api_key, &api_base_url, &providers, &template, &outputs).await
+        }
+        Command::OnchainReport(args) => run_report(ReportKind::Onchain, args, &data_provider_api_key, &api_base_url).await,
+        Command::MacroReport(args) => run_report(ReportKind::Macro, args, &data_provider_api_key, &api_base_url).await,
+        Command::WeeklyReview(args) => run_report(ReportKind::WeeklyReview, args, &data_provider_api_key, &api_base_url).await,
+        Command::Fetch(market) => {
+            let (data, fear_and_greed_data) = fetch_market(&market).await?;
+            let report = technical_analysis::build_technical_report(&data, &fear_and_greed_data);
+            if json_only {
+                println!("{}", renderer::render_json(&report)?);
+            } else {
+                println!("{}", terminal::paint(&renderer::render_text(&report)));
+            }
+            Ok(())
+        }
+        Command::Optimize { market, strategy: strategy_name } => {
+            let (data, _) = fetch_market(&market).await?;
+            let candles = data.candles();
+            log::info!("Searching strategy parameters over {} candles...", candles.len());
+            let result = optimize::run_optimization(candles, strategy::load_strategies()?, strategy_name,
+                signals::SignalRules::from_env(), backtest::BacktestConfig::from_env()?)?;
+
+            if json_only {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("\n{}", renderer::render_optimization(&result));
+            }
+            Ok(())
+        }
+        Command::Backtest { market, strategy: strategy_name, walk_forward, export } => {
+            let strategies = strategy::load_strategies()?;
+            let source = backtest::SignalSource::select(&strategies, strategy_name.as_deref())?;
+            let (data, _) = fetch_market(&market).await?;
+
+            let candles = data.candles();
+            let rules = signals::SignalRules::from_env();
+            let config = backtest::BacktestConfig::from_env()?;
+
+            if let Some(folds) = walk_forward {
+                log::info!("Running {} walk-forward windows over {} candles...", folds, candles.len());
+                let result = backtest::run_walk_forward(&candles, folds as usize, &source, &rules, &config)?;
+                if json_only {
+                    println!("{}", serde_json::to_string_pretty(&result)?);
+                } else {
+                    println!("\n{}", renderer::render_walk_forward(&result));
+                }
+                return Ok(());
+            }
+
+            log::info!("Replaying {} candles through the signal engine...", candles.len());
+            let result = backtest::run_backtest(&candles, &source, &rules, &config);
+
+            if let Some(dir) = export {
+                backtest::export_results(&result, &dir)?;
+                log::info!("Backtest results exported to {}", dir.display());
+            }
+
+            if json_only {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("\n{}", renderer::render_backtest(&result));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// One of the report types; with --only-prompt the prompt is printed instead
+async fn run_report(kind: ReportKind, args: ReportArgs, data_provider_api_key: &String, api_base_url: &String) -> Result<(), Box<dyn Error>> {
+    // Each report type has its own prompt, which --prompt can still replace
+    let template = load_template(args.prompt_options.prompt.as_deref().unwrap_or(kind.command()), &args.prompt_options)?;
+    let (providers, outputs) = if args.only_prompt {
+        (None, output::Outputs::terminal())
+    } else {
+        (Some(load_providers(&args.prompt_options)?), output::Outputs::load(args.outputs.as_deref())?)
+    };
+    reports::run_report(kind, data_provider_api_key, api_base_url, providers.as_deref(), &template, &outputs).await
+}
+
+/// The AI backends from --provider, otherwise AI_PROVIDER
+fn load_providers(options: &PromptArgs) -> Result<Vec<Box<dyn AnalysisProvider>>, Box<dyn Error>> {
+    match &options.provider {
+        Some(list) => ai_client::providers_from_list(list),
+        None => ai_client::providers_from_env(),
+    }
+}
+
+/// The prompt version with the --persona and --ask options applied
+fn load_template(version: &str, options: &PromptArgs) -> Result<PromptTemplate, Box<dyn Error>> {
+    let template = PromptTemplate::named(version)?;
+    let template = match &options.persona {
+        Some(persona) => template.with_persona(persona)?,
+        None => template,
+    };
+    Ok(match &options.ask {
+        Some(question) => template.with_question(question),
+        None => template,
+    })
+}
+
+/// The analysis pipeline for the market chosen with --symbol and --interval
+fn market_pipeline(market: &MarketArgs) -> Result<Pipeline, Box<dyn Error>> {
+    let pipeline = Pipeline::new().symbol(&market.symbol()?);
+    Ok(match &market.interval {
+        Some(interval) => pipeline.interval(interval),
+        None => pipeline,
+    })
+}
+
+/// Price data for the symbol at the chosen interval, and the Fear & Greed index
+async fn fetch_market(market: &MarketArgs) -> Result<(data_fetcher::CryptoData, Vec<data_fetcher::FearGreedData>), Box<dyn Error>> {
+    market_pipeline(market)?.fetch().await
+}
+
+/// The analysis: the technical report, the prompt, the model's answer and its delivery
+async fn run_analysis(args: AnalyzeArgs, json_only: bool) -> Result<(), Box<dyn Error>> {
+    let AnalyzeArgs { market, prompt_options, only_prompt, only_signals, only_indicators, dry_run, execute, brief, ab, porcelain, exit_code, outputs: output_list, .. } = args;
+    // An A/B run sends the same data through two prompt versions or models instead of the usual single analysis
+    let ab_spec = ab.filter(|_| !json_only);
+    // A dry run sets everything up as the real run would, even when its summary is printed as JSON
+    let prompts_model = !only_prompt && !only_signals && !only_indicators && (dry_run || !json_only);
+    let mut pipeline = market_pipeline(&market)?;
+
+    // Select the analysis backends up front (only if we need them) so a missing key fails before fetching data
+    if prompts_model && ab_spec.is_none() {
+        pipeline = pipeline.providers(load_providers(&prompt_options)?);
+    }
+
+    // Set up the output destinations up front as well, so missing settings fail before fetching data
+    if prompts_model || (only_indicators && !json_only) {
+        let outputs = output::Outputs::load(output_list.as_deref())?;
+        // With --porcelain stdout is reserved for the call, so the terminal and JSON outputs are left out
+        pipeline = pipeline.outputs(if porcelain { outputs.without(&["text", "json"]) } else { outputs });
+    }
+
+    // Load the prompt up front too, so a broken template or unknown persona fails before fetching data
+    let env_version = env::var("PROMPT_VERSION").ok();
+    let version = if brief { "brief" } else { prompt_options.prompt.as_deref().or(env_version.as_deref()).unwrap_or("default") };
+    if !only_signals && !only_indicators && (dry_run || !json_only) {
+        pipeline = pipeline.template(load_template(version, &prompt_options)?);
+    }
+    let ab_variants = match ab_spec {
+        Some(spec) => Some(ab::parse_variants(&spec, |version| load_template(version, &prompt_options))?),
+        None => None,
+    };
+
+    if only_signals {
+        // Rule-based signal only, no LLM call needed
+        let report = pipeline.report().await?;
+        let strategies = strategy::load_strategies()?;
+        let signal = signals::generate_signal(&report, &signals::SignalRules::from_env(), &strategies);
+        // With --porcelain only the call is printed
+        if json_only && !porcelain {
+            println!("{}", serde_json::to_string_pretty(&signal)?);
+        } else if !porcelain {
+            println!("\n{}", terminal::paint(&renderer::render_signal(&signal)));
+        }
+        if execute {
+            execution::execute_signal(&signal, &execution::ExecutionConfig::from_env()).await?;
+        }
+        report_call(Some(signal.action), porcelain, exit_code);
+        return Ok(());
+    }
+
+    if dry_run {
+        // Everything up to the model request, to check a configuration without spending tokens or notifying anyone
+        let dry_run = pipeline.dry_run().await?;
+        if json_only {
+            println!("{}", serde_json::to_string_pretty(&dry_run)?);
+        } else {
+            println!("\n{}", terminal::paint(&renderer::render_dry_run(&dry_run)));
+        }
+        return Ok(());
+    }
+
+    if json_only {
+        // Progress messages go to stderr so stdout stays valid JSON
+        println!("{}", renderer::render_json(&pipeline.report().await?)?);
+        return Ok(());
+    }
+
+    if only_indicators {
+        // The numbers on their own, for scheduled runs that don't need the model's reading
+        return pipeline.run_indicators().await;
+    }
+
+    if let Some(variants) = ab_variants {
+        return pipeline.run_ab(variants).await;
+    }
+
     if only_prompt {
         // Display only the prompt
-        println!("\n=== PROMPT ===\n");
+        let prompt = pipeline.prompt().await?;
+        println!("\n=== {} ===\n", i18n::t("PROMPT"));
         println!("{}", prompt);
-        println!("\n===============================");    } else {        // Get analysis from Claude
-        let analysis = ai_client::get_analysis_from_claude(&api_key, &prompt).await?;
-        
-        // Use the output module to handle the output formatting
-        output::send_output(&analysis, output_format).await?;    }
-    
+        println!("\n===============================");
+        return Ok(());
+    }
+
+    let action = pipeline.run().await?;
+    report_call(action, porcelain, exit_code);
     Ok(())
 }
+
+/// Check the market every WATCH_INTERVAL_MINUTES and run the analysis when the composite signal, the rule-based call,
+/// or support or resistance (by WATCH_LEVEL_CHANGE_PCT) has changed since the last one; the first check always runs it
+async fn run_watch(args: AnalyzeArgs, json_only: bool) -> Result<(), Box<dyn Error>> {
+    let config = watch::WatchConfig::from_env();
+    let rules = signals::SignalRules::from_env();
+    let strategies = strategy::load_strategies()?;
+    let mut analyzed: Option<watch::WatchState> = None;
+
+    loop {
+        let checked = fetch_market(&args.market).await.map(|(data, fear_and_greed_data)| {
+            let report = technical_analysis::build_technical_report(&data, &fear_and_greed_data);
+            watch::WatchState::new(&report, &signals::generate_signal(&report, &rules, &strategies))
+        });
+        // Keep watching through transient API failures
+        match checked {
+            Err(e) => log::warn!("Watch check failed: {}", e),
+            Ok(state) => {
+                let changes = analyzed.as_ref().map(|previous| state.changes(previous, &config));
+                match changes {
+                    Some(changes) if changes.is_empty() => log::info!("No meaningful change since the last analysis"),
+                    _ => {
+                        if let Some(changes) = changes {
+                            log::info!("Changed since the last analysis: {}", changes.join(", "));
+                        }
+                        match run_analysis(args.clone(), json_only).await {
+                            Ok(()) => analyzed = Some(state),
+                            // A failing first analysis is usually a setup problem such as a missing key, so it ends the watch
+                            Err(e) if analyzed.is_none() => return Err(e),
+                            Err(e) => log::warn!("Analysis failed: {}", e),
+                        }
+                    }
+                }
+            }
+        }
+        log::info!("Next check in {} minutes", config.interval.as_secs() / 60);
+        tokio::time::sleep(config.interval).await;
+    }
+}
+
+/// For scripts: with --porcelain print just the call (BUY, SELL, HOLD or NONE), and with --exit-code
+/// exit with 10 for BUY, 11 for SELL, 12 for HOLD or 13 when there is no call
+fn report_call(action: Option<signals::Action>, porcelain: bool, exit_code: bool) {
+    if porcelain {
+        println!("{}", action.map(|action| action.as_str()).unwrap_or("NONE"));
+    }
+    if exit_code {
+        std::process::exit(match action {
+            Some(signals::Action::Buy) => 10,
+            Some(signals::Action::Sell) => 11,
+            Some(signals::Action::Hold) => 12,
+            None => 13,
+        });
+    }
+}
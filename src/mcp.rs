@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use crate::cli;
+use crate::output::{Delivery, OutputSink};
+use crate::pipeline::Pipeline;
+use crate::renderer;
+
+/// Protocol version answered when the client doesn't name one
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Candles returned by get_market_data unless the caller asks for another number
+const DEFAULT_CANDLES: usize = 50;
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve the data pipeline to agent hosts such as Claude Desktop over the Model Context Protocol: JSON-RPC messages,
+/// one per line, on stdin and stdout. Logs stay on stderr. Tools analyze `symbol` (default `default_symbol`) at the
+/// interval this process was started with, as the candle interval is shared by every fetch.
+pub async fn run_mcp(default_symbol: &str, interval: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    log::info!("MCP server ready on stdin/stdout");
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle(&message, default_symbol, interval).await,
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, &format!("Invalid JSON: {}", e))),
+        };
+        // Notifications (messages without an id) get no answer
+        if let Some(response) = response {
+            stdout.write_all(format!("{}\n", response).as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// The answer to one request, or None for a notification
+async fn handle(message: &Value, default_symbol: &str, interval: Option<&str>) -> Option<Value> {
+    let id = message.get("id")?.clone();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match message["method"].as_str().unwrap_or_default() {
+        "initialize" => json!({
+            "protocolVersion": params["protocolVersion"].as_str().unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "crypto-forecast", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tools(default_symbol) }),
+        "tools/call" => {
+            let arguments = &params["arguments"];
+            let symbol = match arguments["symbol"].as_str() {
+                Some(symbol) => match cli::parse_symbol(symbol) {
+                    Ok(symbol) => symbol,
+                    Err(e) => return Some(error(id, INVALID_PARAMS, &format!("Invalid symbol: {}", e))),
+                },
+                None => default_symbol.to_string(),
+            };
+            let mut pipeline = Pipeline::new().symbol(&symbol);
+            if let Some(interval) = interval {
+                pipeline = pipeline.interval(interval);
+            }
+            let outcome = match params["name"].as_str().unwrap_or_default() {
+                "get_market_data" => market_data(pipeline, arguments["candles"].as_u64().map(|count| count as usize)).await,
+                "get_indicators" => indicators(pipeline).await,
+                "run_analysis" => analysis(pipeline).await,
+                name => return Some(error(id, INVALID_PARAMS, &format!("Unknown tool '{}'", name))),
+            };
+            // Failures of the tool itself go back to the model as a result it can read
+            match outcome {
+                Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+                Err(e) => json!({ "content": [{ "type": "text", "text": e.to_string() }], "isError": true }),
+            }
+        }
+        method => return Some(error(id, METHOD_NOT_FOUND, &format!("Unknown method '{}'", method))),
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// The tools with their input schemas
+fn tools(default_symbol: &str) -> Value {
+    let symbol = json!({ "type": "string", "description": format!("Binance symbol, e.g. ETHUSDT (default {})", default_symbol) });
+    json!([
+        {
+            "name": "get_market_data",
+            "description": "Latest price and the most recent OHLCV candles for a symbol",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "symbol": symbol,
+                    "candles": { "type": "integer", "minimum": 1, "description": format!("Number of candles (default {})", DEFAULT_CANDLES) },
+                },
+            },
+        },
+        {
+            "name": "get_indicators",
+            "description": "Technical report for a symbol: indicators, composite score, support and resistance, and Fear & Greed",
+            "inputSchema": { "type": "object", "properties": { "symbol": symbol } },
+        },
+        {
+            "name": "run_analysis",
+            "description": "Full AI market analysis of a symbol with the configured model and prompt; takes up to a few minutes",
+            "inputSchema": { "type": "object", "properties": { "symbol": symbol } },
+        },
+    ])
+}
+
+/// The last price and the most recent candles as JSON
+async fn market_data(pipeline: Pipeline, count: Option<usize>) -> Result<String, Box<dyn Error>> {
+    let (data, _) = pipeline.fetch().await?;
+    let candles = data.candles();
+    let recent = &candles[candles.len().saturating_sub(count.unwrap_or(DEFAULT_CANDLES).max(1))..];
+    let candles: Vec<Value> = recent.iter()
+        .map(|candle| json!({
+            "time": candle.timestamp, "open": candle.open, "high": candle.high, "low": candle.low, "close": candle.close, "volume": candle.volume,
+        }))
+        .collect();
+    Ok(serde_json::to_string_pretty(&json!({ "price": recent.last().map(|candle| candle.close), "candles": candles }))?)
+}
+
+async fn indicators(pipeline: Pipeline) -> Result<String, Box<dyn Error>> {
+    Ok(renderer::render_json(&pipeline.report().await?)?)
+}
+
+/// The analysis as it would be delivered, caught instead of sent anywhere
+async fn analysis(pipeline: Pipeline) -> Result<String, Box<dyn Error>> {
+    let text = Arc::new(Mutex::new(None));
+    pipeline.sink(Box::new(CaptureSink(text.clone()))).run().await?;
+    let text = text.lock().map_err(|_| "The analysis could not be read")?.take();
+    Ok(text.unwrap_or_default())
+}
+
+/// Keeps the delivered text for the tool result
+struct CaptureSink(Arc<Mutex<Option<String>>>);
+
+#[async_trait]
+impl OutputSink for CaptureSink {
+    fn name(&self) -> &str {
+        "mcp"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        *self.0.lock().map_err(|_| "The analysis could not be kept")? = Some(delivery.text.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::ai_client::{Analysis, AnalysisResult};
+use crate::currency::{self, Currency};
+use crate::report::TechnicalReport;
+use crate::signals::Action;
+
+/// Default location of the persisted analysis memory
+const DEFAULT_MEMORY_FILE: &str = "analysis_memory.json";
+
+/// Longest fallback summary taken from the prose when no structured result is available
+const MAX_SUMMARY_CHARS: usize = 600;
+
+/// One earlier analysis, condensed for the next prompt
+#[derive(Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub time: DateTime<Utc>,
+    pub price: f64,                     // In `currency`
+    #[serde(default)]
+    pub currency: Currency,
+    #[serde(default = "currency::unit_rate")]
+    pub usd_rate: f64,                  // Units of `currency` per US dollar when the analysis was written
+    pub recommendation: Option<Action>,
+    pub summary: String,
+    pub analysis: String,
+}
+
+impl MemoryEntry {
+    /// The price at the time of the analysis, converted at today's `usd_rate` into the currency it is being compared in
+    pub fn price_at(&self, usd_rate: f64) -> f64 {
+        self.price / self.usd_rate * usd_rate
+    }
+}
+
+/// The last few analyses, persisted between runs so the model can refer back to its calls
+#[derive(Default, Serialize, Deserialize)]
+pub struct AnalysisMemory {
+    pub entries: Vec<MemoryEntry>,
+}
+
+/// Memory settings: ANALYSIS_MEMORY_FILE and ANALYSIS_MEMORY_RUNS (0 disables memory)
+pub struct MemoryConfig {
+    pub file: String,
+    pub runs: usize,
+}
+
+impl MemoryConfig {
+    pub fn from_env() -> Self {
+        MemoryConfig {
+            file: env::var("ANALYSIS_MEMORY_FILE").unwrap_or_else(|_| DEFAULT_MEMORY_FILE.to_string()),
+            runs: env::var("ANALYSIS_MEMORY_RUNS").ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(5),
+        }
+    }
+}
+
+impl AnalysisMemory {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        if !Path::new(path).exists() {
+            return Ok(AnalysisMemory::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The most recent `runs` entries, oldest first
+    pub fn recent(&self, runs: usize) -> &[MemoryEntry] {
+        &self.entries[self.entries.len().saturating_sub(runs)..]
+    }
+
+    /// The analysis stored by the last run, if any
+    pub fn last(&self) -> Option<&MemoryEntry> {
+        self.entries.last()
+    }
+
+    /// Remember an analysis with the price it was written at, keeping only the most recent `runs`
+    pub fn record(&mut self, analysis: &Analysis, report: &TechnicalReport, runs: usize) {
+        self.entries.push(MemoryEntry {
+            time: Utc::now(),
+            price: report.last_price(),
+            currency: report.currency,
+            usd_rate: report.usd_rate,
+            recommendation: analysis.action(),
+            summary: summarize(analysis.result.as_ref(), &analysis.text, report.currency),
+            analysis: analysis.text.clone(),
+        });
+
+        let excess = self.entries.len().saturating_sub(runs);
+        self.entries.drain(..excess);
+    }
+}
+
+/// The structured call when there is one, otherwise the overall recommendation section of the prose
+pub fn summarize(result: Option<&AnalysisResult>, text: &str, currency: Currency) -> String {
+    match result {
+        Some(result) => summarize_result(result, currency),
+        None => summarize_text(text),
+    }
+}
+
+/// One line with the structured call and its levels
+fn summarize_result(result: &AnalysisResult, currency: Currency) -> String {
+    let prices = |prices: &[f64]| prices.iter().map(|price| currency.round(*price)).collect::<Vec<_>>().join("/");
+
+    let mut summary = format!("{} ({:.0}% confidence), {}, {} risk", result.outlook.as_str(), result.confidence,
+        result.recommendation.as_str(), result.risk_level.as_str().to_lowercase());
+    for (label, levels) in [("entries", &result.entries), ("stops", &result.stop_losses), ("targets", &result.take_profits)] {
+        if !levels.is_empty() {
+            summary.push_str(&format!(", {} {}", label, prices(levels)));
+        }
+    }
+    if !result.summary.is_empty() {
+        summary.push_str(&format!(". {}", result.summary));
+    }
+    summary
+}
+
+/// The overall recommendation section of the prose, shortened
+fn summarize_text(text: &str) -> String {
+    let lower = text.to_lowercase();
+    let start = lower.rfind("overall recommendation").unwrap_or(0);
+    let section = text[start..].split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if section.chars().count() > MAX_SUMMARY_CHARS {
+        format!("{}...", section.chars().take(MAX_SUMMARY_CHARS).collect::<String>())
+    } else {
+        section
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_from_before_currencies_count_as_dollars() {
+        let entry: MemoryEntry = serde_json::from_str(r#"{"time": "2025-05-01T00:00:00Z", "price": 95000.0,
+            "recommendation": "buy", "summary": "", "analysis": ""}"#).unwrap();
+        assert_eq!(entry.currency, Currency::USD);
+        assert_eq!(entry.price_at(1.0), 95000.0);
+    }
+
+    #[test]
+    fn prices_are_converted_into_the_current_currency() {
+        let entry: MemoryEntry = serde_json::from_str(r#"{"time": "2025-05-01T00:00:00Z", "price": 87400.0, "currency": "EUR",
+            "usd_rate": 0.92, "recommendation": null, "summary": "", "analysis": ""}"#).unwrap();
+        assert!((entry.price_at(1.0) - 95000.0).abs() < 1e-6);
+        assert!((entry.price_at(0.8) - 76000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn the_prose_summary_starts_at_the_overall_recommendation() {
+        let summary = summarize(None, "1. Trend: up\n\n6. Overall Recommendation:\nBUY   on dips", Currency::USD);
+        assert_eq!(summary, "Overall Recommendation: BUY on dips");
+    }
+}
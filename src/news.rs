@@ -0,0 +1,111 @@
+use std::env;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::data_fetcher::{self, NewsArticle};
+
+/// Bitcoin headlines from CryptoCompare's public news API
+const DEFAULT_NEWS_URL: &str = "https://min-api.cryptocompare.com/data/v2/news/?lang=EN&categories=BTC";
+
+/// Outlets treated as high credibility; NEWS_TRUSTED_SOURCES adds more
+const TRUSTED_SOURCES: [&str; 10] = ["coindesk", "the block", "theblock", "reuters", "bloomberg", "decrypt",
+    "cointelegraph", "blockworks", "financial times", "wall street journal"];
+
+/// Weight given to headlines from outlets not on the trusted list
+const UNVERIFIED_SOURCE_WEIGHT: f64 = 0.6;
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Credibility {
+    High,
+    Medium,
+}
+
+impl Credibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Credibility::High => "high",
+            Credibility::Medium => "medium",
+        }
+    }
+}
+
+/// A recent headline with how much it should count: newer and more credible means a higher weight
+#[derive(Serialize)]
+pub struct NewsHeadline {
+    pub published_at: DateTime<Utc>,
+    pub source: String,
+    pub title: String,
+    pub credibility: Credibility,
+    pub weight: f64,               // 0-1, halving every NEWS_HALF_LIFE_HOURS
+}
+
+fn env_number(name: &str, default: f64) -> f64 {
+    env::var(name).ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|value: &f64| *value > 0.0)
+        .unwrap_or(default)
+}
+
+/// Headlines when NEWS_ENABLED=true, most relevant first: up to NEWS_MAX_HEADLINES (default 10) from the last
+/// NEWS_MAX_AGE_HOURS (default 48), weighted by recency and source. A failed fetch returns None, leaving the news out rather than
+/// failing the run.
+pub async fn load_news() -> Option<Vec<NewsHeadline>> {
+    if !env::var("NEWS_ENABLED").map(|value| value == "true").unwrap_or(false) {
+        return Some(Vec::new());
+    }
+
+    log::info!("Fetching news headlines...");
+    let url = env::var("NEWS_API_URL").unwrap_or_else(|_| DEFAULT_NEWS_URL.to_string());
+    match data_fetcher::fetch_news(&url).await {
+        Ok(articles) => Some(weigh_headlines(articles, Utc::now())),
+        Err(e) => {
+            log::warn!("could not fetch news, continuing without it: {}", e);
+            None
+        }
+    }
+}
+
+fn weigh_headlines(articles: Vec<NewsArticle>, now: DateTime<Utc>) -> Vec<NewsHeadline> {
+    let max_age_hours = env_number("NEWS_MAX_AGE_HOURS", 48.0);
+    let half_life_hours = env_number("NEWS_HALF_LIFE_HOURS", 12.0);
+    let max_headlines = env_number("NEWS_MAX_HEADLINES", 10.0) as usize;
+    let trusted: Vec<String> = env::var("NEWS_TRUSTED_SOURCES").unwrap_or_default()
+        .split(',')
+        .map(|source| source.trim().to_lowercase())
+        .filter(|source| !source.is_empty())
+        .chain(TRUSTED_SOURCES.iter().map(|source| source.to_string()))
+        .collect();
+
+    let mut headlines: Vec<NewsHeadline> = articles.into_iter()
+        .filter_map(|article| {
+            let published_at = DateTime::from_timestamp(article.published_on, 0)?;
+            let age_hours = (now - published_at).num_minutes().max(0) as f64 / 60.0;
+            if age_hours > max_age_hours {
+                return None;
+            }
+
+            let source = if article.source_info.name.is_empty() { article.source } else { article.source_info.name };
+            let credibility = if trusted.iter().any(|trusted| source.to_lowercase().contains(trusted.as_str())) {
+                Credibility::High
+            } else {
+                Credibility::Medium
+            };
+            let source_weight = match credibility {
+                Credibility::High => 1.0,
+                Credibility::Medium => UNVERIFIED_SOURCE_WEIGHT,
+            };
+
+            Some(NewsHeadline {
+                published_at,
+                source,
+                title: article.title.trim().to_string(),
+                credibility,
+                weight: source_weight * 0.5_f64.powf(age_hours / half_life_hours),
+            })
+        })
+        .collect();
+
+    headlines.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    headlines.truncate(max_headlines);
+    headlines
+}
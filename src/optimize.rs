@@ -0,0 +1,121 @@
+use std::error::Error;
+use serde::Serialize;
+use crate::backtest::{self, BacktestConfig, SignalSource};
+use crate::data_fetcher::Candle;
+use crate::indicators::IndicatorParams;
+use crate::signals::SignalRules;
+use crate::strategy::Strategy;
+
+/// Number of configurations listed in the result
+const TOP_CONFIGURATIONS: usize = 5;
+
+/// Fewer trades than this is too little evidence to call a configuration robust
+const MIN_TRADES: usize = 3;
+
+/// Backtest outcome of one parameter combination
+#[derive(Serialize)]
+pub struct Candidate {
+    pub indicators: IndicatorParams,
+    pub rules: SignalRules,
+    pub total_return_pct: f64,
+    pub max_drawdown_pct: f64,
+    pub win_rate: f64,
+    pub trades: usize,
+    pub first_half_return_pct: f64,
+    pub second_half_return_pct: f64,
+    pub robust: bool,          // Profitable in both halves with enough trades
+    pub score: f64,            // Return relative to drawdown
+}
+
+/// Best configurations found by the grid search
+#[derive(Serialize)]
+pub struct OptimizationResult {
+    pub source: String,
+    pub tested: usize,
+    pub best: Vec<Candidate>,
+}
+
+/// Sweep indicator and rule parameters in parallel and rank the configurations by robustness
+pub fn run_optimization(candles: Vec<Candle>, strategies: Vec<Strategy>, strategy_name: Option<String>,
+                        base_rules: SignalRules, base_config: BacktestConfig) -> Result<OptimizationResult, Box<dyn Error>> {
+    let source = SignalSource::select(&strategies, strategy_name.as_deref())?;
+    let grid = parameter_grid(&base_rules);
+    let tested = grid.len();
+
+    // One worker thread per core, each backtesting its share of the grid with the selected source
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let chunk_size = tested.div_ceil(workers).max(1);
+    let results = std::thread::scope(|scope| {
+        let handles: Vec<_> = grid.chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| chunk.iter()
+                .map(|(indicators, rules)| evaluate(&candles, &source, *indicators, rules.clone(), &base_config))
+                .collect::<Vec<Candidate>>()))
+            .collect();
+        handles.into_iter().map(|handle| handle.join()).collect::<Result<Vec<_>, _>>()
+    });
+    let mut candidates: Vec<Candidate> = results.map_err(|_| "An optimization worker failed")?.into_iter().flatten().collect();
+
+    // Robust configurations first, then by return relative to drawdown
+    candidates.sort_by(|a, b| b.robust.cmp(&a.robust)
+        .then(b.score.total_cmp(&a.score)));
+    candidates.truncate(TOP_CONFIGURATIONS);
+
+    Ok(OptimizationResult { source: source.describe(), tested, best: candidates })
+}
+
+fn evaluate(candles: &[Candle], source: &SignalSource, indicators: IndicatorParams, rules: SignalRules,
+            base_config: &BacktestConfig) -> Candidate {
+    let config = BacktestConfig { indicators, ..base_config.clone() };
+    let middle = candles.len() / 2;
+
+    let full = backtest::run_backtest(candles, source, &rules, &config);
+    let first_half = backtest::run_backtest_range(candles, 0..middle, source, &rules, &config);
+    let second_half = backtest::run_backtest_range(candles, middle..candles.len(), source, &rules, &config);
+
+    Candidate {
+        indicators,
+        rules,
+        total_return_pct: full.total_return_pct,
+        max_drawdown_pct: full.max_drawdown_pct,
+        win_rate: full.win_rate,
+        trades: full.trades.len(),
+        first_half_return_pct: first_half.total_return_pct,
+        second_half_return_pct: second_half.total_return_pct,
+        robust: first_half.total_return_pct > 0.0 && second_half.total_return_pct > 0.0 && full.trades.len() >= MIN_TRADES,
+        score: full.total_return_pct / full.max_drawdown_pct.max(1.0),
+    }
+}
+
+/// Every combination of the swept indicator settings and signal thresholds
+fn parameter_grid(base_rules: &SignalRules) -> Vec<(IndicatorParams, SignalRules)> {
+    let mut grid = Vec::new();
+
+    for rsi_period in [10, 14, 21] {
+        for (macd_fast, macd_slow, macd_signal) in [(8, 21, 5), (12, 26, 9)] {
+            for bollinger_std_dev in [2.0, 2.5] {
+                for buy_score in [10.0, 15.0, 25.0] {
+                    for (stop_atr, target_atr) in [(1.5, 3.0), (2.0, 4.0)] {
+                        let indicators = IndicatorParams {
+                            rsi_period,
+                            macd_fast,
+                            macd_slow,
+                            macd_signal,
+                            bollinger_std_dev,
+                            ..IndicatorParams::default()
+                        };
+                        let rules = SignalRules {
+                            buy_score,
+                            sell_score: -buy_score,
+                            stop_atr,
+                            target_atr,
+                            ..base_rules.clone()
+                        };
+                        grid.push((indicators, rules));
+                    }
+                }
+            }
+        }
+    }
+
+    grid
+}
@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::html::{self, escape};
+use crate::memory;
+use super::{Delivery, OutputSink, Settings};
+
+/// Default location of the Atom feed; its entries are kept next to it as JSON
+const DEFAULT_FEED_FILE: &str = "feed.xml";
+
+/// Entries kept when FEED_MAX_ENTRIES is not set
+const DEFAULT_MAX_ENTRIES: usize = 20;
+
+/// One published analysis
+#[derive(Serialize, Deserialize)]
+struct FeedEntry {
+    id: String,
+    title: String,
+    published: DateTime<Utc>,
+    summary: String,
+    body: String,                  // The analysis as Markdown
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct FeedState {
+    entries: Vec<FeedEntry>,
+}
+
+/// Adds each report to the Atom feed at FEED_FILE (default feed.xml), keeping the newest FEED_MAX_ENTRIES (default 20).
+/// FEED_TITLE names the feed and FEED_URL, where it will be served from, becomes its self link.
+pub struct FeedSink {
+    path: String,
+    max_entries: usize,
+    title: String,
+    url: Option<String>,
+}
+
+impl FeedSink {
+    pub fn from_settings(settings: &Settings) -> Self {
+        FeedSink {
+            path: settings.get_or("file", "FEED_FILE", DEFAULT_FEED_FILE),
+            max_entries: settings.get("max_entries", "FEED_MAX_ENTRIES")
+                .and_then(|value| value.trim().parse().ok())
+                .filter(|entries: &usize| *entries > 0)
+                .unwrap_or(DEFAULT_MAX_ENTRIES),
+            title: settings.get_or("title", "FEED_TITLE", "Crypto-Forecast analyses"),
+            url: settings.get("url", "FEED_URL"),
+        }
+    }
+
+    /// The feed as Atom XML, newest entry first
+    fn render_atom(&self, state: &FeedState, updated: DateTime<Utc>) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str(&format!("  <title>{}</title>\n", escape(&self.title)));
+        xml.push_str(&format!("  <id>{}</id>\n", escape(self.url.as_deref().unwrap_or("urn:crypto-forecast:feed"))));
+        if let Some(url) = &self.url {
+            xml.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", escape(url)));
+        }
+        xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+        xml.push_str("  <author><name>crypto-forecast</name></author>\n");
+
+        for entry in state.entries.iter().rev() {
+            xml.push_str("  <entry>\n");
+            xml.push_str(&format!("    <id>{}</id>\n", escape(&entry.id)));
+            xml.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+            xml.push_str(&format!("    <published>{}</published>\n", entry.published.to_rfc3339()));
+            xml.push_str(&format!("    <updated>{}</updated>\n", entry.published.to_rfc3339()));
+            xml.push_str(&format!("    <summary>{}</summary>\n", escape(&entry.summary)));
+            xml.push_str(&format!("    <content type=\"html\">{}</content>\n", escape(&html::render_markdown(&entry.body))));
+            xml.push_str("  </entry>\n");
+        }
+        xml.push_str("</feed>\n");
+        xml
+    }
+}
+
+#[async_trait]
+impl OutputSink for FeedSink {
+    fn name(&self) -> &str {
+        "feed"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let state_path = Path::new(&self.path).with_extension("json");
+
+        let mut state: FeedState = if state_path.exists() {
+            serde_json::from_str(&fs::read_to_string(&state_path)?)?
+        } else {
+            FeedState::default()
+        };
+
+        let now = Utc::now();
+        let mut title = delivery.symbol.to_string();
+        if let Some(action) = delivery.analysis.and_then(|analysis| analysis.action()) {
+            title.push_str(&format!(" {}", action.as_str()));
+        }
+        if let Some(report) = delivery.report {
+            title.push_str(&format!(" at {}", report.currency.price(report.last_price())));
+        }
+        title.push_str(&format!(", {}", now.format("%Y-%m-%d %H:%M UTC")));
+
+        state.entries.push(FeedEntry {
+            id: format!("urn:crypto-forecast:{}:{}", delivery.symbol, now.timestamp()),
+            title,
+            published: now,
+            summary: memory::summarize(delivery.analysis.and_then(|analysis| analysis.result.as_ref()), delivery.text, delivery.currency()),
+            body: delivery.text.to_string(),
+        });
+        let excess = state.entries.len().saturating_sub(self.max_entries);
+        state.entries.drain(..excess);
+
+        fs::write(&state_path, serde_json::to_string_pretty(&state)?)?;
+        fs::write(&self.path, self.render_atom(&state, now))?;
+        log::info!("Feed updated: {} ({} entries)", self.path, state.entries.len());
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use chrono::Utc;
+use super::{markdown_summary, Delivery, OutputSink, Settings};
+
+/// Appends each report as Markdown to REPORTS_DIR/{symbol}/{date}.md (default reports/), one section per run
+pub struct FileSink {
+    directory: PathBuf,
+}
+
+impl FileSink {
+    pub fn from_settings(settings: &Settings) -> Self {
+        FileSink { directory: PathBuf::from(settings.get_or("dir", "REPORTS_DIR", "reports")) }
+    }
+}
+
+#[async_trait]
+impl OutputSink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let now = Utc::now();
+        let directory = self.directory.join(delivery.symbol);
+        fs::create_dir_all(&directory)?;
+        let path = directory.join(format!("{}.md", now.format("%Y-%m-%d")));
+
+        let markdown = format!("## {} {}\n\n{}{}\n\n", delivery.symbol, now.format("%Y-%m-%d %H:%M UTC"),
+            markdown_summary(delivery), delivery.text.trim_end());
+        OpenOptions::new().create(true).append(true).open(&path)?.write_all(markdown.as_bytes())?;
+        log::info!("Report written to {}", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use std::error::Error;
+use reqwest::Client;
+use chrono::Utc;
+use serde_json::json;
+use crate::html;
+use super::{markdown_summary, Delivery, OutputSink, Settings};
+
+/// GitHub's limit on an issue body
+const GITHUB_ISSUE_MAX_CHARS: usize = 65536;
+
+/// Opens an issue with the report in GITHUB_REPOSITORY (owner/repo) using GITHUB_TOKEN, labelled with GITHUB_ISSUE_LABELS
+pub struct GitHubSink {
+    token: String,
+    repository: String,
+    api_url: String,
+    labels: Vec<String>,
+}
+
+impl GitHubSink {
+    pub fn from_settings(settings: &Settings) -> Result<Self, Box<dyn Error>> {
+        Ok(GitHubSink {
+            token: settings.require("token", "GITHUB_TOKEN", "github")?,
+            repository: settings.require("repository", "GITHUB_REPOSITORY", "github")?,
+            // GitHub Actions sets GITHUB_API_URL, which also covers GitHub Enterprise Server
+            api_url: settings.get_or("api_url", "GITHUB_API_URL", "https://api.github.com"),
+            labels: settings.get_or("labels", "GITHUB_ISSUE_LABELS", "analysis")
+                .split(',')
+                .map(|label| label.trim().to_string())
+                .filter(|label| !label.is_empty())
+                .collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl OutputSink for GitHubSink {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let mut title = format!("{} analysis {}", delivery.symbol, Utc::now().format("%Y-%m-%d %H:%M UTC"));
+        if let Some(action) = delivery.analysis.and_then(|analysis| analysis.action()) {
+            title.push_str(&format!(": {}", action.as_str()));
+        }
+        let mut body = format!("{}{}", markdown_summary(delivery), html::strip_bare_tags(delivery.text));
+        if body.chars().count() > GITHUB_ISSUE_MAX_CHARS {
+            body = format!("{}\n\n_(truncated)_", body.chars().take(GITHUB_ISSUE_MAX_CHARS - 20).collect::<String>());
+        }
+
+        let response = Client::new()
+            .post(format!("{}/repos/{}/issues", self.api_url.trim_end_matches('/'), self.repository))
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "crypto-forecast")
+            .json(&json!({ "title": title, "body": body, "labels": self.labels }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("Creating the GitHub issue failed with status {}: {}", response.status(), response.text().await?).into());
+        }
+        let issue: serde_json::Value = response.json().await?;
+        log::info!("Created GitHub issue {}", issue["html_url"].as_str().unwrap_or_default());
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::fs;
+use crate::html;
+use super::{Delivery, OutputSink, Settings};
+
+/// Writes the report as a self-contained HTML page to HTML_REPORT_PATH (default report.html)
+pub struct HtmlSink {
+    path: String,
+    chart: bool,    // Embed the candlestick chart unless HTML_CHART=false
+}
+
+impl HtmlSink {
+    pub fn from_settings(settings: &Settings) -> Self {
+        HtmlSink {
+            path: settings.get_or("path", "HTML_REPORT_PATH", "report.html"),
+            chart: settings.get("chart", "HTML_CHART").is_none_or(|value| value != "false"),
+        }
+    }
+}
+
+#[async_trait]
+impl OutputSink for HtmlSink {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn wants_chart(&self) -> bool {
+        self.chart
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        fs::write(&self.path, html::render_report(delivery.symbol, delivery.text, delivery.report, delivery.chart.filter(|_| self.chart)))?;
+        log::info!("HTML report written to {}", self.path);
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use chrono::Utc;
+use tera::{Context, Tera};
+use crate::prompt_generator::describe;
+use crate::renderer;
+use super::Delivery;
+
+/// Default location of the report layout
+const DEFAULT_LAYOUT_FILE: &str = "report.tera";
+
+/// The layout of the finished report as a Tera template, applied before it goes to any destination.
+/// Templates can use `{{ analysis }}`, `{{ result }}` and `{{ recommendation }}` (the model's prose and the structured
+/// sections, empty when missing), `{{ report }}` (the report in the default layout), and `{{ symbol }}`, `{{ date }}`,
+/// `{{ price }}`, `{{ call }}`, `{{ score }}`, `{{ score_label }}`, `{{ support }}` and `{{ resistance }}` for headers and footers.
+#[derive(Default)]
+pub struct ReportLayout {
+    tera: Option<Tera>,
+}
+
+impl ReportLayout {
+    /// Load REPORT_TEMPLATE (default report.tera); without a template the report keeps its default layout
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let source = match env::var("REPORT_TEMPLATE") {
+            Ok(path) => fs::read_to_string(&path).map_err(|e| format!("Could not read report template {}: {}", path, e))?,
+            Err(_) if Path::new(DEFAULT_LAYOUT_FILE).exists() => fs::read_to_string(DEFAULT_LAYOUT_FILE)?,
+            Err(_) => return Ok(ReportLayout::default()),
+        };
+        let mut tera = Tera::default();
+        tera.add_raw_template("report", &source).map_err(|e| format!("Invalid report template: {}", describe(&e)))?;
+
+        // Render once with empty values so a template using unknown variables fails now rather than after the analysis
+        let layout = ReportLayout { tera: Some(tera) };
+        let empty = Delivery { text: "", symbol: "", report: None, analysis: None, chart: None, candles: &[] };
+        layout.apply(&empty).map_err(|e| format!("Invalid report template: {}", describe(&e)))?;
+        Ok(layout)
+    }
+
+    /// The delivery's text in this layout, or None when the default layout is used
+    pub fn apply(&self, delivery: &Delivery<'_>) -> Result<Option<String>, tera::Error> {
+        let Some(tera) = &self.tera else { return Ok(None) };
+        let analysis = delivery.analysis;
+        let report = delivery.report;
+        let price = |value: Option<f64>| value.map(|value| format!("{:.2}", value)).unwrap_or_default();
+
+        let mut context = Context::new();
+        // The rule-based fallback has no separate prose, so its whole text stands in for the analysis
+        context.insert("analysis", analysis.map(|analysis| analysis.text.as_str()).unwrap_or(delivery.text));
+        context.insert("result", &analysis.and_then(|analysis| analysis.result.as_ref())
+            .map(|result| renderer::render_analysis_result(result, delivery.currency())).unwrap_or_default());
+        context.insert("recommendation", &analysis.and_then(|analysis| analysis.recommendation.as_ref())
+            .map(|recommendation| renderer::render_recommendation(recommendation, delivery.currency())).unwrap_or_default());
+        context.insert("report", delivery.text);
+        context.insert("symbol", delivery.symbol);
+        context.insert("date", &Utc::now().format("%Y-%m-%d %H:%M UTC").to_string());
+        context.insert("price", &price(report.map(|report| report.last_price())));
+        context.insert("call", analysis.and_then(|analysis| analysis.action()).map(|action| action.as_str()).unwrap_or_default());
+        context.insert("score", &report.map(|report| format!("{:+.0}", report.composite.score)).unwrap_or_default());
+        context.insert("score_label", report.map(|report| report.composite.label()).unwrap_or_default());
+        context.insert("support", &price(report.map(|report| report.levels.support)));
+        context.insert("resistance", &price(report.map(|report| report.levels.resistance)));
+        Ok(Some(tera.render("report", &context)?.trim().to_string()))
+    }
+}
@@ -0,0 +1,333 @@
+mod feed;
+mod file;
+mod github;
+mod html_report;
+mod layout;
+mod mqtt;
+mod notion;
+mod pushover;
+mod social;
+mod telegram;
+mod webhook;
+
+use async_trait::async_trait;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use crate::ai_client::Analysis;
+use crate::chart::{self, Chart};
+use crate::currency::Currency;
+use crate::data_fetcher::Candle;
+use crate::i18n;
+use crate::prompt_generator::describe;
+use crate::report::TechnicalReport;
+use crate::terminal;
+
+pub use feed::FeedSink;
+pub use file::FileSink;
+pub use github::GitHubSink;
+pub use html_report::HtmlSink;
+pub use layout::ReportLayout;
+pub use mqtt::MqttSink;
+pub use notion::NotionSink;
+pub use pushover::PushoverSink;
+pub use social::{MastodonSink, XSink};
+pub use telegram::TelegramSink;
+pub use webhook::WebhookSink;
+
+/// Default location of the output configuration
+const DEFAULT_OUTPUTS_FILE: &str = "outputs.toml";
+
+/// A finished report and the data behind it, for destinations that send more than the text
+pub struct Delivery<'a> {
+    pub text: &'a str,
+    pub symbol: &'a str,
+    pub report: Option<&'a TechnicalReport>,
+    pub analysis: Option<&'a Analysis>,    // None when the rule-based fallback was sent instead
+    pub chart: Option<&'a Chart>,
+    pub candles: &'a [Candle],             // Empty when the price history isn't at hand
+}
+
+impl Delivery<'_> {
+    /// What the prices in the report are in
+    pub fn currency(&self) -> Currency {
+        self.report.map(|report| report.currency).unwrap_or_default()
+    }
+}
+
+/// A destination for finished reports
+#[async_trait]
+pub trait OutputSink: Send + Sync {
+    /// Short name used on the command line and in outputs.toml (e.g. "telegram")
+    fn name(&self) -> &str;
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>>;
+
+    /// Whether the destination shows the candlestick chart, so it is only rendered when needed
+    fn wants_chart(&self) -> bool {
+        false
+    }
+
+    /// Send a short notification; destinations that only take full reports return false and the alert is printed instead
+    async fn send_alert(&self, _message: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(false)
+    }
+}
+
+/// One destination's settings: its entry in outputs.toml, falling back to environment variables
+#[derive(Default)]
+pub struct Settings {
+    table: toml::Table,
+}
+
+impl Settings {
+    /// `key` from outputs.toml, otherwise the environment variable
+    pub fn get(&self, key: &str, variable: &str) -> Option<String> {
+        match self.table.get(key) {
+            Some(toml::Value::String(value)) => Some(value.clone()),
+            Some(value) => Some(value.to_string()),
+            None => env::var(variable).ok(),
+        }
+    }
+
+    pub fn get_or(&self, key: &str, variable: &str, default: &str) -> String {
+        self.get(key, variable).unwrap_or_else(|| default.to_string())
+    }
+
+    pub fn require(&self, key: &str, variable: &str, sink: &str) -> Result<String, Box<dyn Error>> {
+        self.get(key, variable)
+            .ok_or_else(|| format!("{} must be set (or `{}` in outputs.toml) when using {} output format", variable, key, sink).into())
+    }
+}
+
+#[derive(Deserialize)]
+struct OutputFile {
+    #[serde(default)]
+    output: Vec<OutputConfig>,
+}
+
+#[derive(Deserialize)]
+struct OutputConfig {
+    sink: String,
+    #[serde(flatten)]
+    settings: toml::Table,
+}
+
+/// Build the named destination from its settings
+pub fn sink_by_name(name: &str, settings: &Settings) -> Result<Box<dyn OutputSink>, Box<dyn Error>> {
+    match name {
+        "text" => Ok(Box::new(TextSink::from_settings(settings))),
+        "json" => Ok(Box::new(JsonSink)),
+        "telegram" => Ok(Box::new(TelegramSink::from_settings(settings)?)),
+        "webhook" => Ok(Box::new(WebhookSink::from_settings(settings)?)),
+        "file" => Ok(Box::new(FileSink::from_settings(settings))),
+        "html" => Ok(Box::new(HtmlSink::from_settings(settings))),
+        "feed" => Ok(Box::new(FeedSink::from_settings(settings))),
+        "github" => Ok(Box::new(GitHubSink::from_settings(settings)?)),
+        "notion" => Ok(Box::new(NotionSink::from_settings(settings)?)),
+        "pushover" => Ok(Box::new(PushoverSink::from_settings(settings)?)),
+        "mastodon" => Ok(Box::new(MastodonSink::from_settings(settings)?)),
+        "x" => Ok(Box::new(XSink::from_settings(settings)?)),
+        "mqtt" => Ok(Box::new(MqttSink::from_settings(settings))),
+        other => Err(format!("Unknown output '{}', expected text, json, telegram, webhook, file, html, feed, github, notion, \
+            pushover, mastodon, x or mqtt", other).into()),
+    }
+}
+
+/// The destinations for a run
+pub struct Outputs {
+    sinks: Vec<Box<dyn OutputSink>>,
+    layout: ReportLayout,
+}
+
+impl Outputs {
+    /// The destinations given on the command line or in OUTPUTS (comma-separated, e.g. `telegram,file,json`), otherwise those listed in
+    /// OUTPUTS_FILE (default outputs.toml), otherwise the terminal. Settings in outputs.toml apply to the listed destinations too.
+    /// Reports are laid out by REPORT_TEMPLATE when one is set up.
+    pub fn load(list: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let path = env::var("OUTPUTS_FILE").unwrap_or_else(|_| DEFAULT_OUTPUTS_FILE.to_string());
+        let configured = if Path::new(&path).exists() {
+            let file: OutputFile = toml::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| format!("Invalid outputs file {}: {}", path, e))?;
+            file.output
+        } else {
+            Vec::new()
+        };
+
+        let list = list.map(str::to_string).or_else(|| env::var("OUTPUTS").ok());
+        let sinks = match list {
+            Some(list) => list.split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    let settings = configured.iter().find(|config| config.sink == name)
+                        .map(|config| Settings { table: config.settings.clone() })
+                        .unwrap_or_default();
+                    sink_by_name(name, &settings)
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None if !configured.is_empty() => configured.into_iter()
+                .map(|config| sink_by_name(&config.sink, &Settings { table: config.settings }))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => vec![Box::new(TextSink::from_settings(&Settings::default())) as Box<dyn OutputSink>],
+        };
+        Ok(Outputs { sinks, layout: ReportLayout::from_env()? })
+    }
+
+    /// These destinations, in the default layout
+    pub fn new(sinks: Vec<Box<dyn OutputSink>>) -> Self {
+        Outputs { sinks, layout: ReportLayout::default() }
+    }
+
+    /// Print to the terminal only, for commands that don't send reports
+    pub fn terminal() -> Self {
+        Outputs { sinks: vec![Box::new(TextSink::from_settings(&Settings::default()))], layout: ReportLayout::default() }
+    }
+
+    pub fn add(&mut self, sink: Box<dyn OutputSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Names of the destinations, in order
+    pub fn names(&self) -> Vec<&str> {
+        self.sinks.iter().map(|sink| sink.name()).collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.sinks.iter().any(|sink| sink.name() == name)
+    }
+
+    /// The same destinations without the named ones
+    pub fn without(mut self, names: &[&str]) -> Self {
+        self.sinks.retain(|sink| !names.contains(&sink.name()));
+        self
+    }
+
+    /// Split off the named destinations, which keep the layout; the rest are returned with the default layout
+    pub fn split_off(self, names: &[&str]) -> (Self, Self) {
+        let (named, rest) = self.sinks.into_iter().partition(|sink| names.contains(&sink.name()));
+        (Outputs { sinks: named, layout: self.layout }, Outputs::new(rest))
+    }
+
+    pub fn wants_chart(&self) -> bool {
+        self.sinks.iter().any(|sink| sink.wants_chart())
+    }
+
+    /// Send the report to every destination in the configured layout; one that fails is reported without stopping the others
+    pub async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let text = self.layout.apply(delivery).map_err(|e| format!("The report template failed to render: {}", describe(&e)))?;
+        let delivery = match &text {
+            Some(text) => &Delivery { text, ..*delivery },
+            None => delivery,
+        };
+
+        let mut failed = Vec::new();
+        for sink in &self.sinks {
+            if let Err(e) = sink.send(delivery).await {
+                log::warn!("Sending to {} failed: {}", sink.name(), e);
+                failed.push(sink.name());
+            }
+        }
+        if !failed.is_empty() {
+            return Err(format!("Output failed for {}", failed.join(", ")).into());
+        }
+        Ok(())
+    }
+
+    /// Send a short notification, independently of a full analysis; it is printed once if any destination can't take it
+    pub async fn send_alert(&self, message: &str) -> Result<(), Box<dyn Error>> {
+        let mut printed = false;
+        for sink in &self.sinks {
+            if !sink.send_alert(message).await? && !printed {
+                println!("{}", message);
+                printed = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Default text output with headers, colored when printed to a terminal
+struct TextSink {
+    chart: bool,
+}
+
+impl TextSink {
+    fn from_settings(settings: &Settings) -> Self {
+        TextSink { chart: settings.get_or("ascii_chart", "ASCII_CHART", "true") != "false" }
+    }
+}
+
+#[async_trait]
+impl OutputSink for TextSink {
+    fn name(&self) -> &str {
+        "text"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        // The price chart with the report's support and resistance goes above the analysis
+        let price_chart = delivery.report
+            .filter(|_| self.chart)
+            .and_then(|report| chart::render_ascii(delivery.candles, report.levels.support, report.levels.resistance));
+        if let Some(price_chart) = price_chart {
+            println!("\n{}", terminal::paint(&price_chart));
+        }
+        let text = format!("\n=== {} ===\n\n{}\n\n===============================", i18n::t("BITCOIN TRADING RECOMMENDATIONS"), delivery.text);
+        println!("{}", terminal::paint(&text));
+        Ok(())
+    }
+}
+
+/// The webhook payload, printed to stdout
+struct JsonSink;
+
+#[async_trait]
+impl OutputSink for JsonSink {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        println!("{}", serde_json::to_string_pretty(&json_payload(delivery))?);
+        Ok(())
+    }
+}
+
+/// The report as JSON: the indicators, the analysis text and the structured recommendation
+fn json_payload(delivery: &Delivery<'_>) -> serde_json::Value {
+    json!({
+        "timestamp": Utc::now(),
+        "symbol": delivery.symbol,
+        "price": delivery.report.map(|report| report.last_price()),
+        "composite": delivery.report.map(|report| &report.composite),
+        "indicators": delivery.report.map(|report| &report.indicators),
+        "analysis": delivery.text,
+        "recommendation": delivery.analysis.and_then(|analysis| analysis.recommendation.as_ref()),
+        "structured": delivery.analysis.and_then(|analysis| analysis.result.as_ref()),
+    })
+}
+
+/// Price and composite score as a Markdown list, when the report is available
+fn markdown_summary(delivery: &Delivery<'_>) -> String {
+    match delivery.report {
+        Some(report) => format!("- Price: {}\n- Composite score: {:+.0} ({})\n\n",
+            report.currency.price(report.last_price()), report.composite.score, report.composite.label()),
+        None => String::new(),
+    }
+}
+
+/// The symbol and the model's call, e.g. "BTCUSDT: Long (70% confidence)"
+fn headline(delivery: &Delivery<'_>) -> String {
+    let recommendation = delivery.analysis.and_then(|analysis| analysis.recommendation.as_ref());
+    let result = delivery.analysis.and_then(|analysis| analysis.result.as_ref());
+    match (recommendation, result) {
+        (Some(recommendation), _) => format!("{}: {} ({:.0}% confidence)", delivery.symbol,
+            recommendation.direction.as_str(), recommendation.confidence),
+        (None, Some(result)) => format!("{}: {} ({:.0}% confidence)", delivery.symbol, result.recommendation.as_str(), result.confidence),
+        (None, None) => format!("{} analysis", delivery.symbol),
+    }
+}
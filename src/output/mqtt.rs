@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::time::Duration;
+use chrono::Utc;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use serde_json::json;
+use super::{Delivery, OutputSink, Settings};
+
+/// Topic used when MQTT_TOPIC is not set; `{symbol}` is replaced with the analyzed symbol
+const DEFAULT_TOPIC: &str = "crypto-forecast/{symbol}";
+
+/// How long to wait for the broker to acknowledge the message
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Publishes the current signal as JSON to MQTT_TOPIC on the broker at MQTT_HOST:MQTT_PORT (default localhost:1883),
+/// logging in with MQTT_USERNAME / MQTT_PASSWORD when set. The message is retained unless MQTT_RETAIN=false,
+/// so dashboards such as Home Assistant show the latest signal as soon as they subscribe.
+pub struct MqttSink {
+    host: String,
+    port: u16,
+    topic: String,
+    retain: bool,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl MqttSink {
+    pub fn from_settings(settings: &Settings) -> Self {
+        MqttSink {
+            host: settings.get_or("host", "MQTT_HOST", "localhost"),
+            port: settings.get("port", "MQTT_PORT").and_then(|port| port.trim().parse().ok()).unwrap_or(1883),
+            topic: settings.get_or("topic", "MQTT_TOPIC", DEFAULT_TOPIC),
+            retain: settings.get("retain", "MQTT_RETAIN").map(|value| value != "false").unwrap_or(true),
+            username: settings.get("username", "MQTT_USERNAME"),
+            password: settings.get("password", "MQTT_PASSWORD"),
+        }
+    }
+}
+
+#[async_trait]
+impl OutputSink for MqttSink {
+    fn name(&self) -> &str {
+        "mqtt"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let topic = self.topic.replace("{symbol}", delivery.symbol);
+
+        let analysis = delivery.analysis;
+        let result = analysis.and_then(|analysis| analysis.result.as_ref());
+        let recommendation = analysis.and_then(|analysis| analysis.recommendation.as_ref());
+        let payload = json!({
+            "timestamp": Utc::now(),
+            "symbol": delivery.symbol,
+            "price": delivery.report.map(|report| report.last_price()),
+            "composite_score": delivery.report.map(|report| report.composite.score),
+            "action": analysis.and_then(|analysis| analysis.action()),
+            "outlook": analysis.and_then(|analysis| analysis.outlook()),
+            "confidence": recommendation.map(|recommendation| recommendation.confidence).or(result.map(|result| result.confidence)),
+            "risk_level": result.map(|result| result.risk_level),
+            "recommendation": recommendation,
+        });
+
+        let mut options = MqttOptions::new(format!("crypto-forecast-{}", std::process::id()), self.host.as_str(), self.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(username) = &self.username {
+            options.set_credentials(username, self.password.clone().unwrap_or_default());
+        }
+
+        let (client, mut event_loop) = AsyncClient::new(options, 10);
+        client.publish(topic.as_str(), QoS::AtLeastOnce, self.retain, serde_json::to_vec(&payload)?).await?;
+
+        // The event loop connects and sends the message; it is delivered once the broker acknowledges it
+        tokio::time::timeout(PUBLISH_TIMEOUT, async {
+            loop {
+                if let Event::Incoming(Packet::PubAck(_)) = event_loop.poll().await? {
+                    return Ok::<(), rumqttc::ConnectionError>(());
+                }
+            }
+        }).await.map_err(|_| format!("MQTT broker at {}:{} did not acknowledge the message", self.host, self.port))??;
+        client.disconnect().await?;
+
+        log::info!("Signal published to MQTT topic {}", topic);
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use std::error::Error;
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::{json, Map, Value};
+use crate::html;
+use super::{Delivery, OutputSink, Settings};
+
+const NOTION_API_URL: &str = "https://api.notion.com/v1/pages";
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// Notion's limits on one rich text item and on the blocks created with a page
+const MAX_TEXT_CHARS: usize = 2000;
+const MAX_BLOCKS: usize = 100;
+
+/// Adds the analysis as a page in the Notion database NOTION_DATABASE_ID, using the integration token NOTION_TOKEN.
+/// The database needs a `Name` title and `Date` (date), `Symbol`, `Outlook` and `Risk Level` (select) properties;
+/// Outlook and Risk Level are left empty when the model gave no structured call.
+pub struct NotionSink {
+    token: String,
+    database_id: String,
+}
+
+impl NotionSink {
+    pub fn from_settings(settings: &Settings) -> Result<Self, Box<dyn Error>> {
+        Ok(NotionSink {
+            token: settings.require("token", "NOTION_TOKEN", "notion")?,
+            database_id: settings.require("database_id", "NOTION_DATABASE_ID", "notion")?,
+        })
+    }
+}
+
+#[async_trait]
+impl OutputSink for NotionSink {
+    fn name(&self) -> &str {
+        "notion"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let now = Utc::now();
+        let result = delivery.analysis.and_then(|analysis| analysis.result.as_ref());
+        let outlook = delivery.analysis.and_then(|analysis| analysis.outlook());
+
+        let mut properties = Map::new();
+        properties.insert("Name".to_string(), json!({
+            "title": [{ "text": { "content": format!("{} analysis {}", delivery.symbol, now.format("%Y-%m-%d %H:%M UTC")) } }]
+        }));
+        properties.insert("Date".to_string(), json!({ "date": { "start": now.to_rfc3339() } }));
+        properties.insert("Symbol".to_string(), json!({ "select": { "name": delivery.symbol } }));
+        if let Some(outlook) = outlook {
+            properties.insert("Outlook".to_string(), json!({ "select": { "name": outlook.as_str() } }));
+        }
+        if let Some(result) = result {
+            properties.insert("Risk Level".to_string(), json!({ "select": { "name": result.risk_level.as_str() } }));
+        }
+
+        let response = Client::new()
+            .post(NOTION_API_URL)
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .json(&json!({
+                "parent": { "database_id": self.database_id },
+                "properties": properties,
+                "children": blocks(delivery.text),
+            }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("Creating the Notion page failed with status {}: {}", response.status(), response.text().await?).into());
+        }
+        log::info!("Analysis added to Notion successfully!");
+        Ok(())
+    }
+}
+
+/// The analysis as Notion blocks: headings, bullet points and paragraphs, split to fit Notion's limits
+fn blocks(text: &str) -> Vec<Value> {
+    let mut blocks = Vec::new();
+    for line in html::strip_bare_tags(text).lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let (kind, content) = if let Some(heading) = line.strip_prefix("### ").or_else(|| line.strip_prefix("## ")) {
+            ("heading_3", heading)
+        } else if let Some(heading) = line.strip_prefix("# ") {
+            ("heading_2", heading)
+        } else if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            ("bulleted_list_item", item)
+        } else {
+            ("paragraph", line)
+        };
+
+        let chars: Vec<char> = content.chars().collect();
+        for chunk in chars.chunks(MAX_TEXT_CHARS) {
+            blocks.push(json!({
+                "object": "block",
+                "type": kind,
+                kind: { "rich_text": [{ "type": "text", "text": { "content": chunk.iter().collect::<String>() } }] }
+            }));
+        }
+    }
+
+    if blocks.len() > MAX_BLOCKS {
+        blocks.truncate(MAX_BLOCKS - 1);
+        blocks.push(json!({
+            "object": "block",
+            "type": "paragraph",
+            "paragraph": { "rich_text": [{ "type": "text", "text": { "content": "(truncated)" } }] }
+        }));
+    }
+    blocks
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use std::error::Error;
+use reqwest::Client;
+use crate::ai_client::Direction;
+use crate::memory;
+use crate::signals::Action;
+use super::{headline, Delivery, OutputSink, Settings};
+
+/// Pushover's limits on the message and title
+const PUSHOVER_MAX_MESSAGE_CHARS: usize = 1024;
+const PUSHOVER_MAX_TITLE_CHARS: usize = 250;
+
+/// Pushes a short notification through Pushover (PUSHOVER_TOKEN, PUSHOVER_USER). The title carries the call, the message
+/// its levels and summary, and the priority follows the call unless PUSHOVER_PRIORITY is set: -1 for flat/hold, 1 at or above
+/// PUSHOVER_HIGH_CONFIDENCE (default 75), otherwise 0. The link is PUSHOVER_URL (e.g. the published HTML report) or the TradingView chart.
+pub struct PushoverSink {
+    token: String,
+    user: String,
+    priority: Option<i8>,
+    high_confidence: f64,
+    url: Option<String>,
+}
+
+impl PushoverSink {
+    pub fn from_settings(settings: &Settings) -> Result<Self, Box<dyn Error>> {
+        Ok(PushoverSink {
+            token: settings.require("token", "PUSHOVER_TOKEN", "pushover")?,
+            user: settings.require("user", "PUSHOVER_USER", "pushover")?,
+            priority: settings.get("priority", "PUSHOVER_PRIORITY").and_then(|value| value.trim().parse().ok()),
+            high_confidence: settings.get("high_confidence", "PUSHOVER_HIGH_CONFIDENCE")
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(75.0),
+            url: settings.get("url", "PUSHOVER_URL"),
+        })
+    }
+}
+
+#[async_trait]
+impl OutputSink for PushoverSink {
+    fn name(&self) -> &str {
+        "pushover"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let analysis = delivery.analysis;
+        let recommendation = analysis.and_then(|analysis| analysis.recommendation.as_ref());
+        let result = analysis.and_then(|analysis| analysis.result.as_ref());
+
+        let mut message = String::new();
+        if let Some(recommendation) = recommendation {
+            let currency = delivery.currency();
+            let price = |price: Option<f64>| price.map(|price| currency.round(price)).unwrap_or_else(|| "-".to_string());
+            let targets: Vec<String> = recommendation.targets.iter().map(|target| currency.round(*target)).collect();
+            message.push_str(&format!("Entry {}, stop {}, targets {}", price(recommendation.entry), price(recommendation.stop),
+                if targets.is_empty() { "-".to_string() } else { targets.join("/") }));
+            if !recommendation.horizon.is_empty() {
+                message.push_str(&format!(", {}", recommendation.horizon));
+            }
+            message.push_str("\n\n");
+        }
+        message.push_str(&memory::summarize(result, delivery.text, delivery.currency()));
+        let message: String = message.chars().take(PUSHOVER_MAX_MESSAGE_CHARS).collect();
+        let title: String = headline(delivery).chars().take(PUSHOVER_MAX_TITLE_CHARS).collect();
+
+        let confidence = recommendation.map(|recommendation| recommendation.confidence).or(result.map(|result| result.confidence));
+        let flat = recommendation.map(|recommendation| matches!(recommendation.direction, Direction::Flat))
+            .unwrap_or_else(|| analysis.and_then(|analysis| analysis.action()) == Some(Action::Hold));
+        let priority = match self.priority {
+            Some(priority) => priority.clamp(-2, 1),
+            None if flat => -1,
+            None if confidence.is_some_and(|confidence| confidence >= self.high_confidence) => 1,
+            None => 0,
+        };
+        let url = self.url.clone()
+            .unwrap_or_else(|| format!("https://www.tradingview.com/chart/?symbol=BINANCE:{}", delivery.symbol));
+
+        let response = Client::new()
+            .post("https://api.pushover.net/1/messages.json")
+            .form(&[
+                ("token", self.token.clone()),
+                ("user", self.user.clone()),
+                ("title", title),
+                ("message", message),
+                ("priority", priority.to_string()),
+                ("url", url),
+                ("url_title", format!("{} report", delivery.symbol)),
+            ])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("Pushover request failed with status {}: {}", response.status(), response.text().await?).into());
+        }
+        log::info!("Analysis sent to Pushover successfully!");
+        Ok(())
+    }
+}
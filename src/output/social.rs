@@ -0,0 +1,155 @@
+use async_trait::async_trait;
+use std::error::Error;
+use reqwest::Client;
+use serde_json::json;
+use super::{Delivery, OutputSink, Settings};
+
+/// Longest post on a default Mastodon instance and on X
+const MASTODON_MAX_CHARS: usize = 500;
+const X_MAX_CHARS: usize = 280;
+
+/// What closes each post: a link to the full report (SOCIAL_REPORT_URL) and hashtags (SOCIAL_HASHTAGS, default #Bitcoin)
+struct PostFooter {
+    report_url: Option<String>,
+    hashtags: String,
+}
+
+impl PostFooter {
+    fn from_settings(settings: &Settings) -> Self {
+        PostFooter {
+            report_url: settings.get("report_url", "SOCIAL_REPORT_URL"),
+            hashtags: settings.get_or("hashtags", "SOCIAL_HASHTAGS", "#Bitcoin"),
+        }
+    }
+}
+
+/// Posts the condensed summary to Mastodon: MASTODON_URL (the instance, e.g. https://mastodon.social) and MASTODON_TOKEN,
+/// an access token with the write:statuses scope
+pub struct MastodonSink {
+    instance: String,
+    token: String,
+    footer: PostFooter,
+}
+
+impl MastodonSink {
+    pub fn from_settings(settings: &Settings) -> Result<Self, Box<dyn Error>> {
+        Ok(MastodonSink {
+            instance: settings.require("url", "MASTODON_URL", "mastodon")?,
+            token: settings.require("token", "MASTODON_TOKEN", "mastodon")?,
+            footer: PostFooter::from_settings(settings),
+        })
+    }
+}
+
+#[async_trait]
+impl OutputSink for MastodonSink {
+    fn name(&self) -> &str {
+        "mastodon"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let response = Client::new()
+            .post(format!("{}/api/v1/statuses", self.instance.trim_end_matches('/')))
+            .bearer_auth(&self.token)
+            .form(&[("status", condensed_summary(delivery, MASTODON_MAX_CHARS, &self.footer))])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("Mastodon request failed with status {}: {}", response.status(), response.text().await?).into());
+        }
+        log::info!("Summary posted to Mastodon successfully!");
+        Ok(())
+    }
+}
+
+/// Posts the condensed summary to X with X_ACCESS_TOKEN, an OAuth 2.0 user access token with the tweet.write scope
+pub struct XSink {
+    token: String,
+    footer: PostFooter,
+}
+
+impl XSink {
+    pub fn from_settings(settings: &Settings) -> Result<Self, Box<dyn Error>> {
+        Ok(XSink {
+            token: settings.require("access_token", "X_ACCESS_TOKEN", "x")?,
+            footer: PostFooter::from_settings(settings),
+        })
+    }
+}
+
+#[async_trait]
+impl OutputSink for XSink {
+    fn name(&self) -> &str {
+        "x"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let response = Client::new()
+            .post("https://api.twitter.com/2/tweets")
+            .bearer_auth(&self.token)
+            .json(&json!({ "text": condensed_summary(delivery, X_MAX_CHARS, &self.footer) }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("X request failed with status {}: {}", response.status(), response.text().await?).into());
+        }
+        log::info!("Summary posted to X successfully!");
+        Ok(())
+    }
+}
+
+/// A post of at most `limit` characters: the call, outlook, key level and risk, then the link to the full report
+/// and hashtags as far as they fit
+fn condensed_summary(delivery: &Delivery<'_>, limit: usize, footer: &PostFooter) -> String {
+    let analysis = delivery.analysis;
+    let recommendation = analysis.and_then(|analysis| analysis.recommendation.as_ref());
+    let result = analysis.and_then(|analysis| analysis.result.as_ref());
+
+    let mut lines = Vec::new();
+    let mut headline = delivery.symbol.to_string();
+    if let Some(action) = analysis.and_then(|analysis| analysis.action()) {
+        headline.push_str(&format!(" {}", action.as_str()));
+    }
+    if let Some(confidence) = recommendation.map(|recommendation| recommendation.confidence).or(result.map(|result| result.confidence)) {
+        headline.push_str(&format!(" ({:.0}% confidence)", confidence));
+    }
+    lines.push(headline);
+    if let Some(outlook) = analysis.and_then(|analysis| analysis.outlook()) {
+        lines.push(format!("Outlook: {}", outlook.as_str()));
+    }
+
+    // The stop is the level that invalidates the call; without one, the nearest support and resistance
+    let currency = delivery.currency();
+    match (recommendation.and_then(|recommendation| recommendation.stop), delivery.report) {
+        (Some(stop), _) => lines.push(format!("Key level: {} (stop)", currency.round(stop))),
+        (None, Some(report)) => lines.push(format!("Key levels: support {}, resistance {}",
+            currency.round(report.levels.support), currency.round(report.levels.resistance))),
+        (None, None) => {}
+    }
+    if let Some(targets) = recommendation.map(|recommendation| &recommendation.targets).filter(|targets| !targets.is_empty()) {
+        let targets: Vec<String> = targets.iter().map(|target| currency.round(*target)).collect();
+        lines.push(format!("Targets: {}", targets.join(", ")));
+    }
+    if let Some(result) = result {
+        lines.push(format!("Risk: {}", result.risk_level.as_str()));
+    }
+
+    let mut post = String::new();
+    let mut append = |line: &str| {
+        let separator = if post.is_empty() { 0 } else { 1 };
+        if post.chars().count() + separator + line.chars().count() <= limit {
+            if separator == 1 {
+                post.push('\n');
+            }
+            post.push_str(line);
+        }
+    };
+    for line in &lines {
+        append(line);
+    }
+    if let Some(url) = &footer.report_url {
+        append(&format!("Full report: {}", url));
+    }
+    append(&footer.hashtags);
+    post
+}
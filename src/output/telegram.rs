@@ -0,0 +1,477 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::time::Duration;
+use reqwest::{Client, RequestBuilder};
+use chrono::{NaiveTime, Utc};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde_json::{json, Value};
+use super::{Delivery, OutputSink, Settings};
+use crate::chart::Chart;
+use crate::html;
+use crate::i18n;
+
+/// Characters per message, below Telegram's 4096 limit
+const MAX_MESSAGE_CHARS: usize = 3900;
+
+/// Telegram chats, from TELEGRAM_API_KEY and TELEGRAM_CHAT_ID
+pub struct TelegramSink {
+    api_key: String,
+    chats: Vec<Chat>,
+    chart: bool,    // Send the candlestick chart before the analysis unless TELEGRAM_CHART=false
+    buttons: bool,  // Attach action buttons to the analysis when TELEGRAM_BUTTONS=true, for the `bot telegram` command
+    max_retries: u32,
+    quiet_hours: Option<(NaiveTime, NaiveTime)>,   // UTC; messages are sent without a notification sound in between
+}
+
+/// A chat to send to, and the forum topic within it
+struct Chat {
+    id: String,
+    thread: Option<i64>,
+}
+
+impl Chat {
+    /// TELEGRAM_CHAT_ID entries are `chat_id` or `chat_id:message_thread_id`
+    fn parse(entry: &str) -> Result<Self, Box<dyn Error>> {
+        match entry.split_once(':') {
+            Some((id, thread)) => Ok(Chat {
+                id: id.trim().to_string(),
+                thread: Some(thread.trim().parse().map_err(|_| format!("Invalid Telegram topic id in '{}'", entry))?),
+            }),
+            None => Ok(Chat { id: entry.to_string(), thread: None }),
+        }
+    }
+
+    /// Form fields addressing this chat and topic
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("chat_id", self.id.clone())];
+        if let Some(thread) = self.thread {
+            fields.push(("message_thread_id", thread.to_string()));
+        }
+        fields
+    }
+}
+
+impl TelegramSink {
+    pub fn from_settings(settings: &Settings) -> Result<Self, Box<dyn Error>> {
+        let chats = settings.require("chat_id", "TELEGRAM_CHAT_ID", "telegram")?
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(Chat::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if chats.is_empty() {
+            return Err("TELEGRAM_CHAT_ID must list at least one chat".into());
+        }
+        let quiet_hours = match settings.get("quiet_hours", "TELEGRAM_QUIET_HOURS") {
+            Some(value) => Some(parse_quiet_hours(&value)?),
+            None => None,
+        };
+        Ok(TelegramSink {
+            api_key: settings.require("api_key", "TELEGRAM_API_KEY", "telegram")?,
+            chats,
+            chart: settings.get("chart", "TELEGRAM_CHART").is_none_or(|value| value != "false"),
+            buttons: settings.get("buttons", "TELEGRAM_BUTTONS").is_some_and(|value| value == "true"),
+            max_retries: settings.get("max_retries", "TELEGRAM_MAX_RETRIES")
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(3),
+            quiet_hours,
+        })
+    }
+
+    /// Whether the current time is within the quiet hours, which may wrap past midnight
+    fn is_quiet(&self) -> bool {
+        let Some((start, end)) = self.quiet_hours else { return false };
+        let now = Utc::now().time();
+        if start <= end { now >= start && now < end } else { now >= start || now < end }
+    }
+
+    /// Form fields addressing the chat, muted during quiet hours
+    fn fields(&self, chat: &Chat) -> Vec<(&'static str, String)> {
+        let mut fields = chat.fields();
+        if self.is_quiet() {
+            fields.push(("disable_notification", "true".to_string()));
+        }
+        fields
+    }
+
+    /// Make a Bot API call, retrying network errors, server errors and rate limiting (honouring Telegram's retry_after);
+    /// other rejections, like a wrong chat id, are returned with Telegram's description
+    async fn call(&self, method: &str, request: impl Fn() -> RequestBuilder) -> Result<(), Box<dyn Error>> {
+        let mut attempt = 0;
+        loop {
+            let (error, retry_after) = match request().send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    let body: Value = response.json().await.unwrap_or_default();
+                    if body["ok"].as_bool() == Some(true) {
+                        return Ok(());
+                    }
+                    let description = body["description"].as_str().map(str::to_string).unwrap_or_else(|| status.to_string());
+                    if status.is_client_error() && status.as_u16() != 429 {
+                        return Err(format!("Telegram {} failed: {}", method, description).into());
+                    }
+                    (description, body["parameters"]["retry_after"].as_u64())
+                }
+                Err(e) => (e.to_string(), None),
+            };
+            if attempt >= self.max_retries {
+                return Err(format!("Telegram {} failed: {}", method, error).into());
+            }
+
+            // Honour retry_after when given, otherwise back off exponentially (2s, 4s, 8s, ... up to a minute)
+            let delay = retry_after.unwrap_or_else(|| 2u64.saturating_pow(attempt + 1).min(60));
+            attempt += 1;
+            log::warn!("Telegram {} failed ({}), retrying in {}s ({}/{})", method, error, delay, attempt, self.max_retries);
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+        }
+    }
+
+    /// Send one message to a chat, as MarkdownV2 or plain text, optionally with buttons
+    async fn send_message(&self, client: &Client, chat: &Chat, text: &str, formatted: bool, keyboard: Option<&Value>)
+        -> Result<(), Box<dyn Error>> {
+        let mut payload = json!({ "text": text });
+        if let Some(keyboard) = keyboard {
+            payload["reply_markup"] = keyboard.clone();
+        }
+        for (name, value) in self.fields(chat) {
+            payload[name] = json!(value);
+        }
+        if formatted {
+            payload["parse_mode"] = json!("MarkdownV2");
+        }
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.api_key);
+        self.call("sendMessage", || client.post(&url).json(&payload)).await
+    }
+
+    /// Send the chart with sendPhoto; reqwest is built without multipart support, so the form body is assembled here
+    async fn send_photo(&self, client: &Client, chat: &Chat, chart: &Chart, caption: &str) -> Result<(), Box<dyn Error>> {
+        let boundary = format!("----crypto-forecast-{}", Utc::now().timestamp_millis());
+        let mut fields = self.fields(chat);
+        fields.push(("caption", caption.to_string()));
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, name, value).as_bytes());
+        }
+        body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"photo\"; filename=\"chart.png\"\r\n\
+            Content-Type: image/png\r\n\r\n", boundary).as_bytes());
+        body.extend_from_slice(&chart.png);
+        body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+        let url = format!("https://api.telegram.org/bot{}/sendPhoto", self.api_key);
+        self.call("sendPhoto", || client.post(&url)
+            .header(reqwest::header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary))
+            .body(body.clone())).await
+    }
+}
+
+#[async_trait]
+impl OutputSink for TelegramSink {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn wants_chart(&self) -> bool {
+        self.chart
+    }
+
+    /// Send the chart and then the analysis as MarkdownV2 messages to every chat, split between blocks
+    /// so no formatting is cut across messages
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let client = Client::new();
+        let date = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+        let header = format!("📊 *{}*", escape(&i18n::tf("Bitcoin Trading Analysis - {date}", &[("date", &date)])));
+        let chunks = chunk_blocks(to_markdown_v2(delivery.text), MAX_MESSAGE_CHARS);
+        let call = delivery.analysis.and_then(|analysis| analysis.action()).map(|action| action.as_str()).unwrap_or("NONE");
+        let keyboard = self.buttons.then(|| action_buttons(delivery.symbol, call));
+
+        for chat in &self.chats {
+            // Header first, with the time of the run
+            self.send_message(&client, chat, &header, true, None).await?;
+
+            if self.chart
+                && let Some(chart) = delivery.chart {
+                self.send_photo(&client, chat, chart, &format!("{} - {}", delivery.symbol, chart.title)).await?;
+            }
+
+            // The buttons go under the last part of the analysis
+            for (i, chunk) in chunks.iter().enumerate() {
+                let keyboard = keyboard.as_ref().filter(|_| i + 1 == chunks.len());
+                self.send_message(&client, chat, &chunk.text, chunk.formatted, keyboard).await?;
+
+                // Add a small delay to avoid rate limiting
+                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+            }
+        }
+
+        log::info!("Analysis sent to Telegram successfully!");
+
+        Ok(())
+    }
+
+    async fn send_alert(&self, message: &str) -> Result<bool, Box<dyn Error>> {
+        let client = Client::new();
+        for chat in &self.chats {
+            self.send_message(&client, chat, message, false, None).await?;
+        }
+        Ok(true)
+    }
+}
+
+/// TELEGRAM_QUIET_HOURS as `HH:MM-HH:MM` in UTC, e.g. `22:00-07:00`
+fn parse_quiet_hours(value: &str) -> Result<(NaiveTime, NaiveTime), Box<dyn Error>> {
+    let invalid = || format!("Invalid TELEGRAM_QUIET_HOURS '{}', expected HH:MM-HH:MM (UTC), e.g. 22:00-07:00", value);
+    let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+    let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid());
+    Ok((parse(start)?, parse(end)?))
+}
+
+/// Inline keyboard under an analysis; the callback data (at most 64 bytes) carries the action, symbol and call,
+/// e.g. `correct:BTCUSDT:BUY`, for the `bot telegram` command to act on
+fn action_buttons(symbol: &str, call: &str) -> Value {
+    json!({ "inline_keyboard": [
+        [
+            { "text": "🔄 Re-run", "callback_data": format!("rerun:{}", symbol) },
+            { "text": "❓ Ask follow-up", "callback_data": format!("ask:{}", symbol) },
+        ],
+        [
+            { "text": "✅ Mark correct", "callback_data": format!("correct:{}:{}", symbol, call) },
+            { "text": "❌ Mark incorrect", "callback_data": format!("incorrect:{}:{}", symbol, call) },
+        ],
+    ] })
+}
+
+/// One paragraph, heading, list item or code block of the message. Formatting never spans blocks,
+/// so messages can be split between any two of them.
+struct Block {
+    formatted: String,  // MarkdownV2
+    plain: String,      // The same text without formatting, sent when the block alone is too long for a message
+}
+
+/// A message's text and whether it is MarkdownV2
+struct Chunk {
+    text: String,
+    formatted: bool,
+}
+
+/// The model's Markdown as Telegram MarkdownV2 blocks: headings and bold become bold, list items get bullets,
+/// and everything else is escaped so the message always parses
+fn to_markdown_v2(markdown: &str) -> Vec<Block> {
+    let markdown = html::strip_bare_tags(markdown);
+    let mut blocks = Vec::new();
+    let mut current = Block { formatted: String::new(), plain: String::new() };
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut in_heading = false;
+    let mut in_code_block = false;
+    let mut bold_depth = 0;
+    let mut link = None;
+
+    let finish = |current: &mut Block, blocks: &mut Vec<Block>, separator: &str| {
+        if !current.plain.trim().is_empty() {
+            current.formatted.push_str(separator);
+            current.plain.push_str(separator);
+            blocks.push(std::mem::replace(current, Block { formatted: String::new(), plain: String::new() }));
+        }
+    };
+
+    for event in Parser::new_ext(&markdown, Options::ENABLE_STRIKETHROUGH) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => {
+                in_heading = true;
+                current.formatted.push('*');
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                in_heading = false;
+                current.formatted.push('*');
+                finish(&mut current, &mut blocks, "\n\n");
+            }
+            Event::Start(Tag::Strong) => {
+                if !in_heading && bold_depth == 0 {
+                    current.formatted.push('*');
+                }
+                bold_depth += 1;
+            }
+            Event::End(TagEnd::Strong) => {
+                bold_depth -= 1;
+                if !in_heading && bold_depth == 0 {
+                    current.formatted.push('*');
+                }
+            }
+            Event::Start(Tag::Emphasis) | Event::End(TagEnd::Emphasis) => current.formatted.push('_'),
+            Event::Start(Tag::Strikethrough) | Event::End(TagEnd::Strikethrough) => current.formatted.push('~'),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                current.formatted.push('[');
+                link = Some(dest_url.to_string());
+            }
+            Event::End(TagEnd::Link) => {
+                let url: String = link.take().unwrap_or_default();
+                current.formatted.push_str(&format!("]({})", url.replace('\\', "\\\\").replace(')', "\\)")));
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                finish(&mut current, &mut blocks, "\n");
+                in_code_block = true;
+                current.formatted.push_str("```\n");
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                current.formatted.push_str("```");
+                finish(&mut current, &mut blocks, "\n\n");
+            }
+            Event::Start(Tag::List(start)) => {
+                finish(&mut current, &mut blocks, "\n");
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                if lists.is_empty()
+                    && let Some(last) = blocks.last_mut() {
+                    last.formatted.push('\n');
+                    last.plain.push('\n');
+                }
+            }
+            Event::Start(Tag::Item) => {
+                finish(&mut current, &mut blocks, "\n");
+                let indent = "  ".repeat(lists.len().saturating_sub(1));
+                let bullet = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "•".to_string(),
+                };
+                current.formatted.push_str(&format!("{}{} ", indent, escape(&bullet)));
+                current.plain.push_str(&format!("{}{} ", indent, bullet));
+            }
+            Event::End(TagEnd::Item) => finish(&mut current, &mut blocks, "\n"),
+            Event::End(TagEnd::Paragraph) => finish(&mut current, &mut blocks, if lists.is_empty() { "\n\n" } else { "\n" }),
+            Event::Text(text) if in_code_block => {
+                current.formatted.push_str(&escape_code(&text));
+                current.plain.push_str(&text);
+            }
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                current.formatted.push_str(&escape(&text));
+                current.plain.push_str(&text);
+            }
+            Event::Code(code) => {
+                current.formatted.push_str(&format!("`{}`", escape_code(&code)));
+                current.plain.push_str(&code);
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                current.formatted.push('\n');
+                current.plain.push('\n');
+            }
+            Event::Rule => {
+                current.formatted.push_str("———");
+                current.plain.push_str("———");
+                finish(&mut current, &mut blocks, "\n\n");
+            }
+            _ => {}
+        }
+    }
+    finish(&mut current, &mut blocks, "\n");
+    blocks
+}
+
+/// Pack blocks into messages of at most `limit` characters; a block longer than that is sent
+/// unformatted, split at whitespace
+fn chunk_blocks(blocks: Vec<Block>, limit: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let flush = |current: &mut String, chunks: &mut Vec<Chunk>| {
+        if !current.trim().is_empty() {
+            chunks.push(Chunk { text: current.trim_end().to_string(), formatted: true });
+        }
+        current.clear();
+    };
+
+    for block in blocks {
+        let length = block.formatted.chars().count();
+        if length > limit {
+            flush(&mut current, &mut chunks);
+            chunks.extend(split_plain(&block.plain, limit).into_iter().map(|text| Chunk { text, formatted: false }));
+        } else {
+            if current.chars().count() + length > limit {
+                flush(&mut current, &mut chunks);
+            }
+            current.push_str(&block.formatted);
+        }
+    }
+    flush(&mut current, &mut chunks);
+    chunks
+}
+
+/// Split text into pieces of at most `limit` characters, at the last whitespace where there is one
+fn split_plain(text: &str, limit: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let Some((end, _)) = rest.char_indices().nth(limit) else {
+            pieces.push(rest.to_string());
+            break;
+        };
+        let end = rest[..end].rfind(char::is_whitespace).filter(|&space| space > 0).unwrap_or(end);
+        pieces.push(rest[..end].trim_end().to_string());
+        rest = rest[end..].trim_start();
+    }
+    pieces
+}
+
+/// Escape text for MarkdownV2
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape text inside MarkdownV2 code spans and blocks, where only ` and \ are special
+fn escape_code(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('`', "\\`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatted(markdown: &str) -> String {
+        to_markdown_v2(markdown).into_iter().map(|block| block.formatted).collect()
+    }
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(escape("Price: $94,210.50 (+1.2%) > SMA-20!"), "Price: $94,210\\.50 \\(\\+1\\.2%\\) \\> SMA\\-20\\!");
+        assert_eq!(escape_code("a`b\\c.d"), "a\\`b\\\\c.d");
+    }
+
+    #[test]
+    fn headings_bold_and_lists_become_markdown_v2() {
+        let text = formatted("## 1. Trend\n\n**Bullish** above 94.2k\n\n- RSI 61.5\n- MACD up");
+        assert_eq!(text, "*1\\. Trend*\n\n*Bullish* above 94\\.2k\n\n• RSI 61\\.5\n• MACD up\n\n");
+    }
+
+    #[test]
+    fn code_only_escapes_backticks_and_backslashes() {
+        assert_eq!(formatted("Run `a_b.c` now"), "Run `a_b.c` now\n\n");
+    }
+
+    #[test]
+    fn long_blocks_are_sent_unformatted_and_split_at_whitespace() {
+        let blocks = to_markdown_v2("Short.\n\nA much longer paragraph that will not fit.");
+        let chunks = chunk_blocks(blocks, 20);
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks[0].formatted);
+        assert_eq!(chunks[0].text, "Short\\.");
+        assert!(chunks[1..].iter().all(|chunk| !chunk.formatted && chunk.text.chars().count() <= 20));
+        assert_eq!(chunks[1].text, "A much longer");
+    }
+
+    #[test]
+    fn blocks_are_packed_into_as_few_messages_as_fit() {
+        let chunks = chunk_blocks(to_markdown_v2("One.\n\nTwo.\n\nThree."), 4096);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].text, "One\\.\n\nTwo\\.\n\nThree\\.");
+    }
+}
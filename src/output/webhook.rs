@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use std::error::Error;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use sha2::Sha256;
+use super::{json_payload, Delivery, OutputSink, Settings};
+
+/// POSTs the report as JSON to WEBHOOK_URL, signed with WEBHOOK_SECRET when set
+pub struct WebhookSink {
+    url: String,
+    secret: Option<String>,
+}
+
+impl WebhookSink {
+    pub fn from_settings(settings: &Settings) -> Result<Self, Box<dyn Error>> {
+        Ok(WebhookSink {
+            url: settings.require("url", "WEBHOOK_URL", "webhook")?,
+            secret: settings.get("secret", "WEBHOOK_SECRET"),
+        })
+    }
+}
+
+#[async_trait]
+impl OutputSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn send(&self, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let body = serde_json::to_vec(&json_payload(delivery))?;
+
+        let mut request = Client::new().post(&self.url).header("Content-Type", "application/json");
+        // Receivers verify the body with the shared secret, as with GitHub's X-Hub-Signature-256
+        if let Some(secret) = &self.secret {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+            mac.update(&body);
+            request = request.header("X-Signature-256", format!("sha256={}", hex::encode(mac.finalize().into_bytes())));
+        }
+
+        let response = request.body(body).send().await?;
+        if !response.status().is_success() {
+            return Err(format!("Webhook request failed with status: {}", response.status()).into());
+        }
+        log::info!("Analysis sent to the webhook successfully!");
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::ai_client::{self, AnalysisProvider};
+use crate::currency::Currency;
+use crate::data_fetcher;
+use crate::prompt_generator;
+use crate::renderer;
+use crate::signals::{self, Action, SignalRules, TradeSignal};
+use crate::strategy::{self, Strategy};
+use crate::technical_analysis;
+
+/// Default location of the persisted paper-trading state
+const DEFAULT_STATE_FILE: &str = "paper_state.json";
+
+/// Simulated account, persisted between runs
+#[derive(Serialize, Deserialize)]
+pub struct PaperState {
+    pub initial_balance: f64,
+    pub cash: f64,
+    pub position: Option<PaperPosition>,
+    pub realized_pnl: f64,
+    pub trades: Vec<PaperTrade>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PaperPosition {
+    pub entry_time: DateTime<Utc>,
+    pub entry_price: f64,
+    pub quantity: f64,
+    pub cost: f64,             // Cash spent including the entry fee
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PaperTrade {
+    pub entry_time: DateTime<Utc>,
+    pub exit_time: DateTime<Utc>,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub quantity: f64,
+    pub pnl: f64,
+    pub exit_reason: String,
+}
+
+/// Settings for the paper-trading loop, read from PAPER_* environment variables
+pub struct PaperConfig {
+    pub state_file: String,
+    pub initial_balance: f64,
+    pub fee_pct: f64,
+    pub interval: Duration,
+    pub use_llm: bool,         // Take the action from the AI analysis instead of the rule engine
+}
+
+impl PaperConfig {
+    pub fn from_env() -> Self {
+        let env_f64 = |name: &str, default: f64| env::var(name).ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(default);
+
+        PaperConfig {
+            state_file: env::var("PAPER_STATE_FILE").unwrap_or_else(|_| DEFAULT_STATE_FILE.to_string()),
+            initial_balance: env_f64("PAPER_BALANCE", 10_000.0),
+            fee_pct: env_f64("PAPER_FEE_PCT", 0.1),
+            interval: Duration::from_secs((env_f64("PAPER_INTERVAL_MINUTES", 60.0) * 60.0) as u64),
+            use_llm: env::var("PAPER_SIGNAL_SOURCE").map(|source| source == "llm").unwrap_or(false),
+        }
+    }
+}
+
+impl PaperState {
+    fn new(initial_balance: f64) -> Self {
+        PaperState {
+            initial_balance,
+            cash: initial_balance,
+            position: None,
+            realized_pnl: 0.0,
+            trades: Vec::new(),
+            updated_at: None,
+        }
+    }
+
+    /// Load the saved state, or start a fresh account if none exists
+    pub fn load(path: &str, initial_balance: f64) -> Result<Self, Box<dyn Error>> {
+        if !Path::new(path).exists() {
+            return Ok(PaperState::new(initial_balance));
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Current account value at the given price
+    pub fn equity(&self, price: f64) -> f64 {
+        self.cash + self.position.as_ref().map(|position| position.quantity * price).unwrap_or(0.0)
+    }
+
+    pub fn unrealized_pnl(&self, price: f64) -> f64 {
+        self.position.as_ref().map(|position| position.quantity * price - position.cost).unwrap_or(0.0)
+    }
+
+    /// Apply stops/targets and the latest signal at the current price
+    pub fn step(&mut self, signal: &TradeSignal, price: f64, now: DateTime<Utc>, fee_pct: f64) {
+        if let Some(position) = &self.position {
+            let exit = match (position.stop_loss, position.take_profit) {
+                (Some(stop), _) if price <= stop => Some("Stop loss"),
+                (_, Some(target)) if price >= target => Some("Take profit"),
+                _ if signal.action == Action::Sell => Some("Sell signal"),
+                _ => None,
+            };
+            if let Some(reason) = exit {
+                self.close(price, now, reason, fee_pct);
+            }
+        }
+
+        if self.position.is_none() && signal.action == Action::Buy {
+            let fee = self.cash * fee_pct / 100.0;
+            self.position = Some(PaperPosition {
+                entry_time: now,
+                entry_price: price,
+                quantity: (self.cash - fee) / price,
+                cost: self.cash,
+                stop_loss: signal.stop_loss,
+                take_profit: signal.take_profit,
+            });
+            self.cash = 0.0;
+            println!("Paper BUY at {}", Currency::USD.price(price));
+        }
+
+        self.updated_at = Some(now);
+    }
+
+    fn close(&mut self, price: f64, now: DateTime<Utc>, reason: &str, fee_pct: f64) {
+        let Some(position) = self.position.take() else {
+            return;
+        };
+
+        let proceeds = position.quantity * price;
+        self.cash = proceeds - proceeds * fee_pct / 100.0;
+        let pnl = self.cash - position.cost;
+        self.realized_pnl += pnl;
+        println!("Paper SELL at {} ({}): PnL {}", Currency::USD.price(price), reason, Currency::USD.price(pnl));
+
+        self.trades.push(PaperTrade {
+            entry_time: position.entry_time,
+            exit_time: now,
+            entry_price: position.entry_price,
+            exit_price: price,
+            quantity: position.quantity,
+            pnl,
+            exit_reason: reason.to_string(),
+        });
+    }
+}
+
+/// Run the paper-trading loop; with `once` a single step is taken (e.g. when scheduled externally)
+pub async fn run_paper(data_provider_api_key: &String, api_base_url: &String, once: bool) -> Result<(), Box<dyn Error>> {
+    let config = PaperConfig::from_env();
+    let rules = SignalRules::from_env();
+    let strategies = strategy::load_strategies()?;
+
+    let providers = if config.use_llm {
+        Some(ai_client::providers_from_env()?)
+    } else {
+        None
+    };
+
+    loop {
+        let result = paper_step(data_provider_api_key, api_base_url, &config, &rules, &strategies, providers.as_deref()).await;
+        if once {
+            return result;
+        }
+
+        // Keep the daemon alive through transient API failures
+        if let Err(e) = result {
+            log::warn!("Paper trading step failed: {}", e);
+        }
+        tokio::time::sleep(config.interval).await;
+    }
+}
+
+/// Fetch fresh data, derive a signal and apply it to the persisted account
+async fn paper_step(data_provider_api_key: &String, api_base_url: &String, config: &PaperConfig, rules: &SignalRules,
+                    strategies: &[Strategy], providers: Option<&[Box<dyn AnalysisProvider>]>) -> Result<(), Box<dyn Error>> {
+    let (btc_data, fear_and_greed_data) = tokio::join!(
+        data_fetcher::fetch_bitcoin_trading_data(data_provider_api_key, api_base_url),
+        data_fetcher::load_fear_greed(),
+    );
+    let report = technical_analysis::build_technical_report(&btc_data?, &fear_and_greed_data);
+
+    let signal = match providers {
+        Some(providers) => {
+            let prompt = prompt_generator::PromptTemplate::from_env()?.render(&renderer::render_text(&report));
+            let analysis = ai_client::get_analysis(providers, &prompt, None, false).await?;
+            let action = analysis.action().unwrap_or(Action::Hold);
+            signals::with_levels(&report, rules, action, vec!["Action taken from AI analysis".to_string()], Vec::new())
+        }
+        None => signals::generate_signal(&report, rules, strategies),
+    };
+
+    let price = report.last_price();
+    let mut state = PaperState::load(&config.state_file, config.initial_balance)?;
+    state.step(&signal, price, Utc::now(), config.fee_pct);
+    state.save(&config.state_file)?;
+
+    println!("\n{}", renderer::render_paper_status(&state, &signal, price));
+    Ok(())
+}
@@ -0,0 +1,429 @@
+use std::env;
+use std::error::Error;
+use serde::Serialize;
+use crate::ab::{self, Variant};
+use crate::ai_client::{self, AnalysisProvider};
+use crate::budget;
+use crate::chart;
+use crate::currency::{self, Currency};
+use crate::delta;
+use crate::data_fetcher::{self, CryptoData, FearGreedData};
+use crate::history::{self, RunInput};
+use crate::indicators::{Indicator, IndicatorParams, IndicatorRegistry};
+use crate::journal;
+use crate::memory::{AnalysisMemory, MemoryConfig};
+use crate::news;
+use crate::output::{Delivery, OutputSink, Outputs};
+use crate::portfolio;
+use crate::prompt_generator::PromptTemplate;
+use crate::renderer;
+use crate::report::{JOURNAL_SECTION, NEWS_SECTION, PORTFOLIO_SECTION, TechnicalReport};
+use crate::signals::{self, Action};
+use crate::strategy;
+use crate::technical_analysis;
+use crate::unchanged::{SentCall, SentCalls, UnchangedConfig, UnchangedMode};
+
+/// The analysis from market data to delivered report: fetch, indicators, prompt, model and outputs.
+/// Every step has a default from the environment, so `Pipeline::new().run()` is the plain analysis, and
+/// `Pipeline::new().symbol("ETHUSDT").interval("1d").with_indicator(...).provider(...).sink(...)` changes what it needs.
+pub struct Pipeline {
+    data_provider_api_key: String,
+    api_base_url: String,
+    symbol: String,
+    interval: Option<String>,
+    indicators: IndicatorRegistry,
+    providers: Vec<Box<dyn AnalysisProvider>>,
+    template: Option<PromptTemplate>,
+    outputs: Option<Outputs>,
+}
+
+/// The market data and the technical report built from it
+struct Market {
+    data: CryptoData,
+    report: TechnicalReport,
+}
+
+/// The earlier calls the prompt refers back to
+struct Recall {
+    config: MemoryConfig,
+    memory: AnalysisMemory,
+    section: String,                    // Summary of recent calls, appended to the report
+    previous_analysis: Option<String>,  // The last analysis in full
+}
+
+/// What a run would send and where, from `Pipeline::dry_run`
+#[derive(Serialize)]
+pub struct DryRun {
+    pub symbol: String,
+    pub interval: String,
+    pub currency: Currency,
+    pub price: f64,
+    pub prompt_version: String,
+    pub prompt_tokens: usize,
+    pub providers: Vec<String>,
+    pub outputs: Vec<String>,
+    pub chart: bool,                     // Whether the chart would be rendered for the model or the outputs
+    pub unavailable: Vec<String>,        // Optional sections whose source failed
+    pub estimated_cost: Option<f64>,     // USD, when AI_INPUT_COST_PER_MTOK and AI_OUTPUT_COST_PER_MTOK are set
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::new()
+    }
+}
+
+impl Pipeline {
+    /// BTCUSDT at the configured interval with the indicators from the environment, answered by AI_PROVIDER
+    /// with the PROMPT_VERSION prompt, and printed to the terminal
+    pub fn new() -> Self {
+        Pipeline {
+            data_provider_api_key: env::var("DATA_PROVIDER_API_KEY").unwrap_or_default(),
+            api_base_url: env::var("API_BASE_URL").unwrap_or_else(|_| "https://api.binance.com".to_string()),
+            symbol: "BTCUSDT".to_string(),
+            interval: None,
+            indicators: IndicatorRegistry::from_env(),
+            providers: Vec::new(),
+            template: None,
+            outputs: None,
+        }
+    }
+
+    pub fn symbol(mut self, symbol: &str) -> Self {
+        self.symbol = symbol.to_uppercase();
+        self
+    }
+
+    /// Candle interval, one of data_fetcher::INTERVALS (e.g. "4h")
+    pub fn interval(mut self, interval: &str) -> Self {
+        self.interval = Some(interval.to_string());
+        self
+    }
+
+    /// Compute another indicator alongside the built-in ones
+    pub fn with_indicator(mut self, indicator: Box<dyn Indicator>) -> Self {
+        self.indicators.register(indicator);
+        self
+    }
+
+    /// Ask this backend too; several are queried in parallel and summarized by consensus
+    pub fn provider(mut self, provider: Box<dyn AnalysisProvider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    pub fn providers(mut self, providers: Vec<Box<dyn AnalysisProvider>>) -> Self {
+        self.providers = providers;
+        self
+    }
+
+    pub fn template(mut self, template: PromptTemplate) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// Send the report here too; the terminal is only the default when no destination is given
+    pub fn sink(mut self, sink: Box<dyn OutputSink>) -> Self {
+        self.outputs.get_or_insert_with(|| Outputs::new(Vec::new())).add(sink);
+        self
+    }
+
+    pub fn outputs(mut self, outputs: Outputs) -> Self {
+        self.outputs = Some(outputs);
+        self
+    }
+
+    /// Price data for the symbol at the chosen interval in the quote currency, and the Fear & Greed index (empty when it
+    /// can't be fetched)
+    pub async fn fetch(&self) -> Result<(CryptoData, Vec<FearGreedData>), Box<dyn Error>> {
+        let (data, fear_and_greed_data, _) = self.fetch_priced().await?;
+        Ok((data, fear_and_greed_data))
+    }
+
+    /// The fetched data with the USD rate it was converted at
+    async fn fetch_priced(&self) -> Result<(CryptoData, Vec<FearGreedData>, f64), Box<dyn Error>> {
+        if let Some(interval) = &self.interval {
+            data_fetcher::set_interval(interval);
+        }
+        log::info!("Fetching {} price data from API...", self.symbol);
+        // The sources don't depend on each other, so they are fetched at the same time
+        let (data, fear_and_greed_data, rate) = tokio::join!(
+            data_fetcher::fetch_trading_data(&self.data_provider_api_key, &self.api_base_url, &self.symbol),
+            data_fetcher::load_fear_greed(),
+            currency::usd_rate(),
+        );
+        let (mut data, rate) = (data?, rate?);
+        currency::convert(&mut data, rate);
+        Ok((data, fear_and_greed_data, rate))
+    }
+
+    /// The technical report with the portfolio, journal and news sections
+    pub async fn report(self) -> Result<TechnicalReport, Box<dyn Error>> {
+        Ok(self.into_market().await?.1.report)
+    }
+
+    /// The prompt the model would be sent
+    pub async fn prompt(mut self) -> Result<String, Box<dyn Error>> {
+        let template = self.load_template()?;
+        let (pipeline, mut market) = self.into_market().await?;
+        let recall = pipeline.recall(&market.report)?;
+        Ok(pipeline.render_prompt(template, &mut market.report, &recall))
+    }
+
+    /// Send the same data through two prompt versions or models and deliver both answers side by side
+    pub async fn run_ab(self, variants: Vec<Variant>) -> Result<(), Box<dyn Error>> {
+        let (pipeline, mut market) = self.into_market().await?;
+        let recall = pipeline.recall(&market.report)?;
+        let outputs = pipeline.outputs.unwrap_or_else(Outputs::terminal);
+        ab::run_ab(variants, &mut market.report, &recall.section, recall.previous_analysis, &outputs).await
+    }
+
+    /// The full technical summary with the composite score, delivered to the outputs without asking a model
+    pub async fn run_indicators(mut self) -> Result<(), Box<dyn Error>> {
+        let outputs = self.outputs.take().unwrap_or_else(Outputs::terminal);
+        let (pipeline, Market { data, report }) = self.into_market().await?;
+        let text = renderer::render_text(&report);
+        let candles = data.candles();
+        let chart = if outputs.wants_chart() {
+            Some(chart::render_chart(&candles, data.currency)?)
+        } else {
+            None
+        };
+        let delivery = Delivery { text: &text, symbol: &pipeline.symbol, report: Some(&report), analysis: None,
+                                  chart: chart.as_ref(), candles: &candles };
+        outputs.send(&delivery).await
+    }
+
+    /// Everything `run` does before asking the model (fetch, indicators, prompt), reporting what would be sent where
+    /// instead of making the model request or delivering anything
+    pub async fn dry_run(mut self) -> Result<DryRun, Box<dyn Error>> {
+        if self.providers.is_empty() {
+            self.providers = ai_client::providers_from_env()?;
+        }
+        let outputs = self.outputs.take().unwrap_or_else(Outputs::terminal);
+        let template = self.load_template()?;
+        let prompt_version = template.version().to_string();
+        let (pipeline, mut market) = self.into_market().await?;
+        let recall = pipeline.recall(&market.report)?;
+        let prompt = pipeline.render_prompt(template, &mut market.report, &recall);
+        let prompt_tokens = budget::estimate_tokens(&prompt);
+
+        Ok(DryRun {
+            interval: data_fetcher::candle_interval().0.to_string(),
+            currency: market.report.currency,
+            price: market.report.last_price(),
+            prompt_version,
+            prompt_tokens,
+            providers: pipeline.providers.iter().map(|provider| provider.name().to_string()).collect(),
+            outputs: outputs.names().into_iter().map(str::to_string).collect(),
+            chart: chart_for_model() || outputs.wants_chart(),
+            unavailable: market.report.unavailable.clone(),
+            estimated_cost: budget::estimate_cost(prompt_tokens, pipeline.providers.len()),
+            symbol: pipeline.symbol,
+        })
+    }
+
+    /// The whole analysis: fetch, prompt, ask the model and deliver the report. Returns the model's call, if it made one.
+    /// When every model request fails, the rule-based summary is delivered instead.
+    pub async fn run(mut self) -> Result<Option<Action>, Box<dyn Error>> {
+        // Set everything up before fetching data, so a missing key or broken template fails first
+        if self.providers.is_empty() {
+            self.providers = ai_client::providers_from_env()?;
+        }
+        let outputs = self.outputs.take().unwrap_or_else(Outputs::terminal);
+        let template = self.load_template()?;
+        let prompt_version = template.version().to_string();
+        let (pipeline, mut market) = self.into_market().await?;
+        let mut recall = pipeline.recall(&market.report)?;
+        let prompt = pipeline.render_prompt(template, &mut market.report, &recall);
+        let Market { data, report } = market;
+
+        // Stream the response to the terminal for text output; other destinations only get the final result
+        let stream = outputs.contains("text") && env::var("AI_STREAM").map(|value| value != "false").unwrap_or(true);
+        // Optionally let vision-capable models see the chart as well as the numbers; the HTML report and Telegram show it too
+        let chart_for_model = chart_for_model();
+        let chart = if chart_for_model || outputs.wants_chart() {
+            Some(chart::render_chart(&data.candles(), data.currency)?)
+        } else {
+            None
+        };
+        let model_chart = chart.as_ref().filter(|_| chart_for_model);
+        let (text, analysis) = match ai_client::get_analysis(&pipeline.providers, &prompt, model_chart, stream).await {
+            Ok(analysis) => {
+                if recall.config.runs > 0 {
+                    recall.memory.record(&analysis, &report, recall.config.runs);
+                    recall.memory.save(&recall.config.file)?;
+                }
+
+                // Close with the structured conclusions and recommendation block when the model provided them
+                let mut text = analysis.text.clone();
+                if let Some(result) = &analysis.result {
+                    text.push_str(&format!("\n\n{}", renderer::render_analysis_result(result, report.currency)));
+                }
+                if let Some(recommendation) = &analysis.recommendation {
+                    text.push_str(&format!("\n\n{}", renderer::render_recommendation(recommendation, report.currency)));
+                }
+                (text, Some(analysis))
+            }
+            Err(e) => {
+                // Scheduled runs should still deliver something when every model request fails
+                log::warn!("AI analysis failed: {}; sending the rule-based summary instead", e);
+                let signal = signals::generate_signal(&report, &signals::SignalRules::from_env(), &strategy::load_strategies()?);
+                (renderer::render_fallback_summary(&report, &signal), None)
+            }
+        };
+
+        // Open with what moved since the last stored run of this market
+        let text = if history::enabled() && delta::enabled() {
+            let interval = data_fetcher::candle_interval().0;
+            match history::open().and_then(|connection| history::previous(&connection, &pipeline.symbol, interval)) {
+                Ok(Some(previous)) => {
+                    let changes = delta::changes(&previous, &report, analysis.as_ref().and_then(|analysis| analysis.action()));
+                    format!("{}\n{}", renderer::render_delta(&previous.run_at, &changes), text)
+                }
+                Ok(None) => text,
+                Err(e) => {
+                    log::warn!("could not read the previous run from the history: {}", e);
+                    text
+                }
+            }
+        } else {
+            text
+        };
+
+        let delivery = Delivery { text: &text, symbol: &pipeline.symbol, report: Some(&report), analysis: analysis.as_ref(),
+                                  chart: chart.as_ref(), candles: &data.candles() };
+        pipeline.deliver(outputs, &delivery).await?;
+
+        if history::enabled() {
+            let run = RunInput { symbol: &pipeline.symbol, prompt_version: &prompt_version,
+                                 providers: pipeline.providers.iter().map(|provider| provider.name()).collect(),
+                                 prompt: &prompt, report: &report, analysis: analysis.as_ref(), text: &text };
+            // The report has gone out by now, so a history failure only warns
+            match history::open().and_then(|connection| history::record(&connection, &run)) {
+                Ok(id) => log::debug!("Stored as run #{} in the history", id),
+                Err(e) => log::warn!("could not store the run in the history: {}", e),
+            }
+        }
+        Ok(analysis.as_ref().and_then(|analysis| analysis.action()))
+    }
+
+    /// Send the report, unless UNCHANGED says to hold back a call that hasn't moved since the last one sent:
+    /// then only the terminal gets it, and with `ping` the other destinations get a one-line notice instead
+    async fn deliver(&self, outputs: Outputs, delivery: &Delivery<'_>) -> Result<(), Box<dyn Error>> {
+        let config = UnchangedConfig::from_env();
+        let price = delivery.report.map(|report| report.last_price()).unwrap_or_default();
+        let call = delivery.analysis.and_then(|analysis| SentCall::new(analysis, price, delivery.currency()));
+        let Some(call) = call.filter(|_| config.mode != UnchangedMode::Send) else {
+            return outputs.send(delivery).await;
+        };
+
+        let key = format!("{}@{}", self.symbol, data_fetcher::candle_interval().0);
+        let mut sent = SentCalls::load(&config.file)?;
+        let unchanged = sent.calls.get(&key).filter(|previous| call.changes(previous, &config).is_empty());
+        if let Some(previous) = unchanged {
+            log::info!("The {} call is unchanged since {}; only printing it", self.symbol, previous.sent_at.format("%Y-%m-%d %H:%M UTC"));
+            let (terminal, notified) = outputs.split_off(&["text", "json"]);
+            terminal.send(delivery).await?;
+            if config.mode == UnchangedMode::Ping {
+                notified.send_alert(&format!("{}: no change since {}, still {} at {}", self.symbol,
+                    previous.sent_at.format("%Y-%m-%d %H:%M UTC"), call.action.as_str(), delivery.currency().price(price))).await?;
+            }
+            return Ok(());
+        }
+
+        outputs.send(delivery).await?;
+        sent.calls.insert(key, call);
+        sent.save(&config.file)
+    }
+
+    /// Fetch the data and build the report, handing back the rest of the pipeline
+    async fn into_market(mut self) -> Result<(Self, Market), Box<dyn Error>> {
+        let (fetched, news) = tokio::join!(self.fetch_priced(), news::load_news());
+        let (data, fear_and_greed_data, rate) = fetched?;
+
+        let params = IndicatorParams::from_env();
+        log::info!("Analyzing {} price data with RSI({}), MACD({},{},{}), and other indicators...",
+                   self.symbol, params.rsi_period, params.macd_fast, params.macd_slow, params.macd_signal);
+        let registry = std::mem::replace(&mut self.indicators, IndicatorRegistry::new());
+        let mut report = technical_analysis::build_technical_report_with(&data, &fear_and_greed_data, registry);
+        // Holdings are valued with BTC at the report's price, so they only go with the Bitcoin analysis.
+        // The optional sections are marked unavailable when their source fails, rather than failing the run.
+        if self.symbol == "BTCUSDT" {
+            match portfolio::load_portfolio(&self.api_base_url, report.last_price(), rate).await {
+                Ok(portfolio) => report.portfolio = portfolio,
+                Err(e) => {
+                    log::warn!("could not value the portfolio, continuing without it: {}", e);
+                    report.mark_unavailable(PORTFOLIO_SECTION);
+                }
+            }
+        }
+        match journal::load_summary() {
+            Ok(journal) => report.journal = journal,
+            Err(e) => {
+                log::warn!("could not read the trade journal, continuing without it: {}", e);
+                report.mark_unavailable(JOURNAL_SECTION);
+            }
+        }
+        match news {
+            Some(news) => report.news = news,
+            None => report.mark_unavailable(NEWS_SECTION),
+        }
+        Ok((self, Market { data, report }))
+    }
+
+    /// Let the model look back at its last few calls
+    fn recall(&self, report: &TechnicalReport) -> Result<Recall, Box<dyn Error>> {
+        let mut config = MemoryConfig::from_env();
+        // The memory holds earlier Bitcoin calls, which don't apply to other symbols
+        if self.symbol != "BTCUSDT" {
+            config.runs = 0;
+        }
+        let memory = AnalysisMemory::load(&config.file)?;
+        let section = if config.runs > 0 && !memory.entries.is_empty() {
+            format!("\n{}", renderer::render_memory(memory.recent(config.runs), report))
+        } else {
+            String::new()
+        };
+        let previous_analysis = memory.last()
+            .filter(|_| config.runs > 0)
+            .map(|entry| renderer::render_previous_analysis(entry, report));
+        Ok(Recall { config, memory, section, previous_analysis })
+    }
+
+    /// The chosen template, otherwise the PROMPT_VERSION one
+    fn load_template(&mut self) -> Result<PromptTemplate, Box<dyn Error>> {
+        match self.template.take() {
+            Some(template) => Ok(template),
+            None => PromptTemplate::named(&env::var("PROMPT_VERSION").unwrap_or_else(|_| "default".to_string())),
+        }
+    }
+
+    /// The prompt for the report, trimmed to the token budget
+    fn render_prompt(&self, template: PromptTemplate, report: &mut TechnicalReport, recall: &Recall) -> String {
+        log::info!("Generating trading recommendations...");
+        // Continue from the last analysis, so consecutive reports read as an evolving view
+        let template = match &recall.previous_analysis {
+            Some(previous) => template.with_previous_analysis(previous.clone()),
+            None => template,
+        };
+        let budget = budget::PromptBudget::from_env();
+        let (prompt, trimmed) = budget::fit_prompt(report, &budget, |report| {
+            template.render_symbol(&self.symbol, report.currency, &format!("{}{}", renderer::render_text(report), recall.section))
+        });
+        for part in &trimmed {
+            log::warn!("trimmed {} to fit the {} token prompt budget", part, budget.tokens);
+        }
+        let tokens = budget::estimate_tokens(&prompt);
+        log::info!("Prompt size: ~{} tokens", tokens);
+        if tokens > budget.tokens {
+            log::warn!("the prompt still exceeds the {} token budget after trimming", budget.tokens);
+        }
+        prompt
+    }
+}
+
+/// Whether vision-capable models are shown the chart (AI_CHART_IMAGE=true)
+fn chart_for_model() -> bool {
+    env::var("AI_CHART_IMAGE").map(|value| value == "true").unwrap_or(false)
+}
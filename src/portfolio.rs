@@ -0,0 +1,109 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::data_fetcher;
+
+/// Default location of the holdings configuration
+const DEFAULT_PORTFOLIO_FILE: &str = "portfolio.toml";
+
+/// Assets valued at $1 without a price lookup
+const STABLECOINS: [&str; 6] = ["USD", "USDT", "USDC", "BUSD", "DAI", "FDUSD"];
+
+#[derive(Deserialize)]
+struct PortfolioFile {
+    #[serde(default)]
+    holding: Vec<Holding>,
+}
+
+/// One configured position: how much of an asset is held and what it cost per unit
+#[derive(Deserialize)]
+struct Holding {
+    asset: String,
+    amount: f64,
+    cost_basis: Option<f64>,
+}
+
+/// Holdings valued at current prices
+#[derive(Serialize)]
+pub struct PortfolioReport {
+    pub holdings: Vec<HoldingValue>,
+    pub total_value: f64,
+    pub total_cost: Option<f64>,       // Only when every holding has a cost basis
+    pub total_pnl: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct HoldingValue {
+    pub asset: String,
+    pub amount: f64,
+    pub price: f64,
+    pub value: f64,
+    pub allocation_pct: f64,
+    pub average_cost: Option<f64>,     // Cost basis per unit
+    pub cost: Option<f64>,
+    pub pnl: Option<f64>,
+    pub pnl_pct: Option<f64>,
+}
+
+/// Load holdings from PORTFOLIO_FILE (default portfolio.toml) and value them; a missing file means no portfolio.
+/// `btc_price` is in the quote currency, and the other USDT prices are converted at `usd_rate`.
+pub async fn load_portfolio(api_base_url: &str, btc_price: f64, usd_rate: f64) -> Result<Option<PortfolioReport>, Box<dyn Error>> {
+    let path = env::var("PORTFOLIO_FILE").unwrap_or_else(|_| DEFAULT_PORTFOLIO_FILE.to_string());
+    if !Path::new(&path).exists() {
+        return Ok(None);
+    }
+
+    let file: PortfolioFile = toml::from_str(&fs::read_to_string(&path)?)
+        .map_err(|e| format!("Invalid portfolio file {}: {}", path, e))?;
+
+    // Every asset is priced at the same time
+    let priced = futures::future::join_all(file.holding.into_iter().map(|holding| async move {
+        let asset = holding.asset.to_uppercase();
+        let price = if asset == "BTC" {
+            btc_price
+        } else if STABLECOINS.contains(&asset.as_str()) {
+            usd_rate
+        } else {
+            data_fetcher::fetch_spot_price(api_base_url, &format!("{}USDT", asset)).await
+                .map_err(|e| format!("Could not price {}: {}", asset, e))? * usd_rate
+        };
+        Ok::<_, String>((holding, asset, price))
+    })).await.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Some(value_holdings(priced)))
+}
+
+/// Value, PnL and share of the total for each holding
+fn value_holdings(priced: Vec<(Holding, String, f64)>) -> PortfolioReport {
+    let total_value: f64 = priced.iter().map(|(holding, _, price)| holding.amount * price).sum();
+
+    let holdings: Vec<HoldingValue> = priced.into_iter()
+        .map(|(holding, asset, price)| {
+            let value = holding.amount * price;
+            let cost = holding.cost_basis.map(|basis| basis * holding.amount);
+            let pnl = cost.map(|cost| value - cost);
+            HoldingValue {
+                asset,
+                amount: holding.amount,
+                price,
+                value,
+                allocation_pct: if total_value > 0.0 { value / total_value * 100.0 } else { 0.0 },
+                average_cost: holding.cost_basis,
+                cost,
+                pnl,
+                pnl_pct: cost.zip(pnl).filter(|(cost, _)| *cost > 0.0).map(|(cost, pnl)| pnl / cost * 100.0),
+            }
+        })
+        .collect();
+
+    let total_cost = holdings.iter().map(|holding| holding.cost).sum::<Option<f64>>();
+
+    PortfolioReport {
+        total_value,
+        total_cost,
+        total_pnl: total_cost.map(|cost| total_value - cost),
+        holdings,
+    }
+}
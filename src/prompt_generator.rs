@@ -1,40 +1,192 @@
-/// Generate a trading recommendation prompt
-pub fn generate_trading_recommendation_prompt(data: &str) -> String {
-    format!(
-        "You are a cryptocurrency market analyst specializing in Bitcoin. Your task is to provide an insightful summary of the Bitcoin market, including price predictions, buy and sell positions, key levels, risk assessment, and overall recommendations. Use the following data to conduct your analysis:\n\
-        \n\
-        <historical_data>\n\
-        {}\n\
-        </historical_data>\n\
-        \n\
-        Analyze the provided data carefully, paying attention to trends, patterns, and signals from various indicators. Consider both technical and sentiment factors in your analysis.\n\
-        \n\
-        Prepare a comprehensive summary report with the following sections:\n\
-        \n\
-        1. Market Overview: Provide a brief overview of the current Bitcoin market situation based on the latest data points.\n\
-        \n\
-        2. Price Prediction: Offer price predictions for short-term (1-7 days), mid-term (1-3 months), and long-term (6-12 months) horizons. Support your predictions with relevant data and indicator analysis.\n\
-        \n\
-        3. Long and Short Positions: Recommend entry and exit points for short, mid, and long-term traders. Explain the rationale behind each position.\n\
-        \n\
-        4. Key Levels: Identify and explain important support and resistance levels to watch. Provide specific price points and reasons why these levels are significant.\n\
-        \n\
-        5. Indicator Analysis: Analyze each of the following indicators and explain their implications for Bitcoin's price action:\n\
-           - RSI with EMA (overbought/oversold conditions)\n\
-           - MACD (trend strength and momentum)\n\
-           - Bollinger Bands (volatility and potential reversals)\n\
-           - SMA and EMA crossovers (trend direction)\n\
-           - OBV (volume confirmation of trends)\n\
-           - ATR (volatility measurement)\n\
-           - Fear and Greed Index (market sentiment)\n\
-        \n\
-        6. Risk Assessment: Evaluate the overall risk level (low, medium, or high) for Bitcoin investments at this time. Provide a detailed explanation for your assessment, considering both technical and fundamental factors.\n\
-        \n\
-        7. Overall Recommendation: Conclude with an overall recommendation to Buy, Sell, or Hold Bitcoin. Justify your recommendation based on the analysis of all indicators and market factors discussed in the report.\n\
-        \n\
-        Before providing your final output, use <scratchpad> tags to organize your thoughts and analyze the data. This will help you formulate a well-reasoned and comprehensive report.\n\
-        \n\
-        Present your final analysis and recommendations within <bitcoin_market_analysis> tags. Ensure that your report is well-structured, easy to read, and provides clear, actionable insights for investors with different time horizons.", 
-        data
-    )
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use tera::{Context, Tera};
+use crate::currency::Currency;
+use crate::i18n;
+
+/// The built-in prompts, shipped as prompts/default.tera and prompts/brief.tera
+const DEFAULT_TEMPLATE: &str = include_str!("../prompts/default.tera");
+const BRIEF_TEMPLATE: &str = include_str!("../prompts/brief.tera");
+
+/// Built-in prompts for the report subcommands, used when prompts/<name>.tera is absent
+const REPORT_TEMPLATES: [(&str, &str); 3] = [
+    ("onchain-report", include_str!("../prompts/onchain-report.tera")),
+    ("macro-report", include_str!("../prompts/macro-report.tera")),
+    ("weekly-review", include_str!("../prompts/weekly-review.tera")),
+];
+
+/// Where the prompt templates are looked for when PROMPT_TEMPLATE / PROMPT_BRIEF_TEMPLATE are not set
+const DEFAULT_TEMPLATE_PATH: &str = "prompts/default.tera";
+const BRIEF_TEMPLATE_PATH: &str = "prompts/brief.tera";
+
+/// Directory holding the named prompt versions
+const PROMPT_DIR: &str = "prompts";
+
+/// Perspectives selectable with --persona: name and the emphasis it adds to the prompt
+pub const PERSONAS: [(&str, &str); 4] = [
+    ("scalper", "Write for an intraday scalper. Focus on the next 4 to 24 hours: the most recent candles, short-term momentum \
+        (RSI, MACD histogram), tight ATR-based stops and quick targets at the nearest levels. Keep the mid- and long-term outlook brief."),
+    ("swing", "Write for a swing trader holding positions for days to a few weeks. Emphasize trend structure, support and resistance, \
+        moving average crossovers and setups that meet the minimum reward:risk ratio."),
+    ("investor", "Write for a long-term investor with a horizon of 6 to 12 months or more. Emphasize the primary trend (SMA200), \
+        accumulation zones, the market cycle and sentiment extremes. Treat short-term swings as noise and favor staged entries over precise timing."),
+    ("risk-manager", "Write as a risk manager reviewing exposure. Emphasize downside scenarios, volatility (ATR, Bollinger Band width), \
+        invalidation levels and position sizing. State what could go wrong and what would justify reducing risk before discussing upside."),
+];
+
+/// The analysis prompt as a Tera template, so the instructions can be customized without recompiling.
+/// Templates can use `{{ data }}`, the rendered market data, `{{ persona }}`, the selected perspective,
+/// `{{ question }}`, the reader's --ask question, `{{ previous_analysis }}`, the last stored analysis (all empty by default),
+/// `{{ language }}`, the --lang language (empty for English), and `{{ has_portfolio }}`, whether the data has a portfolio section.
+pub struct PromptTemplate {
+    tera: Tera,
+    version: String,
+    persona: Option<&'static str>,
+    question: Option<String>,
+    previous_analysis: Option<String>,
+}
+
+impl PromptTemplate {
+    /// Load PROMPT_TEMPLATE (default prompts/default.tera); the built-in prompt is used when the default file is absent
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        PromptTemplate::load("PROMPT_TEMPLATE", DEFAULT_TEMPLATE_PATH, DEFAULT_TEMPLATE)
+    }
+
+    /// The compact prompt for --brief, from PROMPT_BRIEF_TEMPLATE (default prompts/brief.tera)
+    fn brief_from_env() -> Result<Self, Box<dyn Error>> {
+        PromptTemplate::load("PROMPT_BRIEF_TEMPLATE", BRIEF_TEMPLATE_PATH, BRIEF_TEMPLATE)
+    }
+
+    /// A named prompt version: default, brief and the report prompts are built in, any other name is read from prompts/<name>.tera
+    pub fn named(version: &str) -> Result<Self, Box<dyn Error>> {
+        let template = match version {
+            "default" => PromptTemplate::from_env(),
+            "brief" => PromptTemplate::brief_from_env(),
+            name => {
+                let path = Path::new(PROMPT_DIR).join(format!("{}.tera", name));
+                let builtin = REPORT_TEMPLATES.iter().find(|(report, _)| *report == name).map(|(_, source)| *source);
+                let source = match builtin {
+                    Some(builtin) if !path.exists() => builtin.to_string(),
+                    _ => fs::read_to_string(&path)
+                        .map_err(|e| format!("Unknown prompt version '{}': could not read {}: {}", name, path.display(), e))?,
+                };
+                PromptTemplate::validated(&source)
+            }
+        }?;
+        Ok(PromptTemplate { version: version.to_string(), ..template })
+    }
+
+    /// The prompt version it was loaded as, for telling runs apart in the history
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn load(variable: &str, default_path: &str, builtin: &str) -> Result<Self, Box<dyn Error>> {
+        let source = match env::var(variable) {
+            Ok(path) => fs::read_to_string(&path).map_err(|e| format!("Could not read prompt template {}: {}", path, e))?,
+            Err(_) if Path::new(default_path).exists() => fs::read_to_string(default_path)?,
+            Err(_) => builtin.to_string(),
+        };
+        PromptTemplate::validated(&source)
+    }
+
+    fn validated(source: &str) -> Result<Self, Box<dyn Error>> {
+        let template = PromptTemplate::parse(source)?;
+        // Render once so a template using unknown variables fails now rather than mid-run
+        template.try_render("").map_err(|e| format!("Invalid prompt template: {}", describe(&e)))?;
+        Ok(template)
+    }
+
+    fn parse(source: &str) -> Result<Self, Box<dyn Error>> {
+        let mut tera = Tera::default();
+        tera.add_raw_template("prompt", source).map_err(|e| format!("Invalid prompt template: {}", describe(&e)))?;
+        Ok(PromptTemplate { tera, version: "default".to_string(), persona: None, question: None, previous_analysis: None })
+    }
+
+    /// Emphasize one of the personas (scalper, swing, investor, risk-manager)
+    pub fn with_persona(mut self, name: &str) -> Result<Self, Box<dyn Error>> {
+        let (_, instructions) = PERSONAS.iter().find(|(persona, _)| *persona == name)
+            .ok_or_else(|| format!("Unknown persona '{}', expected {}", name,
+                PERSONAS.map(|(persona, _)| persona).join(", ")))?;
+        self.persona = Some(instructions);
+        Ok(self)
+    }
+
+    /// Ask the model a question about the current data, answered in its own section
+    pub fn with_question(mut self, question: &str) -> Self {
+        self.question = Some(question.trim().to_string()).filter(|question| !question.is_empty());
+        self
+    }
+
+    /// Show the model its previous analysis and ask what changed since then
+    pub fn with_previous_analysis(mut self, analysis: String) -> Self {
+        self.previous_analysis = Some(analysis);
+        self
+    }
+
+    fn try_render(&self, data: &str) -> Result<String, tera::Error> {
+        let mut context = Context::new();
+        context.insert("data", data);
+        context.insert("persona", self.persona.unwrap_or_default());
+        context.insert("question", self.question.as_deref().unwrap_or_default());
+        context.insert("previous_analysis", self.previous_analysis.as_deref().unwrap_or_default());
+        context.insert("language", i18n::language_name().unwrap_or_default());
+        // The section header is in the report's language, so templates shouldn't look for it themselves
+        context.insert("has_portfolio", &data.contains(&format!("=== {} ===", i18n::t("PORTFOLIO"))));
+        let mut prompt = self.tera.render("prompt", &context)?;
+
+        // Custom templates written before these options existed don't place them, so add them at the end
+        if let Some(previous) = &self.previous_analysis
+            && !prompt.contains("<previous_analysis>") {
+            prompt.push_str(&format!("\n\n<previous_analysis>\n{}\n</previous_analysis>\n\nThis is your previous analysis. \
+                State explicitly what has changed since then and whether your view has changed.", previous));
+        }
+        if let Some(question) = &self.question
+            && !prompt.contains(question.as_str()) {
+            prompt.push_str(&format!("\n\nAlso include a section titled \"Answer to Your Question:\" that answers the reader's question \
+                directly, using the current data: \"{}\"", question));
+        }
+        if let Some(language) = i18n::language_name()
+            && !prompt.contains(language) {
+            // The reply is checked for its sections and call by their English names, so those can't be translated
+            prompt.push_str(&format!("\n\nWrite the whole report in {}. Keep the numbered section titles \
+                (such as \"Overall Recommendation:\"), the BUY, SELL or HOLD call, the XML tags and the JSON block \
+                (field names and values) in English.", language));
+        }
+        Ok(prompt)
+    }
+
+    /// Generate a trading recommendation prompt
+    pub fn render(&self, data: &str) -> String {
+        self.try_render(data).unwrap_or_else(|e| {
+            // Loading already rendered the template once, so this is not expected; keep the run going regardless
+            log::warn!("the prompt template failed to render ({}), using the built-in prompt", describe(&e));
+            PromptTemplate::parse(DEFAULT_TEMPLATE).ok()
+                .and_then(|template| template.try_render(data).ok())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Prompt for a watchlist symbol; the instructions are written for Bitcoin, so other assets get a note saying what the data covers
+    pub fn render_symbol(&self, symbol: &str, currency: Currency, data: &str) -> String {
+        let prompt = self.render(data);
+        if symbol == "BTCUSDT" {
+            return prompt;
+        }
+        format!("Note: the data below is for {} (priced in {}), not Bitcoin. Wherever these instructions mention Bitcoin, \
+            analyze {} instead, but keep the <bitcoin_market_analysis> tags.\n\n{}", symbol, currency.code, symbol, prompt)
+    }
+}
+
+/// Tera's message with its causes, which carry the line and variable at fault
+pub fn describe(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
 }
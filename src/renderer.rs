@@ -0,0 +1,815 @@
+use crate::accuracy::{AccuracyConfig, Scorecard};
+use crate::backtest::{BacktestResult, WalkForwardResult};
+use crate::optimize::OptimizationResult;
+use crate::paper::PaperState;
+use crate::pipeline::DryRun;
+use crate::ai_client::{AnalysisResult, Consensus, Recommendation};
+use crate::history::Run;
+use crate::i18n;
+use crate::journal::{JournalEntry, JournalSummary};
+use crate::memory::MemoryEntry;
+use crate::news::NewsHeadline;
+use crate::portfolio::PortfolioReport;
+use crate::currency::{self, Currency};
+use crate::locale;
+use crate::data_fetcher::{Candle, MempoolStats, OnchainStats};
+use crate::report::{FEAR_GREED_SECTION, IndicatorReport, JOURNAL_SECTION, NEWS_SECTION, PORTFOLIO_SECTION, TechnicalReport, ValueUnit};
+use crate::reports::{self, MacroAsset, WeekSummary};
+use crate::risk::{PositionSizing, TradeLevels};
+use crate::signal_score::CompositeScore;
+use crate::signals::{SignalRules, TradeSignal};
+use crate::strategy;
+use chrono::{DateTime, Utc};
+
+/// Render the technical report as the plain-text summary used in the prompt
+pub fn render_text(report: &TechnicalReport) -> String {
+    let money = |value: f64| report.currency.price(value);
+    let mut result = String::new();
+
+    result.push_str(&format!("{}\n\n", i18n::tf("Report generated: {time}", &[("time", &report.generated_at.format("%Y-%m-%d %H:%M UTC"))])));
+
+    // Lead with the composite signal score so the overall bias is visible first
+    result.push_str(&render_composite(&report.composite));
+
+    if let Some(market) = &report.market {
+        result.push_str(&format!("=== {} ===\n", i18n::t("BITCOIN HISTORICAL DATA SUMMARY")));
+
+        result.push_str(&format!("\n{}:\n", i18n::t("5 Highest Bitcoin Prices (All-Time)")));
+        for (i, (date, price)) in market.highest_closes.iter().enumerate() {
+            result.push_str(&format!("{}. {}: {}\n", i + 1, date.format("%Y-%m-%d %H:%M:%S"), money(*price)));
+        }
+
+        result.push_str(&format!("\n{}:\n", i18n::t("5 Lowest Bitcoin Prices (All-Time)")));
+        for (i, (date, price)) in market.lowest_closes.iter().enumerate() {
+            result.push_str(&format!("{}. {}: {}\n", i + 1, date.format("%Y-%m-%d %H:%M:%S"), money(*price)));
+        }
+
+        result.push_str(&format!("\n{}:\n", i18n::t("Key Statistics")));
+        result.push_str(&format!("{}: {}\n", i18n::t("Average Price"), money(market.average_price)));
+        result.push_str(&format!("{}: {}\n", i18n::t("All-Time High"), money(market.all_time_high)));
+        result.push_str(&format!("{}: {}\n", i18n::t("All-Time Low"), money(market.all_time_low)));
+        result.push_str(&format!("{}: {}\n", i18n::t("Price Range"), i18n::tf("{range} ({low} to {high})", &[
+            ("range", &money(market.all_time_high - market.all_time_low)), ("low", &money(market.all_time_low)), ("high", &money(market.all_time_high)),
+        ])));
+        result.push_str(&format!("{}: {} ({:.2}%)\n", i18n::t("Price Volatility (Std Dev)"),
+            money(market.std_dev), (market.std_dev / market.average_price) * 100.0));
+        result.push_str(&format!("{}: {:.2}\n", i18n::t("Average Daily Volume"), market.average_volume));
+        if let Some(change) = market.change_30_periods {
+            result.push_str(&format!("{}: {:.2}%\n", i18n::t("30-Day Price Change"), change));
+        }
+        if let Some(change) = market.change_7_periods {
+            result.push_str(&format!("{}: {:.2}%\n", i18n::t("7-Day Price Change"), change));
+        }
+    }
+
+    if !report.recent_candles.is_empty() {
+        result.push_str(&format!("\n=== {} ===\n", i18n::tf("RECENT BITCOIN OHLCV DATA (LAST {count} RECORDS)", &[("count", &report.recent_candles.len())])));
+        result.push_str(&format!("{}\n", i18n::t("Date,Open,High,Low,Close,Volume")));
+        for candle in &report.recent_candles {
+            result.push_str(&format!("{}: O={} H={} L={} C={} V={:.2}\n", candle.time.format("%Y-%m-%d %H:%M:%S"),
+                money(candle.open), money(candle.high), money(candle.low), money(candle.close), candle.volume));
+        }
+    } else {
+        result.push_str(&format!("{}:\n", i18n::tf("Bitcoin price data (timestamp, price in {currency})", &[("currency", &report.currency.code)])));
+        for (date, price) in &report.price_points {
+            result.push_str(&format!("{}: Price={}\n", date.format("%Y-%m-%d %H:%M:%S"), money(*price)));
+        }
+    }
+
+    result.push_str(&format!("\n=== {} ===\n", i18n::t("TECHNICAL INDICATORS")));
+    for indicator in &report.indicators {
+        result.push_str(&render_indicator(indicator, report.currency));
+    }
+
+    result.push_str(&format!("\n{}: {}\n", i18n::t("Support level"), money(report.levels.support)));
+    result.push_str(&format!("{}: {}\n", i18n::t("Resistance level"), money(report.levels.resistance)));
+
+    if let Some(levels) = &report.trade_levels {
+        result.push_str(&render_trade_levels(levels, report.currency));
+    }
+
+    if let Some(sizing) = &report.position_sizing {
+        result.push_str(&render_position_sizing(sizing, report.currency));
+    }
+
+    if let Some(portfolio) = &report.portfolio {
+        result.push_str(&render_portfolio(portfolio, report.currency));
+    } else if report.is_unavailable(PORTFOLIO_SECTION) {
+        result.push_str(&render_unavailable("PORTFOLIO"));
+    }
+
+    if let Some(journal) = &report.journal {
+        result.push_str(&format!("\n{}", render_journal_summary(journal)));
+    } else if report.is_unavailable(JOURNAL_SECTION) {
+        result.push_str(&render_unavailable("TRADE JOURNAL"));
+    }
+
+    if !report.news.is_empty() {
+        result.push_str(&render_news(&report.news, report.generated_at));
+    } else if report.is_unavailable(NEWS_SECTION) {
+        result.push_str(&render_unavailable("NEWS"));
+    }
+
+    if report.is_unavailable(FEAR_GREED_SECTION) {
+        result.push_str(&render_unavailable("FEAR & GREED INDEX"));
+    } else {
+        result.push_str(&format!("\n=== {} ===\n", i18n::t("FEAR & GREED INDEX")));
+        result.push_str(&format!("{}\n", i18n::t("Date: Index classification - Index value")));
+        for reading in &report.fear_greed {
+            result.push_str(&format!("{}: {} - {}\n", reading.date.format("%Y-%m-%d"), reading.classification, reading.value));
+        }
+    }
+
+    result
+}
+
+/// A section whose source failed this run, so the model doesn't read its absence as a signal
+fn render_unavailable(title: &'static str) -> String {
+    format!("\n=== {} ===\n{}\n", i18n::t(title), i18n::t("Unavailable: the data source could not be reached for this report"))
+}
+
+/// Render the full report as a machine-readable JSON snapshot
+pub fn render_json(report: &TechnicalReport) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(report)
+}
+
+/// Render a rule-based trade signal with its levels and reasoning
+pub fn render_signal(signal: &TradeSignal) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("RULE-BASED SIGNAL")));
+    result.push_str(&format!("{}: {}\n", i18n::t("Action"), signal.action.as_str()));
+    result.push_str(&format!("{}: {}\n", i18n::t("Entry"), signal.currency.price(signal.entry)));
+    if let (Some(stop), Some(target)) = (signal.stop_loss, signal.take_profit) {
+        result.push_str(&format!("{}: {}\n", i18n::t("Stop Loss"), signal.currency.price(stop)));
+        result.push_str(&format!("{}: {}\n", i18n::t("Take Profit"), signal.currency.price(target)));
+    }
+    result.push_str(&format!("{}:\n", i18n::t("Reasons")));
+    for reason in &signal.reasons {
+        result.push_str(&format!("  - {}\n", reason));
+    }
+
+    if !signal.strategies.is_empty() {
+        result.push_str(&format!("{}:\n", i18n::t("Strategies")));
+        for strategy in &signal.strategies {
+            result.push_str(&format!("  [{}] {} ({}): {}\n",
+                if strategy.triggered { "x" } else { " " }, strategy.name, strategy.action.as_str(), strategy.condition));
+        }
+    }
+
+    result
+}
+
+/// Deterministic market summary from the indicators and signal engine, for when no AI analysis is available
+pub fn render_fallback_summary(report: &TechnicalReport, signal: &TradeSignal) -> String {
+    let value = |name: &str| strategy::variable(report, name);
+    let price = report.last_price();
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("RULE-BASED MARKET SUMMARY")));
+    result.push_str(&format!("{}\n\n", i18n::t("The AI analysis is unavailable; this summary is generated from the indicators alone.")));
+    result.push_str(&format!("{}: {}\n", i18n::t("Price"), report.currency.price(price)));
+    result.push_str(&format!("{}: {:+.1} / 100 ({})\n", i18n::t("Composite score"), report.composite.score, i18n::t(report.composite.label())));
+
+    let side = |above: bool| if above { i18n::t("above") } else { i18n::t("below") };
+    let trend = match (value("sma50"), value("sma200")) {
+        (Some(sma50), Some(sma200)) => i18n::tf("{trend} (SMA50 {sma50} {cross} SMA200 {sma200}), price {position} SMA200", &[
+            ("trend", &if sma50 > sma200 { i18n::t("Uptrend") } else { i18n::t("Downtrend") }),
+            ("sma50", &report.currency.price(sma50)), ("cross", &side(sma50 > sma200)),
+            ("sma200", &report.currency.price(sma200)), ("position", &side(price > sma200)),
+        ]),
+        _ => i18n::t("Not enough data for the 50/200 moving averages").to_string(),
+    };
+    result.push_str(&format!("{}: {}\n", i18n::t("Trend"), trend));
+
+    let mut momentum = Vec::new();
+    if let Some(rsi) = value("rsi") {
+        let state = if rsi >= 70.0 { "overbought" } else if rsi <= 30.0 { "oversold" } else { "neutral" };
+        momentum.push(format!("RSI {:.1} ({})", rsi, i18n::t(state)));
+    }
+    if let (Some(macd), Some(signal_line)) = (value("macd"), value("macd_signal")) {
+        momentum.push(i18n::tf("MACD {cross} its signal line", &[("cross", &side(macd > signal_line))]));
+    }
+    if !momentum.is_empty() {
+        result.push_str(&format!("{}: {}\n", i18n::t("Momentum"), momentum.join(", ")));
+    }
+
+    result.push_str(&format!("{}\n", i18n::tf("Key levels: support {support}, resistance {resistance}", &[
+        ("support", &report.currency.price(report.levels.support)), ("resistance", &report.currency.price(report.levels.resistance)),
+    ])));
+    if let Some(fear_greed) = report.fear_greed.first() {
+        result.push_str(&format!("{}: Fear & Greed {} ({})\n", i18n::t("Sentiment"), fear_greed.value, fear_greed.classification));
+    } else if report.is_unavailable(FEAR_GREED_SECTION) {
+        result.push_str(&format!("{}\n", i18n::t("Sentiment: Fear & Greed unavailable")));
+    }
+
+    result.push_str(&format!("\n{}", render_signal(signal)));
+    result
+}
+
+/// Render backtest statistics and the trade log
+pub fn render_backtest(backtest: &BacktestResult) -> String {
+    // The replayed candles are in the quote currency, and so is the balance
+    let money = |value: f64| currency::quote().price(value);
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("BACKTEST RESULTS")));
+    result.push_str(&format!("{}: {}\n", i18n::t("Signal source"), i18n::term(&backtest.source)));
+    result.push_str(&format!("{}: {}\n", i18n::t("Candles replayed"), backtest.candles));
+    result.push_str(&format!("{}: {}\n", i18n::t("Initial balance"), money(backtest.initial_balance)));
+    result.push_str(&format!("{}: {}\n", i18n::t("Final balance"), money(backtest.final_balance)));
+    result.push_str(&format!("{}: {} ({:+.2}%)\n", i18n::t("Total PnL"),
+        money(backtest.final_balance - backtest.initial_balance), backtest.total_return_pct));
+    result.push_str(&format!("{}: {}\n", i18n::t("Trades"), backtest.trades.len()));
+    result.push_str(&format!("{}: {:.1}%\n", i18n::t("Win rate"), backtest.win_rate));
+    result.push_str(&format!("{}: {:.2}%\n", i18n::t("Max drawdown"), backtest.max_drawdown_pct));
+    result.push_str(&format!("{}: {}\n", i18n::t("Fees paid"), money(backtest.fees_paid)));
+
+    if !backtest.trades.is_empty() {
+        result.push_str(&format!("\n{}:\n", i18n::t("Trade log")));
+        for trade in &backtest.trades {
+            result.push_str(&format!("{} -> {}: {} -> {} {} {} ({:+.2}%) [{}]\n",
+                trade.entry_time.format("%Y-%m-%d %H:%M"), trade.exit_time.format("%Y-%m-%d %H:%M"),
+                money(trade.entry_price), money(trade.exit_price), i18n::t("PnL"), money(trade.pnl), trade.return_pct,
+                i18n::term(&trade.exit_reason)));
+        }
+    }
+
+    result
+}
+
+/// Render per-window walk-forward results and out-of-sample consistency
+pub fn render_walk_forward(walk_forward: &WalkForwardResult) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("WALK-FORWARD RESULTS")));
+    result.push_str(&format!("{}: {}\n", i18n::t("Signal source"), i18n::term(&walk_forward.source)));
+
+    for (i, window) in walk_forward.windows.iter().enumerate() {
+        result.push_str(&format!("\n{}\n", i18n::tf("Window {number}: train from {train_start}, test {test_start} to {test_end}", &[
+            ("number", &(i + 1)), ("train_start", &window.train_start.format("%Y-%m-%d")),
+            ("test_start", &window.test_start.format("%Y-%m-%d")), ("test_end", &window.test_end.format("%Y-%m-%d")),
+        ])));
+        result.push_str(&format!("  {}: {}\n", i18n::t("Parameters"), render_rules(&window.rules)));
+        result.push_str(&format!("  {}: {:+.2}%\n", i18n::t("In-sample return"), window.in_sample_return_pct));
+        result.push_str(&format!("  {}: {:+.2}% ({})\n", i18n::t("Out-of-sample return"), window.out_of_sample_return_pct,
+            i18n::tf("{trades} trades, max drawdown {drawdown}%", &[
+                ("trades", &window.out_of_sample_trades), ("drawdown", &format!("{:.2}", window.out_of_sample_max_drawdown_pct)),
+            ])));
+    }
+
+    result.push_str(&format!("\n{}: {:+.2}%\n", i18n::t("Average in-sample return"), walk_forward.average_in_sample_return_pct));
+    result.push_str(&format!("{}: {:+.2}%\n", i18n::t("Average out-of-sample return"), walk_forward.average_out_of_sample_return_pct));
+    result.push_str(&format!("{}: {:.0}%\n", i18n::t("Profitable out-of-sample windows"), walk_forward.consistency_pct));
+
+    result
+}
+
+/// Render the best configurations found by the optimizer
+pub fn render_optimization(optimization: &OptimizationResult) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("OPTIMIZATION RESULTS")));
+    result.push_str(&format!("{}: {}\n", i18n::t("Signal source"), i18n::term(&optimization.source)));
+    result.push_str(&format!("{}: {}\n", i18n::t("Configurations tested"), optimization.tested));
+
+    for (i, candidate) in optimization.best.iter().enumerate() {
+        let indicators = &candidate.indicators;
+        result.push_str(&format!("\n{}. {}\n", i + 1, i18n::t(if candidate.robust { "Robust" } else { "Not robust" })));
+        result.push_str(&format!("  RSI {}, MACD ({}, {}, {}), Bollinger ({}, {})\n",
+            indicators.rsi_period, indicators.macd_fast, indicators.macd_slow, indicators.macd_signal,
+            indicators.bollinger_period, indicators.bollinger_std_dev));
+        result.push_str(&format!("  {}\n", render_rules(&candidate.rules)));
+        result.push_str(&format!("  {}: {:+.2}% ({})\n", i18n::t("Return"), candidate.total_return_pct,
+            i18n::tf("max drawdown {drawdown}%, {trades} trades, win rate {win_rate}%", &[
+                ("drawdown", &format!("{:.2}", candidate.max_drawdown_pct)), ("trades", &candidate.trades),
+                ("win_rate", &format!("{:.1}", candidate.win_rate)),
+            ])));
+        result.push_str(&format!("  {}\n", i18n::tf("First half: {first}%, second half: {second}%", &[
+            ("first", &format!("{:+.2}", candidate.first_half_return_pct)), ("second", &format!("{:+.2}", candidate.second_half_return_pct)),
+        ])));
+    }
+
+    result
+}
+
+/// The signal rules a backtest window or optimizer candidate ran with
+fn render_rules(rules: &SignalRules) -> String {
+    i18n::tf("buy score {buy}, sell score {sell}, stop {stop} ATR, target {target} ATR", &[
+        ("buy", &format!("{:+.0}", rules.buy_score)), ("sell", &format!("{:+.0}", rules.sell_score)),
+        ("stop", &format!("{:.1}", rules.stop_atr)), ("target", &format!("{:.1}", rules.target_atr)),
+    ])
+}
+
+/// Render the paper account's position and PnL after a step
+pub fn render_paper_status(state: &PaperState, signal: &TradeSignal, price: f64) -> String {
+    let mut result = String::new();
+    let equity = state.equity(price);
+
+    result.push_str(&format!("=== {} ({}) ===\n", i18n::t("PAPER TRADING"), Utc::now().format("%Y-%m-%d %H:%M UTC")));
+    result.push_str(&format!("{}\n", i18n::tf("Signal: {action} at {price}", &[
+        ("action", &signal.action.as_str()), ("price", &Currency::USD.price(price)),
+    ])));
+    match &state.position {
+        Some(position) => result.push_str(&format!("{}\n", i18n::tf("Position: {quantity} BTC from {price}", &[
+            ("quantity", &format!("{:.6}", position.quantity)), ("price", &Currency::USD.price(position.entry_price)),
+        ]))),
+        None => result.push_str(&format!("{}\n", i18n::t("Position: flat"))),
+    }
+    result.push_str(&format!("{}: {} ({:+.2}%)\n", i18n::t("Equity"), Currency::USD.price(equity),
+        (equity - state.initial_balance) / state.initial_balance * 100.0));
+    result.push_str(&format!("{}: {}\n", i18n::t("Realized PnL"), Currency::USD.price(state.realized_pnl)));
+    result.push_str(&format!("{}: {}\n", i18n::t("Unrealized PnL"), Currency::USD.price(state.unrealized_pnl(price))));
+    result.push_str(&format!("{}: {}\n", i18n::t("Closed trades"), state.trades.len()));
+
+    result
+}
+
+/// Format the ATR-based stop and target levels for both trade directions
+fn render_trade_levels(levels: &TradeLevels, currency: Currency) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("\n=== {} ===\n", i18n::t("TRADE LEVELS")));
+    result.push_str(&format!("{}\n", i18n::tf("Entry: {entry}, ATR: {atr} (stop {stop_atr}x ATR, target {target_r}R)", &[
+        ("entry", &currency.price(levels.entry)), ("atr", &currency.price(levels.atr)), ("stop_atr", &levels.stop_atr), ("target_r", &levels.target_r),
+    ])));
+    let sides = [(i18n::t("Long"), &levels.long), (i18n::t("Short"), &levels.short)];
+    for (side, level) in sides {
+        result.push_str(&format!("{}: {}\n", side, i18n::tf("stop loss {stop}, take profit {target}", &[
+            ("stop", &currency.price(level.stop_loss)), ("target", &currency.price(level.take_profit)),
+        ])));
+    }
+
+    result.push_str(&format!("\n{}:\n", i18n::tf("Risk/reward vs support and resistance (minimum {min_rr}:1)", &[("min_rr", &format!("{:.1}", levels.min_rr))])));
+    if levels.setups.is_empty() {
+        result.push_str(&format!("{}\n", i18n::t("No setup with the target beyond the entry")));
+    }
+    for setup in &levels.setups {
+        result.push_str(&format!("{}: {}{}\n", i18n::term(&setup.name), i18n::tf("entry {entry}, stop {stop}, target {target}, R:R {rr}:1", &[
+            ("entry", &currency.price(setup.entry)), ("stop", &currency.price(setup.stop_loss)),
+            ("target", &currency.price(setup.take_profit)), ("rr", &format!("{:.2}", setup.risk_reward)),
+        ]), if setup.meets_minimum { "" } else { i18n::t(" (below minimum)") }));
+    }
+
+    result
+}
+
+/// Format the risk-based position size for the configured account
+fn render_position_sizing(sizing: &PositionSizing, currency: Currency) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("\n=== {} ===\n", i18n::t("POSITION SIZING")));
+    result.push_str(&format!("{}\n", i18n::tf("Account equity: {equity}, risk per trade: {risk_pct}% ({risk})", &[
+        ("equity", &currency.price(sizing.account_equity)), ("risk_pct", &format!("{:.2}", sizing.risk_pct)), ("risk", &currency.price(sizing.risk_amount)),
+    ])));
+    result.push_str(&format!("{}\n", i18n::tf("Stop distance: {distance} from entry {entry}", &[
+        ("distance", &currency.price(sizing.stop_distance)), ("entry", &currency.price(sizing.entry)),
+    ])));
+    result.push_str(&format!("{}{}\n", i18n::tf("Position size: {size} BTC ({value})", &[
+        ("size", &format!("{:.6}", sizing.position_size)), ("value", &currency.price(sizing.position_value)),
+    ]), if sizing.capped { i18n::t(", capped at account equity") } else { "" }));
+    if let (Some(fraction), Some(value)) = (sizing.kelly_fraction, sizing.kelly_position_value) {
+        result.push_str(&format!("{}\n", i18n::tf("Kelly fraction: {fraction}% of equity ({value})", &[
+            ("fraction", &format!("{:.1}", fraction * 100.0)), ("value", &currency.price(value)),
+        ])));
+    }
+
+    result
+}
+
+/// Format the valued holdings with their allocation and PnL
+fn render_portfolio(portfolio: &PortfolioReport, currency: Currency) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("\n=== {} ===\n", i18n::t("PORTFOLIO")));
+    for holding in &portfolio.holdings {
+        result.push_str(&format!("{}: {} @ {} = {} ({})", holding.asset, holding.amount, currency.price(holding.price), currency.price(holding.value),
+            i18n::tf("{allocation}% of portfolio", &[("allocation", &format!("{:.1}", holding.allocation_pct))])));
+        if let Some(average_cost) = holding.average_cost {
+            result.push_str(&format!(", {}", i18n::tf("average cost {cost}", &[("cost", &currency.price(average_cost))])));
+        }
+        if let (Some(pnl), Some(pnl_pct)) = (holding.pnl, holding.pnl_pct) {
+            result.push_str(&format!(", {}", i18n::tf("unrealized PnL {pnl} ({pnl_pct}%)", &[
+                ("pnl", &currency.price(pnl)), ("pnl_pct", &format!("{:+.2}", pnl_pct)),
+            ])));
+        }
+        result.push('\n');
+    }
+    result.push_str(&format!("{}: {}\n", i18n::t("Total value"), currency.price(portfolio.total_value)));
+    if let (Some(cost), Some(pnl)) = (portfolio.total_cost, portfolio.total_pnl) {
+        result.push_str(&format!("{}\n", i18n::tf("Total cost: {cost}, unrealized PnL: {pnl}", &[("cost", &currency.price(cost)), ("pnl", &currency.price(pnl))])));
+    }
+
+    result
+}
+
+/// Format the headlines as a <news> block, with each one's age, source credibility and weight
+fn render_news(news: &[NewsHeadline], now: DateTime<Utc>) -> String {
+    let mut result = String::new();
+
+    result.push_str("\n<news>\n");
+    for headline in news {
+        let age = now - headline.published_at;
+        result.push_str(&format!("- [{}, {}] {} ({})\n", headline.published_at.format("%Y-%m-%d %H:%M UTC"),
+            i18n::tf("{hours}h ago", &[("hours", &age.num_hours().max(0))]), headline.title,
+            i18n::tf("{source}, {credibility} credibility, weight {weight}", &[
+                ("source", &headline.source), ("credibility", &i18n::t(headline.credibility.as_str())), ("weight", &format!("{:.2}", headline.weight)),
+            ])));
+    }
+    result.push_str("</news>\n");
+
+    result
+}
+
+/// Latest price, recent changes and the last three candles, for reports that don't include the full technical data
+pub fn render_price_context(candles: &[Candle], currency: Currency) -> String {
+    let mut result = String::new();
+
+    result.push_str("=== BITCOIN PRICE ===\n");
+    let last = candles[candles.len() - 1];
+    result.push_str(&format!("Price: {}\n", currency.price(last.close)));
+    let daily = reports::daily_closes(candles);
+    for days in [1, 7, 30] {
+        if let Some(change) = reports::change_since(&daily, days) {
+            result.push_str(&format!("{}-day change: {:+.2}%\n", days, change));
+        }
+    }
+    result.push_str("Recent candles:\n");
+    for candle in &candles[candles.len().saturating_sub(3)..] {
+        let time = DateTime::from_timestamp_millis(candle.timestamp as i64).unwrap_or_default();
+        result.push_str(&format!("{}: O={} H={} L={} C={} V={:.2}\n", time.format("%Y-%m-%d %H:%M:%S"),
+            currency.price(candle.open), currency.price(candle.high), currency.price(candle.low), currency.price(candle.close), candle.volume));
+    }
+
+    result
+}
+
+/// Format the network statistics and mempool state
+pub fn render_onchain(stats: &OnchainStats, mempool: Option<&MempoolStats>) -> String {
+    const SATOSHIS_PER_BTC: f64 = 100_000_000.0;
+    let mut result = String::new();
+
+    result.push_str("\n=== ON-CHAIN ACTIVITY (LAST 24 HOURS) ===\n");
+    result.push_str(&format!("Hash rate: {:.1} EH/s\n", stats.hash_rate / 1e9));
+    result.push_str(&format!("Difficulty: {:.3}T\n", stats.difficulty / 1e12));
+    result.push_str(&format!("Blocks mined: {:.0} (average {:.1} minutes apart)\n", stats.n_blocks_mined, stats.minutes_between_blocks));
+    result.push_str(&format!("Transactions: {:.0}\n", stats.n_tx));
+    result.push_str(&format!("Estimated BTC sent: {:.0} BTC ({})\n",
+        stats.estimated_btc_sent / SATOSHIS_PER_BTC, Currency::USD.round(stats.estimated_transaction_volume_usd)));
+    result.push_str(&format!("Total fees: {:.2} BTC\n", stats.total_fees_btc / SATOSHIS_PER_BTC));
+    result.push_str(&format!("Miners' revenue: {}\n", Currency::USD.round(stats.miners_revenue_usd)));
+
+    if let Some(mempool) = mempool {
+        result.push_str("\n=== MEMPOOL ===\n");
+        result.push_str(&format!("Unconfirmed transactions: {} ({:.1} MvB)\n", mempool.count, mempool.vsize as f64 / 1e6));
+        result.push_str(&format!("Recommended fees: {:.0} sat/vB next block, {:.0} within an hour, {:.0} economy\n",
+            mempool.fastest_fee, mempool.hour_fee, mempool.economy_fee));
+    }
+
+    result
+}
+
+/// Format the macro assets next to Bitcoin, with their correlation to it
+pub fn render_macro(assets: &[MacroAsset], dominance: Option<f64>) -> String {
+    let change = |change: Option<f64>| change.map(|change| format!("{:+.2}%", change)).unwrap_or_else(|| "-".to_string());
+    let mut result = String::new();
+
+    result.push_str("\n=== MACRO MARKETS ===\n");
+    result.push_str("Asset: last, 7-day change, 30-day change, 30-day correlation with BTC\n");
+    for asset in assets {
+        result.push_str(&format!("{} ({}): {:.2}, {}, {}, {}\n", asset.name, asset.symbol, asset.last,
+            change(asset.change_7d), change(asset.change_30d),
+            asset.correlation.map(|correlation| format!("{:+.2}", correlation)).unwrap_or_else(|| "-".to_string())));
+    }
+    if let Some(dominance) = dominance {
+        result.push_str(&format!("\nBTC dominance: {:.1}% of the crypto market cap\n", dominance));
+    }
+
+    result
+}
+
+/// Format Bitcoin's last seven days against the week before
+pub fn render_week(week: &WeekSummary, currency: Currency) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== THIS WEEK (SINCE {}) ===\n", week.start.format("%Y-%m-%d %H:%M UTC")));
+    result.push_str(&format!("Open: {}, close: {} ({:+.2}%)\n", currency.price(week.open), currency.price(week.close), week.change_pct));
+    result.push_str(&format!("High: {}, low: {}\n", currency.price(week.high), currency.price(week.low)));
+    result.push_str(&format!("Volume: {:.2}", week.volume));
+    if let Some(previous) = week.previous_volume.filter(|previous| *previous > 0.0) {
+        result.push_str(&format!(" ({:+.1}% vs the previous week)", (week.volume - previous) / previous * 100.0));
+    }
+    result.push('\n');
+    result.push_str("Daily closes:\n");
+    for (date, close) in &week.daily_closes {
+        result.push_str(&format!("{}: {}\n", date, currency.price(*close)));
+    }
+
+    result
+}
+
+/// Format the model's structured conclusions
+pub fn render_analysis_result(result: &AnalysisResult, currency: Currency) -> String {
+    let prices = |prices: &[f64]| if prices.is_empty() {
+        "-".to_string()
+    } else {
+        prices.iter().map(|price| currency.price(*price)).collect::<Vec<_>>().join(", ")
+    };
+
+    let mut output = String::new();
+    output.push_str("=== STRUCTURED SUMMARY ===\n");
+    output.push_str(&format!("Outlook: {} (confidence {:.0}%)\n", result.outlook.as_str(), result.confidence));
+    output.push_str(&format!("Recommendation: {}\n", result.recommendation.as_str()));
+    output.push_str(&format!("Risk level: {}\n", result.risk_level.as_str()));
+    output.push_str(&format!("Entries: {}\n", prices(&result.entries)));
+    output.push_str(&format!("Stop losses: {}\n", prices(&result.stop_losses)));
+    output.push_str(&format!("Take profits: {}\n", prices(&result.take_profits)));
+    if !result.summary.is_empty() {
+        output.push_str(&format!("Summary: {}\n", result.summary));
+    }
+
+    output
+}
+
+/// Format the recommendation block parsed from the model's reply
+pub fn render_recommendation(recommendation: &Recommendation, currency: Currency) -> String {
+    let price = |price: Option<f64>| price.map(|price| currency.price(price)).unwrap_or_else(|| "-".to_string());
+
+    let mut output = String::new();
+    output.push_str("=== RECOMMENDATION ===\n");
+    output.push_str(&format!("Direction: {} (confidence {:.0}%)\n", recommendation.direction.as_str(), recommendation.confidence));
+    output.push_str(&format!("Entry: {}\n", price(recommendation.entry)));
+    output.push_str(&format!("Stop: {}\n", price(recommendation.stop)));
+    let targets: Vec<String> = recommendation.targets.iter().map(|target| currency.price(*target)).collect();
+    output.push_str(&format!("Targets: {}\n", if targets.is_empty() { "-".to_string() } else { targets.join(", ") }));
+    if !recommendation.horizon.is_empty() {
+        output.push_str(&format!("Horizon: {}\n", recommendation.horizon));
+    }
+
+    output
+}
+
+/// Format each model's call in an ensemble run and whether they agree, with levels in the quote currency
+pub fn render_consensus(consensus: &Consensus) -> String {
+    let currency = currency::quote();
+    let mut output = String::new();
+    output.push_str("=== MODEL CONSENSUS ===\n");
+
+    for call in &consensus.calls {
+        output.push_str(&format!("{}: ", call.provider));
+        match (&call.error, call.action, &call.result) {
+            (Some(error), _, _) => output.push_str(&format!("failed ({})", error)),
+            (None, Some(action), Some(result)) => {
+                output.push_str(&format!("{} ({}, {:.0}% confidence)", action.as_str(), result.outlook.as_str(), result.confidence));
+                for (label, levels) in [("entries", &result.entries), ("stops", &result.stop_losses), ("targets", &result.take_profits)] {
+                    if !levels.is_empty() {
+                        let levels: Vec<String> = levels.iter().map(|price| currency.price(*price)).collect();
+                        output.push_str(&format!(", {} {}", label, levels.join("/")));
+                    }
+                }
+            }
+            (None, Some(action), None) => output.push_str(action.as_str()),
+            (None, None, _) => output.push_str("no clear recommendation"),
+        }
+        output.push('\n');
+    }
+
+    let voting = consensus.calls.iter().filter(|call| call.action.is_some()).count();
+    match consensus.action {
+        Some(action) if consensus.votes == voting => {
+            output.push_str(&format!("Agreement: all {} models recommend {}\n", voting, action.as_str()));
+        }
+        Some(action) => {
+            output.push_str(&format!("Majority: {} of {} models recommend {}\n", consensus.votes, voting, action.as_str()));
+            let dissent: Vec<String> = consensus.calls.iter()
+                .filter_map(|call| call.action.filter(|other| *other != action).map(|other| format!("{} ({})", call.provider, other.as_str())))
+                .collect();
+            output.push_str(&format!("Disagreeing: {}\n", dissent.join(", ")));
+        }
+        None => output.push_str("No consensus: the models are split; weigh their arguments below before acting\n"),
+    }
+
+    output
+}
+
+/// Format the win rate and expectancy of the journaled trades
+pub fn render_journal_summary(summary: &JournalSummary) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("TRADE JOURNAL")));
+    result.push_str(&format!("{}\n", i18n::tf("Closed trades: {closed} (open: {open})", &[("closed", &summary.closed_trades), ("open", &summary.open_trades)])));
+    result.push_str(&format!("{}: {:.1}%\n", i18n::t("Win rate"), summary.win_rate));
+    result.push_str(&format!("{}\n", i18n::tf("Average win: {win}, average loss: {loss}", &[
+        ("win", &Currency::USD.price(summary.average_win)), ("loss", &Currency::USD.price(summary.average_loss)),
+    ])));
+    result.push_str(&format!("{}\n", i18n::tf("Expectancy: {expectancy} per trade", &[("expectancy", &Currency::USD.price(summary.expectancy))])));
+    result.push_str(&format!("{}: {}\n", i18n::t("Total PnL"), Currency::USD.price(summary.total_pnl)));
+
+    result
+}
+
+/// Format the condensed earlier analyses with how far price has moved since each, oldest first; prices written in
+/// another currency are converted into the report's
+pub fn render_memory(entries: &[MemoryEntry], report: &TechnicalReport) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("PREVIOUS ANALYSES")));
+    for entry in entries {
+        let then = entry.price_at(report.usd_rate);
+        let change = (report.last_price() - then) / then * 100.0;
+        result.push_str(&format!("{} ({}): {}\n", entry.time.format("%Y-%m-%d %H:%M UTC"), i18n::tf("BTC {price}, {change}% since", &[
+            ("price", &report.currency.price(then)), ("change", &format!("{:+.2}", change)),
+        ]), entry.summary));
+    }
+
+    result
+}
+
+/// The last stored analysis with when it was written, without the data points it was prefixed with
+pub fn render_previous_analysis(entry: &MemoryEntry, report: &TechnicalReport) -> String {
+    let analysis = match entry.analysis.strip_prefix("=== LAST 3 DATA POINTS ===") {
+        Some(rest) => rest.split_once("\n\n").map(|(_, analysis)| analysis).unwrap_or(rest),
+        None => &entry.analysis,
+    };
+    let then = entry.price_at(report.usd_rate);
+    let change = (report.last_price() - then) / then * 100.0;
+    format!("{}\n\n{}", i18n::tf("Written {time} at BTC {price} ({change}% since)", &[
+        ("time", &entry.time.format("%Y-%m-%d %H:%M UTC")), ("price", &report.currency.price(then)), ("change", &format!("{:+.2}", change)),
+    ]), analysis.trim())
+}
+
+/// Format every journaled trade, oldest first
+pub fn render_journal_entries(entries: &[JournalEntry]) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("TRADE JOURNAL")));
+    if entries.is_empty() {
+        result.push_str(&format!("{}\n", i18n::t("No trades recorded")));
+    }
+    for entry in entries {
+        result.push_str(&format!("#{} {} {} {} @ {}", entry.id, entry.opened_at.format("%Y-%m-%d %H:%M"),
+            entry.side.to_uppercase(), entry.size, Currency::USD.price(entry.entry)));
+        match (entry.exit, entry.pnl) {
+            (Some(exit), Some(pnl)) => result.push_str(&format!(" -> {}, {} {}", Currency::USD.price(exit), i18n::t("PnL"), Currency::USD.price(pnl))),
+            _ => result.push_str(&format!(" ({})", i18n::t("open"))),
+        }
+        if !entry.rationale.is_empty() {
+            result.push_str(&format!(" - {}", entry.rationale));
+        }
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Accuracy and calibration of each model and prompt version's calls
+pub fn render_accuracy(scorecards: &[Scorecard], config: &AccuracyConfig) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== PREDICTION ACCURACY ({}h horizon, last {} days) ===\n", config.horizon_hours, config.days));
+    if scorecards.is_empty() {
+        result.push_str("No calls old enough to score yet\n");
+    }
+    for scorecard in scorecards {
+        result.push_str(&format!("\n{} (prompt {})\n", scorecard.providers, scorecard.prompt_version));
+        result.push_str(&format!("Accuracy: {:.1}% ({} of {} calls)\n", scorecard.accuracy, scorecard.correct, scorecard.calls));
+        result.push_str(&format!("Average move in the called direction: {:+.2}%\n", scorecard.average_move));
+        if let Some(error) = scorecard.target_error {
+            result.push_str(&format!("Average first-target error: {:.2}%\n", error));
+        }
+        for bucket in &scorecard.calibration {
+            result.push_str(&format!("  Confidence {}: {} calls, stated {:.0}%, right {:.0}%\n",
+                bucket.range, bucket.calls, bucket.average_confidence, bucket.accuracy));
+        }
+    }
+
+    result
+}
+
+/// What a run would send and where, without having sent anything
+pub fn render_dry_run(dry_run: &DryRun) -> String {
+    let mut result = String::new();
+
+    result.push_str("=== DRY RUN (no model request, nothing sent) ===\n");
+    result.push_str(&format!("Market: {} {} at {}\n", dry_run.symbol, dry_run.interval, dry_run.currency.price(dry_run.price)));
+    result.push_str(&format!("Prompt: {} version, ~{} tokens\n", dry_run.prompt_version, dry_run.prompt_tokens));
+    result.push_str(&format!("Models: {}\n", dry_run.providers.join(", ")));
+    result.push_str(&format!("Outputs: {}\n", dry_run.outputs.join(", ")));
+    result.push_str(&format!("Chart: {}\n", if dry_run.chart { "rendered" } else { "not needed" }));
+    if !dry_run.unavailable.is_empty() {
+        result.push_str(&format!("Unavailable sections: {}\n", dry_run.unavailable.join(", ")));
+    }
+    match dry_run.estimated_cost {
+        Some(cost) => result.push_str(&format!("Estimated cost: up to ${:.4}\n", cost)),
+        None => result.push_str("Estimated cost: set AI_INPUT_COST_PER_MTOK and AI_OUTPUT_COST_PER_MTOK to estimate it\n"),
+    }
+
+    result
+}
+
+/// The "What changed" section opening an analysis, against the run at `since`
+pub fn render_delta(since: &DateTime<Utc>, changes: &[String]) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::tf("WHAT CHANGED SINCE {time}", &[("time", &since.format("%Y-%m-%d %H:%M UTC"))])));
+    for change in changes {
+        result.push_str(&format!("- {}\n", change));
+    }
+
+    result
+}
+
+pub fn render_history(runs: &[Run]) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("RUN HISTORY")));
+    if runs.is_empty() {
+        result.push_str(&format!("{}\n", i18n::t("No runs recorded")));
+    }
+    for run in runs {
+        result.push_str(&format!("#{} {} {} {} @ {}: {} ({})\n", run.id, run.run_at.format("%Y-%m-%d %H:%M"),
+            run.symbol, run.interval, run.currency.price(run.price), run.action.as_deref().unwrap_or(i18n::t("no call")),
+            i18n::tf("{providers}, prompt {version}", &[("providers", &run.providers), ("version", &run.prompt_version)])));
+    }
+
+    result
+}
+
+/// One stored run with its analysis
+pub fn render_history_run(run: &Run) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::tf("RUN #{id}", &[("id", &run.id)])));
+    result.push_str(&format!("{}: {}\n", i18n::t("Time"), run.run_at.format("%Y-%m-%d %H:%M UTC")));
+    result.push_str(&format!("{}: {} ({})\n", i18n::t("Market"), run.symbol, run.interval));
+    result.push_str(&format!("{}: {}\n", i18n::t("Price"), run.currency.price(run.price)));
+    result.push_str(&format!("{}: {}\n", i18n::t("Models"), run.providers));
+    result.push_str(&format!("{}: {} ({})\n", i18n::t("Prompt"), run.prompt_version, &run.prompt_hash[..run.prompt_hash.len().min(12)]));
+    result.push_str(&format!("{}: {}\n", i18n::t("Call"), run.action.as_deref().unwrap_or(i18n::t("none"))));
+    if let Some(score) = run.report.pointer("/composite/score").and_then(|score| score.as_f64()) {
+        result.push_str(&format!("{}: {:.1}\n", i18n::t("Composite score"), score));
+    }
+    // The delivered text already ends with the structured conclusions and recommendation block
+    result.push_str(&format!("\n{}\n", run.analysis));
+
+    result
+}
+
+/// Format the score and its per-indicator breakdown
+fn render_composite(composite: &CompositeScore) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("=== {} ===\n", i18n::t("COMPOSITE SIGNAL SCORE")));
+    result.push_str(&format!("{}: {:+.1} / 100 ({})\n", i18n::t("Score"), composite.score, i18n::t(composite.label())));
+    result.push_str(&format!("{}:\n", i18n::t("Contributions (signal x weight)")));
+
+    for c in &composite.contributions {
+        result.push_str(&format!("  {}: {:+.2} x {:.2} = {:+.2} - {}\n",
+            c.indicator, c.signal, c.weight, c.signal * c.weight, c.reason));
+    }
+
+    result.push('\n');
+    result
+}
+
+/// Format one indicator's recent values and trend analysis
+fn render_indicator(indicator: &IndicatorReport, currency: Currency) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("\n{} - {}:\n", indicator.title, i18n::tf("Last {count} periods", &[("count", &indicator.periods.len())])));
+
+    let period_count = indicator.periods.len();
+    for (i, period) in indicator.periods.iter().enumerate() {
+        let date = match period.time {
+            Some(time) => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => i18n::tf("Period -{index}", &[("index", &(period_count - i))]),
+        };
+
+        result.push_str(&format!("{}:\n", date));
+        for value in &period.values {
+            result.push_str(&format!("  {}: {}\n", i18n::term(&value.name), format_value(value.value, value.unit, currency)));
+        }
+        if let Some(indication) = &period.indication {
+            result.push_str(&format!("  {}: {}\n", i18n::t("Indication"), indication));
+        }
+    }
+
+    if !indicator.analysis.is_empty() {
+        result.push_str(&format!("\n{}:\n", indicator.analysis_title));
+        for line in &indicator.analysis {
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+
+    result
+}
+
+pub fn format_value(value: f64, unit: ValueUnit, currency: Currency) -> String {
+    match unit {
+        ValueUnit::Price => currency.price(value),
+        ValueUnit::Percent => format!("{}%", locale::current().number(value, 2)),
+        ValueUnit::Number => locale::current().number(value, 2),
+        ValueUnit::Volume => locale::current().number(value, 0),
+    }
+}
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::currency::Currency;
+use crate::indicators::Interpretation;
+use crate::journal::JournalSummary;
+use crate::news::NewsHeadline;
+use crate::portfolio::PortfolioReport;
+use crate::risk::{PositionSizing, TradeLevels};
+use crate::signal_score::CompositeScore;
+
+/// Names of the optional sections, as listed in `TechnicalReport::unavailable`
+pub const FEAR_GREED_SECTION: &str = "Fear & Greed Index";
+pub const PORTFOLIO_SECTION: &str = "Portfolio";
+pub const JOURNAL_SECTION: &str = "Trade journal";
+pub const NEWS_SECTION: &str = "News";
+
+/// Structured result of the technical analysis, rendered to text separately
+#[derive(Serialize)]
+pub struct TechnicalReport {
+    pub generated_at: DateTime<Utc>,
+    pub currency: Currency,                          // What the prices and levels are in
+    pub usd_rate: f64,                               // Units of `currency` per US dollar, 1 for USD
+    pub composite: CompositeScore,
+    pub market: Option<MarketSummary>,
+    pub recent_candles: Vec<CandleRow>,
+    pub price_points: Vec<(DateTime<Utc>, f64)>,     // Close-only fallback when OHLC data is missing
+    pub indicators: Vec<IndicatorReport>,
+    pub levels: Levels,
+    pub fear_greed: Vec<SentimentReading>,
+    pub trade_levels: Option<TradeLevels>,           // Only when ATR is available
+    pub position_sizing: Option<PositionSizing>,     // Only when account equity is configured
+    pub portfolio: Option<PortfolioReport>,          // Only when holdings are configured
+    pub journal: Option<JournalSummary>,             // Only once trades have been journaled
+    pub news: Vec<NewsHeadline>,                     // Only when the news fetcher is enabled
+    pub unavailable: Vec<String>,                    // Sections left out because their source failed this run
+}
+
+impl TechnicalReport {
+    /// Look up an indicator's report by key
+    pub fn indicator(&self, key: &str) -> Option<&IndicatorReport> {
+        self.indicators.iter().find(|indicator| indicator.key == key)
+    }
+
+    /// Note that a section's source failed, so the report goes out without it
+    pub fn mark_unavailable(&mut self, section: &str) {
+        self.unavailable.push(section.to_string());
+    }
+
+    pub fn is_unavailable(&self, section: &str) -> bool {
+        self.unavailable.iter().any(|name| name == section)
+    }
+
+    /// Most recent close price
+    pub fn last_price(&self) -> f64 {
+        self.recent_candles.last().map(|candle| candle.close)
+            .or_else(|| self.price_points.last().map(|(_, price)| *price))
+            .unwrap_or(0.0)
+    }
+}
+
+/// Summary statistics over the whole fetched history
+#[derive(Serialize)]
+pub struct MarketSummary {
+    pub highest_closes: Vec<(DateTime<Utc>, f64)>,
+    pub lowest_closes: Vec<(DateTime<Utc>, f64)>,
+    pub average_price: f64,
+    pub all_time_high: f64,
+    pub all_time_low: f64,
+    pub std_dev: f64,
+    pub average_volume: f64,
+    pub change_30_periods: Option<f64>,
+    pub change_7_periods: Option<f64>,
+}
+
+/// One OHLCV row for the recent data table
+#[derive(Serialize)]
+pub struct CandleRow {
+    pub time: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+/// Key price levels derived from the data
+#[derive(Serialize)]
+pub struct Levels {
+    pub support: f64,
+    pub resistance: f64,
+}
+
+/// One day of the Fear & Greed Index
+#[derive(Serialize)]
+pub struct SentimentReading {
+    pub date: DateTime<Utc>,
+    pub value: u32,
+    pub classification: String,
+}
+
+/// How a value should be displayed
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueUnit {
+    Price,
+    Percent,
+    Number,
+    Volume,
+}
+
+/// A single named indicator line at one period (e.g. "SMA (20-period)")
+#[derive(Serialize)]
+pub struct IndicatorValue {
+    pub name: String,
+    pub value: f64,
+    pub unit: ValueUnit,
+}
+
+/// All indicator lines at one period
+#[derive(Serialize)]
+pub struct IndicatorPeriod {
+    pub time: Option<DateTime<Utc>>,
+    pub values: Vec<IndicatorValue>,
+    pub indication: Option<String>,
+}
+
+impl IndicatorPeriod {
+    pub fn new(time: Option<DateTime<Utc>>) -> Self {
+        IndicatorPeriod { time, values: Vec::new(), indication: None }
+    }
+
+    pub fn value(mut self, name: &str, value: f64, unit: ValueUnit) -> Self {
+        self.values.push(IndicatorValue { name: name.to_string(), value, unit });
+        self
+    }
+
+    pub fn indication(mut self, indication: &str) -> Self {
+        self.indication = Some(indication.to_string());
+        self
+    }
+}
+
+/// Computed values, trend analysis and directional reading of one indicator
+#[derive(Serialize)]
+pub struct IndicatorReport {
+    pub key: String,
+    pub title: String,
+    pub periods: Vec<IndicatorPeriod>,
+    pub analysis_title: String,
+    pub analysis: Vec<String>,
+    pub interpretation: Option<Interpretation>,
+}
+
+impl IndicatorReport {
+    pub fn new(key: &str, title: &str, analysis_title: &str) -> Self {
+        IndicatorReport {
+            key: key.to_string(),
+            title: title.to_string(),
+            periods: Vec::new(),
+            analysis_title: analysis_title.to_string(),
+            analysis: Vec::new(),
+            interpretation: None,
+        }
+    }
+
+    /// Latest value of a named line (e.g. "RSI")
+    pub fn latest(&self, name: &str) -> Option<f64> {
+        self.periods.last()?
+            .values.iter()
+            .find(|value| value.name == name)
+            .map(|value| value.value)
+    }
+
+    /// Value of a named line one period before the latest
+    pub fn previous(&self, name: &str) -> Option<f64> {
+        self.periods.iter().rev().nth(1)?
+            .values.iter()
+            .find(|value| value.name == name)
+            .map(|value| value.value)
+    }
+}
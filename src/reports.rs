@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use crate::ai_client::{self, AnalysisProvider};
+use crate::budget;
+use crate::currency;
+use crate::data_fetcher::{self, Candle};
+use crate::i18n;
+use crate::journal;
+use crate::memory::{AnalysisMemory, MemoryConfig};
+use crate::output::{self, Outputs};
+use crate::prompt_generator::PromptTemplate;
+use crate::report::JOURNAL_SECTION;
+use crate::renderer;
+use crate::technical_analysis;
+
+/// Assets compared with Bitcoin when MACRO_SYMBOLS is not set: Yahoo Finance symbol and display name
+const DEFAULT_MACRO_ASSETS: [(&str, &str); 5] = [
+    ("^GSPC", "S&P 500"),
+    ("^IXIC", "Nasdaq Composite"),
+    ("DX-Y.NYB", "US Dollar Index"),
+    ("^TNX", "US 10-year Treasury yield"),
+    ("GC=F", "Gold"),
+];
+
+/// Daily returns used for the correlation with Bitcoin
+const CORRELATION_DAYS: usize = 30;
+
+/// Report types beyond the default analysis, each with its own data sections and prompt
+#[derive(Clone, Copy)]
+pub enum ReportKind {
+    Onchain,
+    Macro,
+    WeeklyReview,
+}
+
+impl ReportKind {
+    /// The subcommand, which is also the name of the report's prompt version
+    pub fn command(&self) -> &'static str {
+        match self {
+            ReportKind::Onchain => "onchain-report",
+            ReportKind::Macro => "macro-report",
+            ReportKind::WeeklyReview => "weekly-review",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            ReportKind::Onchain => "BITCOIN ON-CHAIN REPORT",
+            ReportKind::Macro => "BITCOIN MACRO REPORT",
+            ReportKind::WeeklyReview => "BITCOIN WEEKLY REVIEW",
+        }
+    }
+}
+
+/// A macro asset's recent performance next to Bitcoin's
+pub struct MacroAsset {
+    pub name: String,
+    pub symbol: String,
+    pub last: f64,
+    pub change_7d: Option<f64>,
+    pub change_30d: Option<f64>,
+    pub correlation: Option<f64>,  // With Bitcoin's daily returns over the last CORRELATION_DAYS
+}
+
+/// Bitcoin's last seven days against the week before
+pub struct WeekSummary {
+    pub start: DateTime<Utc>,
+    pub open: f64,
+    pub close: f64,
+    pub high: f64,
+    pub low: f64,
+    pub change_pct: f64,
+    pub volume: f64,
+    pub previous_volume: Option<f64>,
+    pub daily_closes: Vec<(NaiveDate, f64)>,
+}
+
+/// Assemble the report's data, ask the model and send the result; without providers (--only-prompt) the prompt is printed instead
+pub async fn run_report(kind: ReportKind, data_provider_api_key: &String, api_base_url: &String, providers: Option<&[Box<dyn AnalysisProvider>]>,
+                        template: &PromptTemplate, outputs: &Outputs) -> Result<(), Box<dyn Error>> {
+    log::info!("Fetching Bitcoin price data from API...");
+    let btc = currency::priced(data_fetcher::fetch_bitcoin_trading_data(data_provider_api_key, api_base_url));
+
+    // Each report's own sources are fetched at the same time as the price data
+    let prompt = match kind {
+        ReportKind::Onchain => {
+            log::info!("Fetching on-chain statistics...");
+            let (btc_data, stats, mempool) = tokio::join!(btc, data_fetcher::fetch_onchain_stats(), data_fetcher::fetch_mempool_stats());
+            let btc_data = btc_data?;
+            let candles = btc_candles(&btc_data)?;
+            let stats = stats?;
+            let mempool = mempool
+                .inspect_err(|e| log::warn!("could not fetch mempool statistics: {}", e))
+                .ok();
+            template.render(&format!("{}{}", renderer::render_price_context(&candles, btc_data.currency), renderer::render_onchain(&stats, mempool.as_ref())))
+        }
+        ReportKind::Macro => {
+            log::info!("Fetching macro market data...");
+            let (btc_data, closes, dominance) = tokio::join!(btc, fetch_macro_closes(), data_fetcher::fetch_btc_dominance());
+            let btc_data = btc_data?;
+            let candles = btc_candles(&btc_data)?;
+            let assets = macro_assets(closes, &candles)?;
+            let dominance = dominance
+                .inspect_err(|e| log::warn!("could not fetch BTC dominance: {}", e))
+                .ok();
+            template.render(&format!("{}{}", renderer::render_price_context(&candles, btc_data.currency), renderer::render_macro(&assets, dominance)))
+        }
+        ReportKind::WeeklyReview => {
+            let (btc_data, fear_and_greed_data) = tokio::join!(btc, data_fetcher::load_fear_greed());
+            let btc_data = btc_data?;
+            let candles = btc_candles(&btc_data)?;
+            let mut report = technical_analysis::build_technical_report(&btc_data, &fear_and_greed_data);
+            match journal::load_summary() {
+                Ok(journal) => report.journal = journal,
+                Err(e) => {
+                    log::warn!("could not read the trade journal, continuing without it: {}", e);
+                    report.mark_unavailable(JOURNAL_SECTION);
+                }
+            }
+
+            // The week's earlier calls, to review whether they played out
+            let memory = AnalysisMemory::load(&MemoryConfig::from_env().file)?;
+            let week_ago = Utc::now() - TimeDelta::days(7);
+            let first = memory.entries.iter().position(|entry| entry.time >= week_ago).unwrap_or(memory.entries.len());
+            let week = renderer::render_week(&summarize_week(&candles), btc_data.currency);
+            let calls = if first == memory.entries.len() {
+                format!("\n=== {} ===\n{}\n", i18n::t("PREVIOUS ANALYSES"), i18n::t("No analyses were recorded this week"))
+            } else {
+                format!("\n{}", renderer::render_memory(&memory.entries[first..], &report))
+            };
+
+            let budget = budget::PromptBudget::from_env();
+            let (prompt, trimmed) = budget::fit_prompt(&mut report, &budget, |report| {
+                template.render(&format!("{}{}\n{}", week, calls, renderer::render_text(report)))
+            });
+            for part in &trimmed {
+                log::warn!("trimmed {} to fit the {} token prompt budget", part, budget.tokens);
+            }
+            prompt
+        }
+    };
+    log::info!("Prompt size: ~{} tokens", budget::estimate_tokens(&prompt));
+
+    let Some(providers) = providers else {
+        println!("\n=== {} ===\n", i18n::t("PROMPT"));
+        println!("{}", prompt);
+        println!("\n===============================");
+        return Ok(());
+    };
+    let analysis = ai_client::get_analysis(providers, &prompt, None, false).await?;
+    let mut text = format!("=== {} ===\n{}", kind.title(), analysis.text);
+    if let Some(result) = &analysis.result {
+        text.push_str(&format!("\n\n{}", renderer::render_analysis_result(result, currency::quote())));
+    }
+    if let Some(recommendation) = &analysis.recommendation {
+        text.push_str(&format!("\n\n{}", renderer::render_recommendation(recommendation, currency::quote())));
+    }
+    let delivery = output::Delivery { text: &text, symbol: "BTCUSDT", report: None, analysis: Some(&analysis), chart: None, candles: &[] };
+    outputs.send(&delivery).await
+}
+
+/// Bitcoin's candles, failing when there are none
+fn btc_candles(btc_data: &data_fetcher::CryptoData) -> Result<Vec<Candle>, Box<dyn Error>> {
+    let candles = btc_data.candles();
+    if candles.is_empty() {
+        return Err("No Bitcoin price data available".into());
+    }
+    Ok(candles)
+}
+
+/// A macro symbol, its display name and its daily closes
+type MacroCloses = (String, String, Vec<(DateTime<Utc>, f64)>);
+
+/// Daily closes of MACRO_SYMBOLS (comma-separated Yahoo Finance symbols) or the default set, all fetched at once,
+/// with each symbol's display name; a symbol that can't be fetched is skipped
+async fn fetch_macro_closes() -> Vec<MacroCloses> {
+    let symbols: Vec<(String, String)> = match env::var("MACRO_SYMBOLS") {
+        Ok(symbols) => symbols.split(',')
+            .map(str::trim)
+            .filter(|symbol| !symbol.is_empty())
+            .map(|symbol| {
+                let name = DEFAULT_MACRO_ASSETS.iter().find(|(known, _)| *known == symbol).map(|(_, name)| *name).unwrap_or(symbol);
+                (symbol.to_string(), name.to_string())
+            })
+            .collect(),
+        Err(_) => DEFAULT_MACRO_ASSETS.iter().map(|(symbol, name)| (symbol.to_string(), name.to_string())).collect(),
+    };
+
+    let fetched = futures::future::join_all(symbols.iter().map(|(symbol, _)| data_fetcher::fetch_daily_closes(symbol))).await;
+    symbols.into_iter().zip(fetched)
+        .filter_map(|((symbol, name), closes)| match closes {
+            Ok(closes) if !closes.is_empty() => Some((symbol, name, closes)),
+            Ok(_) => {
+                log::warn!("Skipping {}: no data", symbol);
+                None
+            }
+            Err(e) => {
+                log::warn!("Skipping {}: {}", symbol, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Each macro asset's changes and correlation with Bitcoin
+fn macro_assets(closes: Vec<MacroCloses>, candles: &[Candle]) -> Result<Vec<MacroAsset>, Box<dyn Error>> {
+    if closes.is_empty() {
+        return Err("No macro market data could be fetched".into());
+    }
+    let btc_daily = daily_closes(candles);
+    Ok(closes.into_iter().map(|(symbol, name, closes)| {
+        let daily: Vec<(NaiveDate, f64)> = closes.iter().map(|(time, close)| (time.date_naive(), *close)).collect();
+        MacroAsset {
+            last: closes[closes.len() - 1].1,
+            change_7d: change_since(&daily, 7),
+            change_30d: change_since(&daily, 30),
+            correlation: correlation(&btc_daily, &daily),
+            name,
+            symbol,
+        }
+    }).collect())
+}
+
+/// The last close of each UTC day, oldest first
+pub fn daily_closes(candles: &[Candle]) -> Vec<(NaiveDate, f64)> {
+    let mut daily: Vec<(NaiveDate, f64)> = Vec::new();
+    for candle in candles {
+        let Some(date) = DateTime::from_timestamp_millis(candle.timestamp as i64).map(|time| time.date_naive()) else { continue };
+        match daily.last_mut() {
+            Some((last, close)) if *last == date => *close = candle.close,
+            _ => daily.push((date, candle.close)),
+        }
+    }
+    daily
+}
+
+/// Percent change from the last close at least `days` before the latest one
+pub fn change_since(daily: &[(NaiveDate, f64)], days: i64) -> Option<f64> {
+    let (latest_date, latest) = *daily.last()?;
+    let (_, earlier) = daily.iter().rev().find(|(date, _)| *date <= latest_date - TimeDelta::days(days))?;
+    (*earlier != 0.0).then(|| (latest - earlier) / earlier * 100.0)
+}
+
+/// Pearson correlation of daily returns on the dates both series traded
+fn correlation(btc: &[(NaiveDate, f64)], other: &[(NaiveDate, f64)]) -> Option<f64> {
+    let btc: HashMap<NaiveDate, f64> = btc.iter().copied().collect();
+    let common: Vec<(f64, f64)> = other.iter()
+        .filter_map(|(date, close)| btc.get(date).map(|btc_close| (*btc_close, *close)))
+        .collect();
+    let returns: Vec<(f64, f64)> = common.windows(2)
+        .map(|pair| (pair[1].0 / pair[0].0 - 1.0, pair[1].1 / pair[0].1 - 1.0))
+        .collect();
+    let returns = &returns[returns.len().saturating_sub(CORRELATION_DAYS)..];
+    if returns.len() < 10 {
+        return None;
+    }
+
+    let n = returns.len() as f64;
+    let (mean_x, mean_y) = (returns.iter().map(|(x, _)| x).sum::<f64>() / n, returns.iter().map(|(_, y)| y).sum::<f64>() / n);
+    let covariance: f64 = returns.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance_x: f64 = returns.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let variance_y: f64 = returns.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    (variance_x > 0.0 && variance_y > 0.0).then(|| covariance / (variance_x * variance_y).sqrt())
+}
+
+/// Bitcoin's last seven days of candles, with the week before for comparison
+fn summarize_week(candles: &[Candle]) -> WeekSummary {
+    let last = candles[candles.len() - 1].timestamp;
+    let week_ms = TimeDelta::days(7).num_milliseconds() as f64;
+    let week: Vec<&Candle> = candles.iter().filter(|candle| candle.timestamp > last - week_ms).collect();
+    let previous: Vec<&Candle> = candles.iter()
+        .filter(|candle| candle.timestamp > last - 2.0 * week_ms && candle.timestamp <= last - week_ms)
+        .collect();
+
+    let open = week[0].open;
+    let close = week[week.len() - 1].close;
+    let week_candles: Vec<Candle> = week.iter().map(|candle| **candle).collect();
+    WeekSummary {
+        start: DateTime::from_timestamp_millis(week[0].timestamp as i64).unwrap_or_default(),
+        open,
+        close,
+        high: week.iter().map(|candle| candle.high).fold(f64::MIN, f64::max),
+        low: week.iter().map(|candle| candle.low).fold(f64::MAX, f64::min),
+        change_pct: if open != 0.0 { (close - open) / open * 100.0 } else { 0.0 },
+        volume: week.iter().map(|candle| candle.volume).sum(),
+        previous_volume: (!previous.is_empty()).then(|| previous.iter().map(|candle| candle.volume).sum()),
+        daily_closes: daily_closes(&week_candles),
+    }
+}
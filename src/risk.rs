@@ -0,0 +1,156 @@
+use std::env;
+use serde::Serialize;
+
+/// Account and risk settings for position sizing, read from RISK_* environment variables
+pub struct RiskConfig {
+    pub account_equity: Option<f64>,   // Position sizing is only done when set
+    pub risk_pct: f64,                 // Share of equity lost if the stop is hit
+    pub stop_atr: f64,                 // Stop distance in ATR multiples
+    pub target_r: f64,                 // Target distance in multiples of the stop distance
+    pub min_rr: f64,                   // Setups below this reward:risk ratio should not be recommended
+    pub kelly_win_rate: Option<f64>,   // Historical win rate (0-1) for the Kelly fraction
+    pub kelly_payoff: Option<f64>,     // Average win / average loss
+}
+
+impl RiskConfig {
+    pub fn from_env() -> Self {
+        let env_f64 = |name: &str| env::var(name).ok().and_then(|value| value.trim().parse::<f64>().ok());
+
+        RiskConfig {
+            account_equity: env_f64("RISK_ACCOUNT_EQUITY"),
+            risk_pct: env_f64("RISK_PER_TRADE_PCT").unwrap_or(1.0),
+            stop_atr: env_f64("RISK_STOP_ATR").unwrap_or(1.5),
+            target_r: env_f64("RISK_TARGET_R").unwrap_or(2.0),
+            min_rr: env_f64("RISK_MIN_RR").unwrap_or(2.0),
+            kelly_win_rate: env_f64("RISK_KELLY_WIN_RATE"),
+            kelly_payoff: env_f64("RISK_KELLY_PAYOFF"),
+        }
+    }
+}
+
+/// Concrete stop and target prices for one trade direction
+#[derive(Serialize)]
+pub struct ScenarioLevels {
+    pub stop_loss: f64,
+    pub take_profit: f64,
+}
+
+/// ATR-anchored stop/target levels for long and short entries at the current price
+#[derive(Serialize)]
+pub struct TradeLevels {
+    pub entry: f64,
+    pub atr: f64,
+    pub stop_atr: f64,
+    pub target_r: f64,
+    pub long: ScenarioLevels,
+    pub short: ScenarioLevels,
+    pub min_rr: f64,
+    pub setups: Vec<TradeSetup>,
+}
+
+/// Entry at market or at a support/resistance level, targeting the opposite level
+#[derive(Serialize)]
+pub struct TradeSetup {
+    pub name: String,
+    pub entry: f64,
+    pub stop_loss: f64,
+    pub take_profit: f64,
+    pub risk_reward: f64,
+    pub meets_minimum: bool,
+}
+
+/// Stop `stop_atr` ATRs from entry and target `target_r` times that distance on the other side,
+/// plus support/resistance setups with their risk:reward ratios
+pub fn trade_levels(config: &RiskConfig, entry: f64, atr: f64, support: f64, resistance: f64) -> TradeLevels {
+    let stop_distance = atr * config.stop_atr;
+    let target_distance = stop_distance * config.target_r;
+
+    TradeLevels {
+        entry,
+        atr,
+        stop_atr: config.stop_atr,
+        target_r: config.target_r,
+        long: ScenarioLevels {
+            stop_loss: entry - stop_distance,
+            take_profit: entry + target_distance,
+        },
+        short: ScenarioLevels {
+            stop_loss: entry + stop_distance,
+            take_profit: entry - target_distance,
+        },
+        min_rr: config.min_rr,
+        setups: [
+            ("Long at market", entry, entry - stop_distance, resistance),
+            ("Long at support", support, support - stop_distance, resistance),
+            ("Short at market", entry, entry + stop_distance, support),
+            ("Short at resistance", resistance, resistance + stop_distance, support),
+        ]
+        .into_iter()
+        .filter_map(|(name, entry, stop_loss, take_profit)| setup(name, entry, stop_loss, take_profit, config.min_rr))
+        .collect(),
+    }
+}
+
+/// A setup is only meaningful when the target lies beyond the entry in the trade's direction
+fn setup(name: &str, entry: f64, stop_loss: f64, take_profit: f64, min_rr: f64) -> Option<TradeSetup> {
+    let risk = (entry - stop_loss).abs();
+    let reward = if stop_loss < entry { take_profit - entry } else { entry - take_profit };
+    if risk <= 0.0 || reward <= 0.0 {
+        return None;
+    }
+
+    let risk_reward = reward / risk;
+    Some(TradeSetup {
+        name: name.to_string(),
+        entry,
+        stop_loss,
+        take_profit,
+        risk_reward,
+        meets_minimum: risk_reward >= min_rr,
+    })
+}
+
+/// How much to buy or sell so a stop-out loses exactly the risk budget
+#[derive(Serialize)]
+pub struct PositionSizing {
+    pub account_equity: f64,
+    pub risk_pct: f64,
+    pub risk_amount: f64,
+    pub entry: f64,
+    pub stop_distance: f64,
+    pub position_size: f64,            // Units of the asset
+    pub position_value: f64,
+    pub capped: bool,                  // Size was limited to the account equity (no leverage)
+    pub kelly_fraction: Option<f64>,
+    pub kelly_position_value: Option<f64>,
+}
+
+/// Size a position from the risk budget and an ATR-based stop distance, if an account is configured
+pub fn size_position(config: &RiskConfig, entry: f64, atr: f64) -> Option<PositionSizing> {
+    let account_equity = config.account_equity?;
+    let risk_amount = account_equity * config.risk_pct / 100.0;
+    let stop_distance = atr * config.stop_atr;
+
+    let uncapped_size = if stop_distance > 0.0 { risk_amount / stop_distance } else { 0.0 };
+    let max_size = account_equity / entry;
+    let position_size = uncapped_size.min(max_size);
+
+    // Kelly: f = W - (1 - W) / R, never negative and never more than the whole account
+    let kelly_fraction = match (config.kelly_win_rate, config.kelly_payoff) {
+        (Some(win_rate), Some(payoff)) if payoff > 0.0 => Some((win_rate - (1.0 - win_rate) / payoff).clamp(0.0, 1.0)),
+        _ => None,
+    };
+
+    Some(PositionSizing {
+        account_equity,
+        risk_pct: config.risk_pct,
+        risk_amount,
+        entry,
+        stop_distance,
+        position_size,
+        position_value: position_size * entry,
+        capped: uncapped_size > max_size,
+        kelly_fraction,
+        kelly_position_value: kelly_fraction.map(|fraction| fraction * account_equity),
+    })
+}
@@ -0,0 +1,155 @@
+use std::env;
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Local};
+use cron::Schedule;
+use rand::Rng;
+use tokio::task::{AbortHandle, JoinSet};
+use crate::config::{self, ScheduledRun};
+
+/// How long running analyses may take to finish once the scheduler is asked to stop
+const DEFAULT_SHUTDOWN_SECONDS: u64 = 300;
+
+/// A scheduled profile and its next start
+struct Job {
+    run: ScheduledRun,
+    schedule: Schedule,
+    due: DateTime<Local>,       // The next time the expression matches
+    start: DateTime<Local>,     // `due` plus the jitter
+    running: Option<AbortHandle>,
+}
+
+impl Job {
+    /// Move to the expression's next time after `due`; false when there is none
+    fn advance(&mut self, jitter: u64) -> bool {
+        let Some(due) = self.schedule.after(&self.due).next() else { return false };
+        let delay = if jitter > 0 { rand::thread_rng().gen_range(0..=jitter) } else { 0 };
+        self.due = due;
+        self.start = due + chrono::Duration::seconds(delay as i64);
+        true
+    }
+}
+
+/// Run each profile in crypto-forecast.toml that has a `schedule` (or only those named) as its own process of this
+/// program whenever its cron expression matches, until Ctrl-C or SIGTERM. Starts are delayed by up to
+/// SCHEDULE_JITTER_SECONDS (default 0), a profile whose previous run is still going is skipped, and on shutdown the
+/// running analyses get SCHEDULE_SHUTDOWN_SECONDS (default 300) to finish before they are stopped.
+/// `exported` lists the variables this process took from the configuration file, which the runs read for themselves.
+pub async fn run_schedule(only: &[String], exported: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut runs = config::scheduled_runs()?;
+    if let Some(name) = only.iter().find(|name| !runs.iter().any(|run| &&run.profile == name)) {
+        return Err(format!("Profile '{}' has no schedule in the configuration file", name).into());
+    }
+    runs.retain(|run| only.is_empty() || only.contains(&run.profile));
+    if runs.is_empty() {
+        return Err("No profile in the configuration file has a schedule".into());
+    }
+
+    let jitter = env_u64("SCHEDULE_JITTER_SECONDS", 0);
+    let grace = Duration::from_secs(env_u64("SCHEDULE_SHUTDOWN_SECONDS", DEFAULT_SHUTDOWN_SECONDS));
+    let now = Local::now();
+    let mut jobs = Vec::new();
+    for run in runs {
+        let schedule = parse_schedule(&run.schedule)
+            .map_err(|e| format!("Invalid schedule '{}' for profile {}: {}", run.schedule, run.profile, e))?;
+        let mut job = Job { run, schedule, due: now, start: now, running: None };
+        if job.advance(jitter) {
+            log::info!("Scheduled profile {} ({}), next run at {}", job.run.profile, job.run.schedule, job.start.format("%Y-%m-%d %H:%M:%S"));
+            jobs.push(job);
+        } else {
+            log::warn!("Schedule '{}' for profile {} never matches", job.run.schedule, job.run.profile);
+        }
+    }
+
+    let exported = Arc::new(exported.to_vec());
+    let mut running = JoinSet::new();
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    while let Some(job) = jobs.iter_mut().min_by_key(|job| job.start) {
+        let wait = (job.start - Local::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            result = &mut shutdown => {
+                result?;
+                log::info!("Stopping the scheduler");
+                break;
+            }
+            // Collect finished runs so the set doesn't grow
+            Some(_) = running.join_next(), if !running.is_empty() => continue,
+            _ = tokio::time::sleep(wait) => {}
+        }
+
+        if job.running.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            log::warn!("Skipping profile {}: its previous run is still going", job.run.profile);
+        } else {
+            job.running = Some(running.spawn(execute(job.run.clone(), exported.clone())));
+        }
+        if !job.advance(jitter) {
+            let profile = job.run.profile.clone();
+            jobs.retain(|job| job.run.profile != profile);
+        }
+    }
+
+    if !running.is_empty() {
+        log::info!("Waiting up to {}s for {} running analyses to finish", grace.as_secs(), running.len());
+        if tokio::time::timeout(grace, async { while running.join_next().await.is_some() {} }).await.is_err() {
+            log::warn!("Stopping the analyses still running after {}s", grace.as_secs());
+            running.shutdown().await;
+        }
+    }
+    log::info!("Scheduler stopped");
+    Ok(())
+}
+
+/// Run the profile in a new process of this program and wait for it
+async fn execute(run: ScheduledRun, exported: Arc<Vec<String>>) {
+    let exe = match env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return log::warn!("Could not start profile {}: {}", run.profile, e),
+    };
+    let mut command = tokio::process::Command::new(exe);
+    // The run loads the configuration file again with its profile, which the base settings exported here would shadow
+    for name in exported.iter() {
+        command.env_remove(name);
+    }
+    command.arg("--profile").arg(&run.profile).args(&run.args).kill_on_drop(true);
+    // In a process group of its own the run doesn't receive the Ctrl-C meant for the scheduler, so it can finish
+    #[cfg(unix)]
+    command.process_group(0);
+
+    log::info!("Starting profile {}", run.profile);
+    match command.status().await {
+        Ok(status) if status.success() => log::info!("Profile {} finished", run.profile),
+        Ok(status) => log::warn!("Profile {} exited with {}", run.profile, status),
+        Err(e) => log::warn!("Profile {} failed to run: {}", run.profile, e),
+    }
+}
+
+/// Parse a cron expression; the usual five fields are accepted as well as the cron crate's form with seconds first
+fn parse_schedule(expression: &str) -> Result<Schedule, cron::error::Error> {
+    if expression.split_whitespace().count() == 5 {
+        Schedule::from_str(&format!("0 {}", expression))
+    } else {
+        Schedule::from_str(expression)
+    }
+}
+
+/// Resolve on Ctrl-C, or SIGTERM where there is one
+async fn shutdown_signal() -> Result<(), Box<dyn Error>> {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    env::var(name).ok().and_then(|value| value.trim().parse().ok()).unwrap_or(default)
+}
@@ -0,0 +1,104 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, IsTerminal};
+use keyring::Entry;
+use crate::cli::SecretsCommand;
+
+/// Name the keys are stored under in the OS keyring
+const KEYRING_SERVICE: &str = "crypto-forecast";
+
+/// Variables holding credentials, which can also be read from a file or the OS keyring
+pub const SECRETS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "OPENAI_API_KEY",
+    "OPENAI_COMPATIBLE_API_KEY",
+    "GEMINI_API_KEY",
+    "AZURE_OPENAI_API_KEY",
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+    "AWS_SESSION_TOKEN",
+    "DATA_PROVIDER_API_KEY",
+    "BINANCE_API_KEY",
+    "BINANCE_API_SECRET",
+    "TELEGRAM_API_KEY",
+    "DISCORD_BOT_TOKEN",
+    "WEBHOOK_SECRET",
+    "NOTION_TOKEN",
+    "GITHUB_TOKEN",
+    "PUSHOVER_TOKEN",
+    "MASTODON_TOKEN",
+    "X_ACCESS_TOKEN",
+    "MQTT_PASSWORD",
+    "TRIGGER_TOKEN",
+];
+
+/// Fill in the credentials the environment leaves unset: from the file named by `<NAME>_FILE` (as Docker secrets are
+/// mounted), then, with SECRETS_KEYRING=true, from the OS keyring. Returns the names of the variables that were set.
+pub fn apply() -> Result<Vec<String>, Box<dyn Error>> {
+    let keyring = env::var("SECRETS_KEYRING").map(|value| value == "true").unwrap_or(false);
+    let mut loaded = Vec::new();
+    for name in SECRETS {
+        if env::var_os(name).is_some() {
+            continue;
+        }
+        let value = match env::var(format!("{}_FILE", name)) {
+            Ok(path) => {
+                let contents = fs::read_to_string(&path).map_err(|e| format!("Could not read {}_FILE {}: {}", name, path, e))?;
+                Some(contents.trim_end_matches(['\n', '\r']).to_string())
+            }
+            Err(_) if keyring => from_keyring(name)?,
+            Err(_) => None,
+        };
+        if let Some(value) = value {
+            // SAFETY: runs at startup, before any task is spawned that could read the environment
+            unsafe { env::set_var(name, &value) };
+            loaded.push(name.to_string());
+        }
+    }
+    if !loaded.is_empty() {
+        log::debug!("Loaded {} from files or the keyring", loaded.join(", "));
+    }
+    Ok(loaded)
+}
+
+/// The secret stored in the OS keyring, if there is one
+fn from_keyring(name: &str) -> Result<Option<String>, Box<dyn Error>> {
+    match Entry::new(KEYRING_SERVICE, name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Could not read {} from the OS keyring: {}", name, e).into()),
+    }
+}
+
+/// Store a secret in the OS keyring, read from stdin, or remove it
+pub fn run_command(command: &SecretsCommand) -> Result<(), Box<dyn Error>> {
+    let name = match command {
+        SecretsCommand::Set { name } | SecretsCommand::Delete { name } => name.as_str(),
+    };
+    let entry = Entry::new(KEYRING_SERVICE, name)?;
+
+    match command {
+        SecretsCommand::Set { .. } => {
+            if io::stdin().is_terminal() {
+                eprint!("{}: ", name);
+            }
+            let mut value = String::new();
+            io::stdin().read_line(&mut value)?;
+            let value = value.trim_end_matches(['\n', '\r']);
+            if value.is_empty() {
+                return Err(format!("No value given for {}", name).into());
+            }
+            entry.set_password(value).map_err(|e| format!("Could not store {} in the OS keyring: {}", name, e))?;
+            log::info!("Stored {} in the OS keyring; set SECRETS_KEYRING=true to use it", name);
+        }
+        SecretsCommand::Delete { .. } => {
+            match entry.delete_credential() {
+                Ok(()) => log::info!("Removed {} from the OS keyring", name),
+                Err(keyring::Error::NoEntry) => log::info!("{} is not stored in the OS keyring", name),
+                Err(e) => return Err(format!("Could not remove {} from the OS keyring: {}", name, e).into()),
+            }
+        }
+    }
+    Ok(())
+}
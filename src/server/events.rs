@@ -0,0 +1,96 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use super::{ApiError, Server};
+
+/// Events kept for subscribers that fall behind; older ones are skipped
+const EVENT_BUFFER: usize = 256;
+
+/// Something that happened to a triggered analysis
+#[derive(Clone, Serialize)]
+pub struct RunEvent {
+    pub id: u64,
+    pub symbol: String,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum EventKind {
+    Queued,
+    Started,
+    Progress { message: String },       // A progress line of the run, or streamed model output
+    Result { report: serde_json::Value }, // The report as the json output writes it
+    Finished,
+    Failed { error: String },
+}
+
+impl EventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::Queued => "queued",
+            EventKind::Started => "started",
+            EventKind::Progress { .. } => "progress",
+            EventKind::Result { .. } => "result",
+            EventKind::Finished => "finished",
+            EventKind::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// Broadcasts run events to every connected dashboard
+#[derive(Clone)]
+pub struct Events {
+    sender: broadcast::Sender<RunEvent>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        Events { sender: broadcast::channel(EVENT_BUFFER).0 }
+    }
+
+    pub fn send(&self, id: u64, symbol: &str, kind: EventKind) {
+        // Nobody listening is fine
+        let _ = self.sender.send(RunEvent { id, symbol: symbol.to_string(), kind });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<RunEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    token: Option<String>,      // EventSource can't set headers
+}
+
+/// Stream the events of triggered analyses as server-sent events, each named after its kind with the event as JSON data
+pub async fn events(State(server): State<Arc<Server>>, headers: HeaderMap, Query(query): Query<EventsQuery>)
+                    -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let queue = server.triggers.as_ref()
+        .ok_or((StatusCode::NOT_FOUND, "Set TRIGGER_TOKEN to enable /events".to_string()))?;
+    if !queue.authorized(&headers, query.token.as_deref()) {
+        return Err((StatusCode::UNAUTHORIZED, "Missing or wrong token".to_string()));
+    }
+
+    let receiver = server.events.subscribe();
+    let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let sse = Event::default().event(event.kind.name()).json_data(&event).unwrap_or_default();
+                    return Some((Ok(sse), receiver));
+                }
+                Err(RecvError::Lagged(skipped)) => log::warn!("An event subscriber fell behind and missed {} events", skipped),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
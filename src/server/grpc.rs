@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use axum::http::StatusCode;
+use futures::Stream;
+use tokio::sync::broadcast::error::RecvError;
+use tonic::{Code, Request, Response, Status};
+use crate::renderer;
+use crate::signals;
+use crate::strategy;
+use super::events::EventKind;
+use super::health;
+use super::trigger::TriggerQueue;
+use super::{ApiError, Server};
+
+/// Code generated from proto/crypto_forecast.proto
+pub mod proto {
+    tonic::include_proto!("crypto_forecast.v1");
+}
+
+use proto::forecast_server::{Forecast, ForecastServer};
+
+/// Serve the Forecast service on its own address until the server stops
+pub async fn run_grpc(addr: SocketAddr, server: Arc<Server>) -> Result<(), Box<dyn Error>> {
+    log::info!("Serving gRPC on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(ForecastServer::new(Service { server }))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+struct Service {
+    server: Arc<Server>,
+}
+
+impl Service {
+    /// The same token check as over HTTP, with the token in the `authorization: Bearer` metadata
+    #[allow(clippy::result_large_err)] // Status is what every tonic handler returns anyway
+    fn authorize<T>(&self, request: &Request<T>, method: &str) -> Result<&TriggerQueue, Status> {
+        let queue = self.server.triggers.as_ref()
+            .ok_or_else(|| Status::unimplemented(format!("Set TRIGGER_TOKEN to enable {}", method)))?;
+        if !queue.authorized(&request.metadata().clone().into_headers(), None) {
+            return Err(Status::unauthenticated("Missing or wrong token"));
+        }
+        Ok(queue)
+    }
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<proto::RunEvent, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Forecast for Service {
+    async fn get_report(&self, request: Request<proto::MarketRequest>) -> Result<Response<proto::Report>, Status> {
+        let report = super::build_report(&self.server, request.get_ref().symbol.as_deref()).await.map_err(status)?;
+        let json = renderer::render_json(&report).map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(proto::Report {
+            generated_at: report.generated_at.to_rfc3339(),
+            price: report.last_price(),
+            composite_score: report.composite.score,
+            composite_label: report.composite.label().to_string(),
+            support: report.levels.support,
+            resistance: report.levels.resistance,
+            json,
+        }))
+    }
+
+    async fn get_signal(&self, request: Request<proto::MarketRequest>) -> Result<Response<proto::Signal>, Status> {
+        let report = super::build_report(&self.server, request.get_ref().symbol.as_deref()).await.map_err(status)?;
+        let strategies = strategy::load_strategies().map_err(|e| Status::internal(e.to_string()))?;
+        let signal = signals::generate_signal(&report, &signals::SignalRules::from_env(), &strategies);
+        Ok(Response::new(proto::Signal {
+            action: signal.action.as_str().to_string(),
+            score: signal.score,
+            entry: signal.entry,
+            stop_loss: signal.stop_loss,
+            take_profit: signal.take_profit,
+            reasons: signal.reasons,
+            strategies: signal.strategies.into_iter().map(|result| proto::StrategyResult {
+                name: result.name,
+                action: result.action.as_str().to_string(),
+                condition: result.condition,
+                triggered: result.triggered,
+            }).collect(),
+        }))
+    }
+
+    /// Answers even when a check fails, with `ready` false, so the caller sees which one
+    async fn ready(&self, _: Request<proto::ReadyRequest>) -> Result<Response<proto::Readiness>, Status> {
+        let readiness = health::readiness(&self.server).await;
+        Ok(Response::new(proto::Readiness {
+            ready: readiness.ready,
+            checks: readiness.checks.into_iter()
+                .map(|check| proto::Check { name: check.name, ok: check.ok, error: check.error })
+                .collect(),
+        }))
+    }
+
+    async fn trigger(&self, request: Request<proto::TriggerRequest>) -> Result<Response<proto::Queued>, Status> {
+        let queue = self.authorize(&request, "Trigger")?;
+        let body = request.get_ref();
+        let trigger = queue.enqueue(&self.server.events, body.symbol.as_deref(), body.interval.as_deref()).map_err(status)?;
+        Ok(Response::new(proto::Queued {
+            id: trigger.id,
+            symbol: trigger.symbol,
+            interval: trigger.interval.map(str::to_string),
+        }))
+    }
+
+    type EventsStream = EventStream;
+
+    async fn events(&self, request: Request<proto::EventsRequest>) -> Result<Response<EventStream>, Status> {
+        self.authorize(&request, "Events")?;
+        let receiver = self.server.events.subscribe();
+        let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let mut message = proto::RunEvent {
+                            id: event.id,
+                            symbol: event.symbol,
+                            event: event.kind.name().to_string(),
+                            ..Default::default()
+                        };
+                        match event.kind {
+                            EventKind::Progress { message: line } => message.message = Some(line),
+                            EventKind::Result { report } => message.report = Some(report.to_string()),
+                            EventKind::Failed { error } => message.error = Some(error),
+                            _ => {}
+                        }
+                        return Some((Ok(message), receiver));
+                    }
+                    Err(RecvError::Lagged(skipped)) => log::warn!("An event subscriber fell behind and missed {} events", skipped),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// The gRPC status closest to the HTTP one
+fn status((code, message): ApiError) -> Status {
+    let code = match code {
+        StatusCode::BAD_REQUEST => Code::InvalidArgument,
+        StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+        StatusCode::NOT_FOUND => Code::Unimplemented,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+        _ => Code::Internal,
+    };
+    Status::new(code, message)
+}
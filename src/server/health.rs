@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+use crate::ai_client;
+use crate::output::Outputs;
+use super::Server;
+
+/// Limit for reaching the market data API in the readiness check
+const READY_TIMEOUT_SECS: u64 = 10;
+
+/// One dependency in the readiness check
+#[derive(Serialize)]
+pub struct Check {
+    pub name: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Check {
+    fn new(name: &str, result: Result<(), impl std::fmt::Display>) -> Self {
+        Check { name: name.to_string(), ok: result.is_ok(), error: result.err().map(|e| e.to_string()) }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: Vec<Check>,
+}
+
+pub async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// 200 when the market data API answers, the AI_PROVIDER backends are configured and accept their credentials,
+/// and the outputs are configured; otherwise 503. Either way the body lists each check.
+pub async fn readyz(State(server): State<Arc<Server>>) -> impl IntoResponse {
+    let readiness = readiness(&server).await;
+    let status = if readiness.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(readiness))
+}
+
+/// Run every readiness check, logging the ones that fail
+pub async fn readiness(server: &Server) -> Readiness {
+    let mut checks = vec![Check::new("market-data", ping_market_data(&server.api_base_url).await)];
+    // Errors are kept as messages, since they can't be held across the checks
+    match ai_client::providers_from_env().map_err(|e| e.to_string()) {
+        Ok(providers) => {
+            let results = futures::future::join_all(providers.iter()
+                .map(|provider| async move { provider.check().await.map_err(|e| e.to_string()) })).await;
+            checks.extend(providers.iter().zip(results).map(|(provider, result)| Check::new(provider.name(), result)));
+        }
+        Err(e) => checks.push(Check::new("ai-provider", Err(e))),
+    }
+    checks.push(Check::new("outputs", Outputs::load(None).map(|_| ())));
+
+    let ready = checks.iter().all(|check| check.ok);
+    for check in checks.iter().filter(|check| !check.ok) {
+        log::warn!("Readiness check {} failed: {}", check.name, check.error.as_deref().unwrap_or_default());
+    }
+    Readiness { ready, checks }
+}
+
+/// Binance's connectivity test endpoint
+async fn ping_market_data(api_base_url: &str) -> Result<(), Box<dyn Error>> {
+    let response = reqwest::Client::new()
+        .get(format!("{}/api/v3/ping", api_base_url))
+        .timeout(Duration::from_secs(READY_TIMEOUT_SECS))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("API request failed with status: {}", response.status()).into());
+    }
+    Ok(())
+}
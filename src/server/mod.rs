@@ -0,0 +1,101 @@
+mod events;
+mod grpc;
+mod health;
+mod trigger;
+
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::Router;
+use serde::Deserialize;
+use crate::data_fetcher;
+use crate::renderer;
+use crate::report::TechnicalReport;
+use crate::signals;
+use crate::strategy;
+use crate::technical_analysis;
+use events::Events;
+use trigger::TriggerQueue;
+
+/// Settings every request needs
+struct Server {
+    data_provider_api_key: String,
+    api_base_url: String,
+    triggers: Option<TriggerQueue>,
+    events: Events,
+}
+
+#[derive(Deserialize)]
+struct MarketQuery {
+    symbol: Option<String>,
+}
+
+/// A failed request: the status and a message for the caller
+type ApiError = (StatusCode, String);
+
+/// Serve the technical report (GET /report) and the rule-based signal (GET /signal) as JSON, each built from fresh
+/// data on every request; `?symbol=ETHUSDT` selects another market. GET /healthz answers while the server is
+/// running and GET /readyz while the market data API and the AI backends are reachable, for container health checks.
+/// With TRIGGER_TOKEN set, POST /trigger queues a full analysis for the configured outputs (e.g. from TradingView alerts)
+/// and GET /events streams the progress and results of those analyses as server-sent events.
+/// With `grpc_addr`, the same endpoints are also served there as the gRPC service in proto/crypto_forecast.proto.
+pub async fn run_server(addr: SocketAddr, grpc_addr: Option<SocketAddr>, data_provider_api_key: &str, api_base_url: &str) -> Result<(), Box<dyn Error>> {
+    let events = Events::new();
+    let server = Arc::new(Server {
+        data_provider_api_key: data_provider_api_key.to_string(),
+        api_base_url: api_base_url.to_string(),
+        triggers: TriggerQueue::from_env(events.clone()),
+        events,
+    });
+    let app = Router::new()
+        .route("/report", get(report))
+        .route("/signal", get(signal))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/trigger", post(trigger::trigger))
+        .route("/events", get(events::events))
+        .with_state(server.clone());
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    log::info!("Serving on http://{}", listener.local_addr()?);
+    let http = async move { axum::serve(listener, app).await.map_err(Box::<dyn Error>::from) };
+    match grpc_addr {
+        Some(grpc_addr) => tokio::try_join!(http, grpc::run_grpc(grpc_addr, server)).map(|_| ()),
+        None => http.await,
+    }
+}
+
+async fn report(State(server): State<Arc<Server>>, Query(query): Query<MarketQuery>) -> Result<impl IntoResponse, ApiError> {
+    let report = build_report(&server, query.symbol.as_deref()).await?;
+    let json = renderer::render_json(&report).map_err(internal)?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], json))
+}
+
+async fn signal(State(server): State<Arc<Server>>, Query(query): Query<MarketQuery>) -> Result<impl IntoResponse, ApiError> {
+    let report = build_report(&server, query.symbol.as_deref()).await?;
+    let strategies = strategy::load_strategies().map_err(internal)?;
+    let signal = signals::generate_signal(&report, &signals::SignalRules::from_env(), &strategies);
+    let json = serde_json::to_string_pretty(&signal).map_err(internal)?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], json))
+}
+
+async fn build_report(server: &Server, symbol: Option<&str>) -> Result<TechnicalReport, ApiError> {
+    let symbol = symbol.unwrap_or("BTCUSDT").to_uppercase();
+    // The error becomes the response before the other fetch is awaited, as handlers can't hold it
+    let (data, fear_and_greed_data) = tokio::join!(
+        async {
+            data_fetcher::fetch_trading_data(&server.data_provider_api_key, &server.api_base_url, &symbol).await
+                .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Could not fetch {} data: {}", symbol, e)))
+        },
+        data_fetcher::load_fear_greed(),
+    );
+    Ok(technical_analysis::build_technical_report(&data?, &fear_and_greed_data))
+}
+
+fn internal(e: impl std::fmt::Display) -> ApiError {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
@@ -0,0 +1,167 @@
+use std::env;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::sync::mpsc;
+use crate::cli;
+use crate::data_fetcher::INTERVALS;
+use crate::output::Outputs;
+use super::events::{EventKind, Events};
+use super::{ApiError, Server};
+
+/// Analyses that can wait to run; further triggers are refused while the queue is full
+const QUEUE_SIZE: usize = 16;
+
+/// An analysis waiting to run
+#[derive(Clone, Serialize)]
+pub struct Trigger {
+    pub id: u64,                // Identifies the run in /events
+    pub symbol: String,
+    pub interval: Option<&'static str>,
+}
+
+/// Body of POST /trigger. TradingView sends alert messages as text, so the body is parsed as JSON whatever its content type.
+#[derive(Deserialize)]
+struct TriggerBody {
+    symbol: Option<String>,
+    #[serde(alias = "timeframe")]
+    interval: Option<String>,
+    token: Option<String>,      // For senders that can't set an Authorization header
+}
+
+/// Analyses requested over HTTP, run one at a time
+pub struct TriggerQueue {
+    token: String,
+    sender: mpsc::Sender<Trigger>,
+    next_id: AtomicU64,
+}
+
+impl TriggerQueue {
+    /// Start running triggered analyses when TRIGGER_TOKEN is set
+    pub fn from_env(events: Events) -> Option<Self> {
+        let token = env::var("TRIGGER_TOKEN").ok().filter(|token| !token.is_empty())?;
+        let (sender, receiver) = mpsc::channel(QUEUE_SIZE);
+        tokio::spawn(run_queue(receiver, events));
+        Some(TriggerQueue { token, sender, next_id: AtomicU64::new(1) })
+    }
+
+    /// Whether `Authorization: Bearer <token>` or the request itself carries the token, compared in constant time
+    pub fn authorized(&self, headers: &HeaderMap, token: Option<&str>) -> bool {
+        let given = headers.get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or(token)
+            .unwrap_or_default();
+        given.len() == self.token.len() && given.bytes().zip(self.token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+
+    /// Queue an analysis of the symbol (default BTCUSDT) and interval, as given by the caller
+    pub fn enqueue(&self, events: &Events, symbol: Option<&str>, interval: Option<&str>) -> Result<Trigger, ApiError> {
+        // TradingView's {{ticker}} may carry the exchange, as in BINANCE:BTCUSDT
+        let symbol = symbol.unwrap_or("BTCUSDT");
+        let symbol = cli::parse_symbol(symbol.rsplit(':').next().unwrap_or(symbol))
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid symbol: {}", e)))?;
+        let interval = match interval {
+            Some(interval) => Some(parse_interval(interval).ok_or_else(|| (StatusCode::BAD_REQUEST,
+                format!("Unknown interval '{}', expected one of {}", interval, INTERVALS.map(|(name, _)| name).join(", "))))?),
+            None => None,
+        };
+
+        let trigger = Trigger { id: self.next_id.fetch_add(1, Ordering::Relaxed), symbol, interval };
+        self.sender.try_send(trigger.clone())
+            .map_err(|_| (StatusCode::SERVICE_UNAVAILABLE, "Too many analyses are waiting, try again later".to_string()))?;
+        events.send(trigger.id, &trigger.symbol, EventKind::Queued);
+        Ok(trigger)
+    }
+}
+
+/// Queue a full analysis of the posted symbol (default BTCUSDT) and interval, delivered to the configured outputs
+pub async fn trigger(State(server): State<Arc<Server>>, headers: HeaderMap, body: String) -> Result<impl IntoResponse, ApiError> {
+    let queue = server.triggers.as_ref()
+        .ok_or((StatusCode::NOT_FOUND, "Set TRIGGER_TOKEN to enable /trigger".to_string()))?;
+    let body: TriggerBody = serde_json::from_str(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Expected a JSON body with symbol and interval: {}", e)))?;
+    if !queue.authorized(&headers, body.token.as_deref()) {
+        return Err((StatusCode::UNAUTHORIZED, "Missing or wrong token".to_string()));
+    }
+
+    let trigger = queue.enqueue(&server.events, body.symbol.as_deref(), body.interval.as_deref())?;
+    Ok((StatusCode::ACCEPTED, Json(json!({ "queued": trigger }))))
+}
+
+/// An interval by name (4h) or as TradingView writes it: minutes (240) or days (D, 1D)
+fn parse_interval(value: &str) -> Option<&'static str> {
+    let value = value.trim().to_lowercase();
+    let minutes = match value.as_str() {
+        "d" | "1d" => Some(1440),
+        _ => value.parse().ok(),
+    };
+    INTERVALS.iter()
+        .find(|(name, length)| *name == value || Some(*length) == minutes)
+        .map(|(name, _)| *name)
+}
+
+/// Run each queued analysis in a new process of this program, so intervals don't mix and outputs.toml applies as usual
+async fn run_queue(mut receiver: mpsc::Receiver<Trigger>, events: Events) {
+    while let Some(trigger) = receiver.recv().await {
+        events.send(trigger.id, &trigger.symbol, EventKind::Started);
+        let kind = match run_trigger(&trigger, &events).await {
+            Ok(()) => EventKind::Finished,
+            Err(error) => {
+                log::warn!("Triggered {} analysis failed: {}", trigger.symbol, error);
+                EventKind::Failed { error }
+            }
+        };
+        events.send(trigger.id, &trigger.symbol, kind);
+    }
+}
+
+/// Run the analysis, passing on its log lines as progress and its JSON report as the result
+async fn run_trigger(trigger: &Trigger, events: &Events) -> Result<(), String> {
+    // The run prints the report as JSON for the result event; the terminal output is left out, as nobody reads it
+    let outputs = Outputs::load(None).map_err(|e| e.to_string())?;
+    let mut names: Vec<&str> = outputs.names().into_iter().filter(|name| !["text", "json"].contains(name)).collect();
+    names.push("json");
+
+    let exe = env::current_exe().map_err(|e| format!("Could not start the analysis: {}", e))?;
+    let mut command = tokio::process::Command::new(exe);
+    command.arg("--symbol").arg(&trigger.symbol);
+    if let Some(interval) = trigger.interval {
+        command.arg("--interval").arg(interval);
+    }
+    command.arg(names.join(",")).stdout(Stdio::piped()).stderr(Stdio::piped()).kill_on_drop(true);
+
+    log::info!("Running the triggered {} analysis", trigger.symbol);
+    let mut child = command.spawn().map_err(|e| format!("Could not start the analysis: {}", e))?;
+    let (Some(mut stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err("The analysis output is not available".to_string());
+    };
+    let progress = async {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            log::info!("{}", line);
+            events.send(trigger.id, &trigger.symbol, EventKind::Progress { message: line });
+        }
+    };
+    let report = async {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).await.map(|_| output)
+    };
+    let (_, report, status) = tokio::join!(progress, report, child.wait());
+
+    if let Ok(report) = serde_json::from_str(&report.map_err(|e| e.to_string())?) {
+        events.send(trigger.id, &trigger.symbol, EventKind::Result { report });
+    }
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("exited with {}", status)),
+        Err(e) => Err(e.to_string()),
+    }
+}
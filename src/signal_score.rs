@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use serde::Serialize;
+use std::env;
+use crate::data_fetcher::FearGreedData;
+use crate::indicators::IndicatorRegistry;
+
+/// Default weight of each indicator in the composite score
+const DEFAULT_WEIGHTS: [(&str, f64); 7] = [
+    ("sma", 1.0),
+    ("ema", 1.0),
+    ("rsi", 1.0),
+    ("macd", 1.5),
+    ("bollinger", 0.75),
+    ("obv", 0.75),
+    ("fear_greed", 0.5),
+];
+
+/// A single indicator's contribution to the composite score
+#[derive(Serialize)]
+pub struct Contribution {
+    pub indicator: String,
+    pub signal: f64,       // -1.0 (fully bearish) to +1.0 (fully bullish)
+    pub weight: f64,
+    pub reason: String,
+}
+
+/// Weighted aggregate of all indicator states on a -100..+100 scale
+#[derive(Serialize)]
+pub struct CompositeScore {
+    pub score: f64,
+    pub contributions: Vec<Contribution>,
+}
+
+impl CompositeScore {
+    /// Human-readable classification of the score
+    pub fn label(&self) -> &'static str {
+        if self.score >= 50.0 {
+            "Strong Bullish"
+        } else if self.score >= 15.0 {
+            "Bullish"
+        } else if self.score > -15.0 {
+            "Neutral"
+        } else if self.score > -50.0 {
+            "Bearish"
+        } else {
+            "Strong Bearish"
+        }
+    }
+}
+
+/// Load indicator weights, applying overrides from SIGNAL_WEIGHTS (e.g. "rsi=2,macd=1.5,obv=0")
+pub fn load_weights() -> HashMap<String, f64> {
+    let mut weights: HashMap<String, f64> = DEFAULT_WEIGHTS.iter()
+        .map(|(name, weight)| (name.to_string(), *weight))
+        .collect();
+
+    if let Ok(overrides) = env::var("SIGNAL_WEIGHTS") {
+        for pair in overrides.split(',') {
+            if let Some((name, value)) = pair.split_once('=') {
+                match value.trim().parse::<f64>() {
+                    Ok(weight) => { weights.insert(name.trim().to_lowercase(), weight); },
+                    Err(_) => log::warn!("Ignoring invalid signal weight: {}", pair),
+                }
+            }
+        }
+    }
+
+    weights
+}
+
+/// Calculate the composite signal score from the latest indicator readings
+pub fn calculate_composite_score(registry: &IndicatorRegistry, fng: &[FearGreedData], weights: &HashMap<String, f64>) -> CompositeScore {
+    let mut signals: Vec<(String, f64, String)> = registry.ready()
+        .filter_map(|indicator| indicator.interpret()
+            .map(|interpretation| (indicator.key().to_string(), interpretation.signal, interpretation.reason)))
+        .collect();
+
+    // Fear & Greed is read contrarian: extreme fear is a buying opportunity
+    if let Some(latest) = fng.first()
+        && let Ok(value) = latest.value.parse::<f64>() {
+        signals.push(("fear_greed".to_string(), (50.0 - value) / 50.0,
+            format!("{} ({})", latest.value_classification, latest.value)));
+    }
+
+    let contributions: Vec<Contribution> = signals.into_iter()
+        .map(|(indicator, signal, reason)| Contribution {
+            weight: *weights.get(&indicator).unwrap_or(&1.0),
+            indicator,
+            signal: signal.clamp(-1.0, 1.0),
+            reason,
+        })
+        .collect();
+
+    let total_weight: f64 = contributions.iter().map(|c| c.weight).sum();
+    let score = if total_weight > 0.0 {
+        contributions.iter().map(|c| c.signal * c.weight).sum::<f64>() / total_weight * 100.0
+    } else {
+        0.0
+    };
+
+    CompositeScore { score, contributions }
+}
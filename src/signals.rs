@@ -0,0 +1,200 @@
+use std::env;
+use serde::{Deserialize, Serialize};
+use crate::currency::Currency;
+use crate::report::TechnicalReport;
+use crate::strategy::{self, Strategy, StrategyResult};
+
+/// Direction of a rule-based trade signal
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Buy,
+    Sell,
+    Hold,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Buy => "BUY",
+            Action::Sell => "SELL",
+            Action::Hold => "HOLD",
+        }
+    }
+}
+
+/// A concrete trade signal with entry, stop and target levels
+#[derive(Serialize)]
+pub struct TradeSignal {
+    pub action: Action,
+    pub currency: Currency,
+    pub score: f64,
+    pub entry: f64,
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+    pub reasons: Vec<String>,
+    pub strategies: Vec<StrategyResult>,
+}
+
+/// Thresholds used to turn indicator states into a signal
+#[derive(Clone, Serialize)]
+pub struct SignalRules {
+    pub buy_score: f64,            // Composite score at or above which to buy
+    pub sell_score: f64,           // Composite score at or below which to sell
+    pub rsi_overbought: f64,       // Blocks buys above this RSI
+    pub rsi_oversold: f64,         // Blocks sells below this RSI
+    pub stop_atr: f64,             // Stop distance in ATR multiples
+    pub target_atr: f64,           // Target distance in ATR multiples
+    pub fallback_stop_pct: f64,    // Stop distance in percent when ATR is unavailable
+}
+
+impl Default for SignalRules {
+    fn default() -> Self {
+        SignalRules {
+            buy_score: 15.0,
+            sell_score: -15.0,
+            rsi_overbought: 70.0,
+            rsi_oversold: 30.0,
+            stop_atr: 1.5,
+            target_atr: 3.0,
+            fallback_stop_pct: 3.0,
+        }
+    }
+}
+
+impl SignalRules {
+    /// Default rules with overrides from SIGNAL_* environment variables
+    pub fn from_env() -> Self {
+        let defaults = SignalRules::default();
+        SignalRules {
+            buy_score: env_f64("SIGNAL_BUY_SCORE", defaults.buy_score),
+            sell_score: env_f64("SIGNAL_SELL_SCORE", defaults.sell_score),
+            rsi_overbought: env_f64("SIGNAL_RSI_OVERBOUGHT", defaults.rsi_overbought),
+            rsi_oversold: env_f64("SIGNAL_RSI_OVERSOLD", defaults.rsi_oversold),
+            stop_atr: env_f64("SIGNAL_STOP_ATR", defaults.stop_atr),
+            target_atr: env_f64("SIGNAL_TARGET_ATR", defaults.target_atr),
+            fallback_stop_pct: env_f64("SIGNAL_STOP_PCT", defaults.fallback_stop_pct),
+        }
+    }
+}
+
+fn env_f64(name: &str, default: f64) -> f64 {
+    match env::var(name) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            log::warn!("Ignoring invalid {}: {}", name, value);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+/// Derive a buy/sell/hold signal from the technical report and any user-defined strategies
+pub fn generate_signal(report: &TechnicalReport, rules: &SignalRules, strategies: &[Strategy]) -> TradeSignal {
+    let score = report.composite.score;
+    let rsi = report.indicator("rsi").and_then(|rsi| rsi.latest("RSI"));
+    let mut reasons = vec![format!("Composite score {:+.1} ({})", score, report.composite.label())];
+
+    let mut action = if score >= rules.buy_score {
+        Action::Buy
+    } else if score <= rules.sell_score {
+        Action::Sell
+    } else {
+        reasons.push(format!("Score inside hold band ({:+.1} to {:+.1})", rules.sell_score, rules.buy_score));
+        Action::Hold
+    };
+
+    // Don't chase stretched moves
+    if let Some(rsi) = rsi {
+        if action == Action::Buy && rsi > rules.rsi_overbought {
+            reasons.push(format!("Buy blocked: RSI {:.1} above {:.0}", rsi, rules.rsi_overbought));
+            action = Action::Hold;
+        } else if action == Action::Sell && rsi < rules.rsi_oversold {
+            reasons.push(format!("Sell blocked: RSI {:.1} below {:.0}", rsi, rules.rsi_oversold));
+            action = Action::Hold;
+        }
+    }
+
+    // A triggered strategy takes precedence over the score thresholds
+    let strategies = strategy::evaluate_strategies(strategies, report);
+    if let Some(triggered) = strategies.iter().find(|result| result.triggered && result.action != Action::Hold) {
+        reasons.push(format!("Strategy '{}' triggered: {}", triggered.name, triggered.condition));
+        action = triggered.action;
+    }
+
+    with_levels(report, rules, action, reasons, strategies)
+}
+
+/// Signal driven by a single strategy: its action when triggered, otherwise hold
+pub fn strategy_signal(report: &TechnicalReport, rules: &SignalRules, strategy: &Strategy) -> TradeSignal {
+    let strategies = strategy::evaluate_strategies(std::slice::from_ref(strategy), report);
+    let (action, reason) = if strategies[0].triggered {
+        (strategy.action, format!("Strategy '{}' triggered: {}", strategy.name, strategy.condition))
+    } else {
+        (Action::Hold, format!("Strategy '{}' not triggered", strategy.name))
+    };
+
+    with_levels(report, rules, action, vec![reason], strategies)
+}
+
+/// Attach entry, stop and target levels to an action
+pub fn with_levels(report: &TechnicalReport, rules: &SignalRules, action: Action, mut reasons: Vec<String>, strategies: Vec<StrategyResult>) -> TradeSignal {
+    let score = report.composite.score;
+    let entry = report.last_price();
+
+    // Size stops to volatility when ATR is available
+    let atr = report.indicator("atr").and_then(|atr| atr.latest("ATR (14-day)"));
+    let (stop_distance, target_distance) = match atr {
+        Some(atr) => {
+            reasons.push(format!("Stop {:.1} ATR, target {:.1} ATR (ATR {})", rules.stop_atr, rules.target_atr, report.currency.price(atr)));
+            (atr * rules.stop_atr, atr * rules.target_atr)
+        }
+        None => {
+            let distance = entry * rules.fallback_stop_pct / 100.0;
+            reasons.push(format!("ATR unavailable, using {:.1}% stop", rules.fallback_stop_pct));
+            (distance, distance * rules.target_atr / rules.stop_atr)
+        }
+    };
+
+    let (stop_loss, take_profit) = match action {
+        Action::Buy => (Some(entry - stop_distance), Some(entry + target_distance)),
+        Action::Sell => (Some(entry + stop_distance), Some(entry - target_distance)),
+        Action::Hold => (None, None),
+    };
+
+    TradeSignal { action, currency: report.currency, score, entry, stop_loss, take_profit, reasons, strategies }
+}
+
+/// Find the Buy/Sell/Hold call in the AI analysis's overall recommendation
+pub fn parse_llm_action(analysis: &str) -> Option<Action> {
+    let lower = analysis.to_lowercase();
+    let section = lower.rfind("overall recommendation").map(|idx| &lower[idx..])?;
+
+    // The first of the three words after the heading is the call; only whole words count, so "threshold" or
+    // "buyers" don't
+    section.split(|c: char| !c.is_alphanumeric())
+        .find_map(|word| match word {
+            "buy" => Some(Action::Buy),
+            "sell" => Some(Action::Sell),
+            "hold" => Some(Action::Hold),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_call_is_the_first_word_after_the_recommendation_heading() {
+        assert_eq!(parse_llm_action("1. Trend: buy the dip\n\n6. Overall Recommendation: SELL into strength"), Some(Action::Sell));
+        assert_eq!(parse_llm_action("**Overall recommendation:** Hold."), Some(Action::Hold));
+        assert_eq!(parse_llm_action("No recommendation here: buy"), None);
+    }
+
+    #[test]
+    fn words_containing_a_call_are_not_the_call() {
+        let analysis = "Overall recommendation: wait for a close above the threshold, then buy";
+        assert_eq!(parse_llm_action(analysis), Some(Action::Buy));
+        assert_eq!(parse_llm_action("Overall recommendation: buyers and holders are exhausted, sell"), Some(Action::Sell));
+    }
+}
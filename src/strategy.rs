@@ -0,0 +1,308 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::report::TechnicalReport;
+use crate::signals::Action;
+
+/// Default location of the strategy definitions
+const DEFAULT_STRATEGIES_FILE: &str = "strategies.toml";
+
+#[derive(Deserialize)]
+struct StrategyFile {
+    #[serde(default)]
+    strategy: Vec<StrategyConfig>,
+}
+
+#[derive(Deserialize)]
+struct StrategyConfig {
+    name: String,
+    action: Action,
+    when: String,
+}
+
+/// A user-defined strategy: an action taken when its condition holds
+pub struct Strategy {
+    pub name: String,
+    pub action: Action,
+    pub condition: String,
+    rule: Rule,
+}
+
+/// Outcome of evaluating one strategy against the latest report
+#[derive(Serialize)]
+pub struct StrategyResult {
+    pub name: String,
+    pub action: Action,
+    pub condition: String,
+    pub triggered: bool,
+}
+
+/// A standalone condition in the strategy syntax, e.g. for alerts
+pub struct Condition(Rule);
+
+impl Condition {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        parse_rule(expression).map(Condition)
+    }
+
+    pub fn matches(&self, report: &TechnicalReport) -> bool {
+        self.0.matches(report)
+    }
+}
+
+/// Conditions joined with OR, each a group of comparisons joined with AND
+struct Rule {
+    any: Vec<Vec<Comparison>>,
+}
+
+struct Comparison {
+    left: Operand,
+    op: Op,
+    right: Operand,
+}
+
+enum Operand {
+    Number(f64),
+    Variable(String),
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+/// Load strategies from STRATEGIES_FILE (default strategies.toml); a missing file means no strategies
+pub fn load_strategies() -> Result<Vec<Strategy>, Box<dyn Error>> {
+    let path = env::var("STRATEGIES_FILE").unwrap_or_else(|_| DEFAULT_STRATEGIES_FILE.to_string());
+    if !Path::new(&path).exists() {
+        return Ok(Vec::new());
+    }
+
+    let file: StrategyFile = toml::from_str(&fs::read_to_string(&path)?)
+        .map_err(|e| format!("Invalid strategy file {}: {}", path, e))?;
+
+    file.strategy.into_iter()
+        .map(|config| {
+            let rule = parse_rule(&config.when)
+                .map_err(|e| format!("Strategy '{}': {}", config.name, e))?;
+            Ok(Strategy { name: config.name, action: config.action, condition: config.when, rule })
+        })
+        .collect()
+}
+
+/// Evaluate every strategy against the report
+pub fn evaluate_strategies(strategies: &[Strategy], report: &TechnicalReport) -> Vec<StrategyResult> {
+    strategies.iter()
+        .map(|strategy| StrategyResult {
+            name: strategy.name.clone(),
+            action: strategy.action,
+            condition: strategy.condition.clone(),
+            triggered: strategy.rule.matches(report),
+        })
+        .collect()
+}
+
+impl Rule {
+    fn matches(&self, report: &TechnicalReport) -> bool {
+        self.any.iter().any(|group| group.iter().all(|comparison| comparison.matches(report)))
+    }
+}
+
+impl Comparison {
+    fn matches(&self, report: &TechnicalReport) -> bool {
+        // A comparison against an unavailable value (e.g. disabled indicator) never holds
+        let (Some(left), Some(right)) = (self.left.value(report), self.right.value(report)) else {
+            return false;
+        };
+
+        match self.op {
+            Op::Lt => left < right,
+            Op::Le => left <= right,
+            Op::Gt => left > right,
+            Op::Ge => left >= right,
+            Op::Eq => (left - right).abs() < f64::EPSILON,
+            Op::Ne => (left - right).abs() >= f64::EPSILON,
+        }
+    }
+}
+
+impl Operand {
+    fn value(&self, report: &TechnicalReport) -> Option<f64> {
+        match self {
+            Operand::Number(value) => Some(*value),
+            Operand::Variable(name) => variable(report, name),
+        }
+    }
+}
+
+/// Resolve a variable name (e.g. "rsi", "sma200", "bb_lower") to its latest value
+pub fn variable(report: &TechnicalReport, name: &str) -> Option<f64> {
+    let latest = |key: &str, value: &str| report.indicator(key).and_then(|indicator| indicator.latest(value));
+
+    match name {
+        "price" => Some(report.last_price()),
+        "score" => Some(report.composite.score),
+        "support" => Some(report.levels.support),
+        "resistance" => Some(report.levels.resistance),
+        "fear_greed" => report.fear_greed.first().map(|reading| reading.value as f64),
+        "rsi" => latest("rsi", "RSI"),
+        "macd" => latest("macd", "MACD Line"),
+        "macd_signal" => latest("macd", "Signal Line"),
+        "macd_hist" => latest("macd", "Histogram"),
+        "bb_upper" => latest("bollinger", "Upper Band"),
+        "bb_middle" => latest("bollinger", "Middle Band (SMA)"),
+        "bb_lower" => latest("bollinger", "Lower Band"),
+        "obv" => latest("obv", "OBV"),
+        "atr" => latest("atr", "ATR (14-day)"),
+        _ => {
+            // Moving averages by length, e.g. sma200 or ema12
+            let (key, length) = if let Some(length) = name.strip_prefix("sma") {
+                ("sma", length)
+            } else if let Some(length) = name.strip_prefix("ema") {
+                ("ema", length)
+            } else {
+                return None;
+            };
+            let prefix = format!("{} ({}-", key.to_uppercase(), length);
+
+            report.indicator(key)?
+                .periods.last()?
+                .values.iter()
+                .find(|value| value.name.starts_with(&prefix))
+                .map(|value| value.value)
+        }
+    }
+}
+
+/// Parse an expression like "RSI < 30 AND price > SMA200"
+fn parse_rule(expression: &str) -> Result<Rule, String> {
+    let tokens = tokenize(expression);
+    if tokens.is_empty() {
+        return Err("empty condition".to_string());
+    }
+
+    let mut any = Vec::new();
+    for group in tokens.split(|token| token == "or") {
+        let mut all = Vec::new();
+        for comparison in group.split(|token| token == "and") {
+            all.push(parse_comparison(comparison)?);
+        }
+        any.push(all);
+    }
+
+    Ok(Rule { any })
+}
+
+/// Split an expression into lowercase words, numbers and operators (spaces around operators are optional)
+fn tokenize(expression: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_operator = false;
+
+    for c in expression.to_lowercase().chars() {
+        let is_operator = matches!(c, '<' | '>' | '=' | '!');
+        if (c.is_whitespace() || is_operator != in_operator) && !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if !c.is_whitespace() {
+            current.push(c);
+            in_operator = is_operator;
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+fn parse_comparison(tokens: &[String]) -> Result<Comparison, String> {
+    let [left, op, right] = tokens else {
+        return Err(format!("expected '<value> <operator> <value>', got '{}'", tokens.join(" ")));
+    };
+
+    let op = match op.as_str() {
+        "<" => Op::Lt,
+        "<=" => Op::Le,
+        ">" => Op::Gt,
+        ">=" => Op::Ge,
+        "=" | "==" => Op::Eq,
+        "!=" => Op::Ne,
+        other => return Err(format!("unknown operator '{}'", other)),
+    };
+
+    Ok(Comparison { left: parse_operand(left)?, op, right: parse_operand(right)? })
+}
+
+fn parse_operand(token: &str) -> Result<Operand, String> {
+    if let Ok(number) = token.parse::<f64>() {
+        return Ok(Operand::Number(number));
+    }
+    if is_known_variable(token) {
+        return Ok(Operand::Variable(token.to_string()));
+    }
+    Err(format!("unknown value '{}'", token))
+}
+
+fn is_known_variable(name: &str) -> bool {
+    const VARIABLES: [&str; 14] = [
+        "price", "score", "support", "resistance", "fear_greed", "rsi", "macd", "macd_signal", "macd_hist",
+        "bb_upper", "bb_middle", "bb_lower", "obv", "atr",
+    ];
+
+    let moving_average = name.strip_prefix("sma").or_else(|| name.strip_prefix("ema"))
+        .is_some_and(|length| !length.is_empty() && length.chars().all(|c| c.is_ascii_digit()));
+
+    VARIABLES.contains(&name) || moving_average
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_example_condition_parses() {
+        let examples = [
+            ("alerts.example.toml", include_str!("../alerts.example.toml")),
+            ("strategies.example.toml", include_str!("../strategies.example.toml")),
+        ];
+        for (file, source) in examples {
+            let table: toml::Table = toml::from_str(source).unwrap();
+            let conditions: Vec<&str> = table.values()
+                .filter_map(toml::Value::as_array)
+                .flatten()
+                .filter_map(|entry| entry.get("when")?.as_str())
+                .collect();
+            assert!(!conditions.is_empty(), "no conditions in {}", file);
+            for condition in conditions {
+                if let Err(e) = parse_rule(condition) {
+                    panic!("'{}' in {}: {}", condition, file, e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or_and_operators_need_no_spaces() {
+        let rule = parse_rule("RSI<30 AND price>=sma200 or score > 50").unwrap();
+        assert_eq!(rule.any.len(), 2);
+        assert_eq!(rule.any[0].len(), 2);
+        assert_eq!(rule.any[1].len(), 1);
+    }
+
+    #[test]
+    fn unknown_values_and_operators_are_errors() {
+        assert_eq!(parse_rule("price > moon").err().as_deref(), Some("unknown value 'moon'"));
+        assert_eq!(parse_rule("price => 5").err().as_deref(), Some("unknown operator '=>'"));
+        assert_eq!(parse_rule("sma > 5").err().as_deref(), Some("unknown value 'sma'"));
+        assert_eq!(parse_rule("  ").err().as_deref(), Some("empty condition"));
+        assert!(parse_rule("price >").is_err());
+    }
+}
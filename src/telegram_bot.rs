@@ -0,0 +1,209 @@
+use std::env;
+use std::error::Error;
+use std::time::Duration;
+use chrono::Utc;
+use reqwest::Client;
+use rusqlite::{Connection, params};
+use serde_json::{json, Value};
+
+/// Default location of the analysis feedback database
+const DEFAULT_FEEDBACK_DB: &str = "feedback.db";
+
+/// Seconds Telegram holds a getUpdates request open while waiting for updates
+const POLL_TIMEOUT_SECONDS: u64 = 50;
+
+/// Start of the message asking for a follow-up question; replies to it are run as `--ask` questions
+const ASK_PROMPT: &str = "Reply to this message with your follow-up question about ";
+
+/// Where a button was pressed: the chat and, in forum groups, the topic
+struct Origin {
+    chat_id: String,
+    thread: Option<i64>,
+}
+
+impl Origin {
+    fn from_message(message: &Value) -> Option<Self> {
+        Some(Origin {
+            chat_id: message["chat"]["id"].as_i64()?.to_string(),
+            thread: message["message_thread_id"].as_i64().filter(|_| message["is_topic_message"].as_bool() == Some(true)),
+        })
+    }
+
+    /// The chat in TELEGRAM_CHAT_ID form, `chat_id` or `chat_id:message_thread_id`
+    fn target(&self) -> String {
+        match self.thread {
+            Some(thread) => format!("{}:{}", self.chat_id, thread),
+            None => self.chat_id.clone(),
+        }
+    }
+}
+
+/// Long-poll Telegram for presses of the buttons under analyses (TELEGRAM_BUTTONS=true) and act on them:
+/// re-run the analysis, ask for and run a follow-up question, or record whether the call was correct.
+/// Only chats listed in TELEGRAM_CHAT_ID are served.
+pub async fn run_bot() -> Result<(), Box<dyn Error>> {
+    let api_key = env::var("TELEGRAM_API_KEY").map_err(|_| "TELEGRAM_API_KEY must be set for the `bot telegram` command")?;
+    let allowed = allowed_chats()?;
+    let client = Client::builder()
+        .timeout(Duration::from_secs(POLL_TIMEOUT_SECONDS + 10))
+        .build()?;
+    let api = format!("https://api.telegram.org/bot{}", api_key);
+    let mut offset = 0;
+
+    log::info!("Telegram bot waiting for button presses in {} chat(s)...", allowed.len());
+    loop {
+        let response = client.get(format!("{}/getUpdates", api))
+            .query(&[("offset", offset.to_string()), ("timeout", POLL_TIMEOUT_SECONDS.to_string())])
+            .send()
+            .await;
+        let updates: Value = match response {
+            Ok(response) => response.json().await?,
+            Err(e) => {
+                log::warn!("could not fetch Telegram updates: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        let Some(updates) = updates["result"].as_array() else {
+            return Err(format!("Telegram rejected getUpdates: {}", updates["description"]).into());
+        };
+
+        for update in updates {
+            offset = update["update_id"].as_i64().unwrap_or(offset) + 1;
+            let result = if let Some(query) = update.get("callback_query") {
+                handle_button(&client, &api, query, &allowed).await
+            } else if let Some(message) = update.get("message") {
+                handle_reply(message, &allowed)
+            } else {
+                Ok(())
+            };
+            if let Err(e) = result {
+                log::warn!("could not handle Telegram update: {}", e);
+            }
+        }
+    }
+}
+
+/// Chat ids from TELEGRAM_CHAT_ID, without topics
+fn allowed_chats() -> Result<Vec<String>, Box<dyn Error>> {
+    let chats: Vec<String> = env::var("TELEGRAM_CHAT_ID").unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| entry.split(':').next())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    if chats.is_empty() {
+        return Err("TELEGRAM_CHAT_ID must list the chats the bot answers in".into());
+    }
+    Ok(chats)
+}
+
+/// Whether the message's chat is listed, by id or by @username
+fn is_allowed(message: &Value, allowed: &[String]) -> bool {
+    let chat = &message["chat"];
+    let id = chat["id"].as_i64().map(|id| id.to_string());
+    let username = chat["username"].as_str().map(|name| format!("@{}", name));
+    allowed.iter().any(|entry| Some(entry) == id.as_ref() || Some(entry) == username.as_ref())
+}
+
+async fn handle_button(client: &Client, api: &str, query: &Value, allowed: &[String]) -> Result<(), Box<dyn Error>> {
+    let message = &query["message"];
+    let data = query["data"].as_str().unwrap_or_default();
+    let parts: Vec<&str> = data.split(':').collect();
+    let reply = match (parts.as_slice(), Origin::from_message(message)) {
+        (_, None) => "This message is too old to act on".to_string(),
+        (_, Some(_)) if !is_allowed(message, allowed) => "This chat is not in TELEGRAM_CHAT_ID".to_string(),
+        (["rerun", symbol], Some(origin)) => {
+            spawn_run(&origin, symbol, None)?;
+            format!("Re-running the {} analysis...", symbol)
+        }
+        (["ask", symbol], Some(origin)) => {
+            let mut payload = json!({
+                "chat_id": origin.chat_id,
+                "text": format!("{}{}.", ASK_PROMPT, symbol),
+                "reply_markup": { "force_reply": true },
+            });
+            if let Some(thread) = origin.thread {
+                payload["message_thread_id"] = json!(thread);
+            }
+            client.post(format!("{}/sendMessage", api)).json(&payload).send().await?;
+            "Send your question as a reply".to_string()
+        }
+        ([verdict @ ("correct" | "incorrect"), symbol, call], Some(origin)) => {
+            let message_id = message["message_id"].as_i64().unwrap_or_default();
+            record_feedback(&origin, message_id, symbol, call, *verdict == "correct")?;
+            log::info!("Recorded {} {} call as {}", symbol, call, verdict);
+            format!("Marked {} as {}", call, verdict)
+        }
+        _ => format!("Unknown action '{}'", data),
+    };
+
+    client.post(format!("{}/answerCallbackQuery", api))
+        .json(&json!({ "callback_query_id": query["id"], "text": reply }))
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// A reply to the follow-up prompt runs the analysis with the reply as its question
+fn handle_reply(message: &Value, allowed: &[String]) -> Result<(), Box<dyn Error>> {
+    let prompt = message["reply_to_message"]["text"].as_str().unwrap_or_default();
+    let (Some(symbol), Some(question)) = (prompt.strip_prefix(ASK_PROMPT), message["text"].as_str()) else {
+        return Ok(());
+    };
+    let Some(origin) = Origin::from_message(message).filter(|_| is_allowed(message, allowed)) else {
+        return Ok(());
+    };
+    spawn_run(&origin, symbol.trim_end_matches('.'), Some(question))
+}
+
+/// Run the analysis in a new process of this program, sent to Telegram in the chat the request came from.
+/// BTCUSDT gets the full analysis; other symbols go through the watchlist command.
+fn spawn_run(origin: &Origin, symbol: &str, question: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let mut command = tokio::process::Command::new(env::current_exe()?);
+    // Telegram settings come from the environment only, so outputs.toml can't redirect the reply
+    command.env("OUTPUTS_FILE", "").env("TELEGRAM_CHAT_ID", origin.target()).env("TELEGRAM_BUTTONS", "true");
+    if symbol != "BTCUSDT" {
+        command.env("WATCHLIST", symbol).arg("watchlist");
+    }
+    if let Some(question) = question {
+        command.arg("--ask").arg(question);
+    }
+    command.arg("telegram");
+
+    let mut child = command.spawn()?;
+    log::info!("Started {} analysis for chat {}", symbol, origin.target());
+    tokio::spawn(async move {
+        match child.wait().await {
+            Ok(status) if !status.success() => log::warn!("analysis run exited with {}", status),
+            Err(e) => log::warn!("analysis run failed: {}", e),
+            Ok(_) => {}
+        }
+    });
+    Ok(())
+}
+
+/// Store a correct/incorrect verdict on an analysis in FEEDBACK_DB (default feedback.db)
+fn record_feedback(origin: &Origin, message_id: i64, symbol: &str, call: &str, correct: bool) -> Result<(), Box<dyn Error>> {
+    let connection = Connection::open(env::var("FEEDBACK_DB").unwrap_or_else(|_| DEFAULT_FEEDBACK_DB.to_string()))?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS feedback (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            recorded_at TEXT NOT NULL,
+            chat_id TEXT NOT NULL,
+            message_id INTEGER NOT NULL,
+            symbol TEXT NOT NULL,
+            call TEXT NOT NULL,
+            correct INTEGER NOT NULL,
+            UNIQUE (chat_id, message_id)
+        )",
+        [],
+    )?;
+    // Pressing the other button later replaces the earlier verdict
+    connection.execute(
+        "INSERT OR REPLACE INTO feedback (recorded_at, chat_id, message_id, symbol, call, correct)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![Utc::now().to_rfc3339(), origin.chat_id, message_id, symbol, call, correct],
+    )?;
+    Ok(())
+}
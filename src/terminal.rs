@@ -0,0 +1,108 @@
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by --no-color
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const REVERSE: &str = "\x1b[7m";
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+/// Width of the rule drawn above each section
+const SEPARATOR_WIDTH: usize = 60;
+
+/// Words colored as bullish, bearish or neutral calls
+const BULLISH: [&str; 5] = ["buy", "long", "bullish", "uptrend", "oversold"];
+const BEARISH: [&str; 5] = ["sell", "short", "bearish", "downtrend", "overbought"];
+const NEUTRAL: [&str; 3] = ["hold", "neutral", "flat"];
+
+/// Lines that carry the final call in the recommendation, structured summary and signal sections
+const CALL_LINES: [&str; 3] = ["Direction:", "Recommendation:", "Action:"];
+
+pub fn disable_color() {
+    NO_COLOR.store(true, Ordering::Relaxed);
+}
+
+/// Colors are used when stdout is a terminal, unless --no-color or NO_COLOR is set
+fn color_enabled() -> bool {
+    !NO_COLOR.load(Ordering::Relaxed) && env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal()
+}
+
+/// Text for the terminal: `=== SECTION ===` headers get a rule and bold title, bullish and bearish words
+/// are green and red, and the line with the final call is highlighted. Unchanged when colors are off.
+pub fn paint(text: &str) -> String {
+    if !color_enabled() {
+        return text.to_string();
+    }
+
+    let mut painted = String::with_capacity(text.len() * 2);
+    for line in text.split_inclusive('\n') {
+        let (content, newline) = match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (line, ""),
+        };
+        let trimmed = content.trim();
+        if !trimmed.is_empty() && trimmed.chars().all(|c| c == '=') {
+            painted.push_str(&format!("{}{}{}", DIM, "─".repeat(SEPARATOR_WIDTH), RESET));
+        } else if let Some(title) = trimmed.strip_prefix("===").and_then(|rest| rest.strip_suffix("===")) {
+            painted.push_str(&format!("{}{}{}\n{}{}{}{}", DIM, "─".repeat(SEPARATOR_WIDTH), RESET, BOLD, CYAN, title.trim(), RESET));
+        } else if trimmed.starts_with('#') {
+            painted.push_str(&format!("{}{}{}", BOLD, color_words(content), RESET));
+        } else if CALL_LINES.iter().any(|label| trimmed.starts_with(label)) {
+            let color = call_color(trimmed).unwrap_or(BOLD);
+            painted.push_str(&format!("{}{}{} {} {}", BOLD, REVERSE, color, content, RESET));
+        } else {
+            painted.push_str(&color_words(content));
+        }
+        painted.push_str(newline);
+    }
+    painted
+}
+
+/// The color of the first bullish, bearish or neutral word in the line
+fn call_color(line: &str) -> Option<&'static str> {
+    line.split(|c: char| !c.is_alphanumeric()).find_map(word_color)
+}
+
+fn word_color(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    if BULLISH.contains(&word.as_str()) {
+        Some(GREEN)
+    } else if BEARISH.contains(&word.as_str()) {
+        Some(RED)
+    } else if NEUTRAL.contains(&word.as_str()) {
+        Some(YELLOW)
+    } else {
+        None
+    }
+}
+
+/// The line with each bullish, bearish or neutral word colored
+fn color_words(line: &str) -> String {
+    let mut colored = String::with_capacity(line.len());
+    let mut word_start = None;
+    for (index, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (c.is_alphanumeric(), word_start) {
+            (true, None) => word_start = Some(index),
+            (false, Some(start)) => {
+                let word = &line[start..index];
+                match word_color(word) {
+                    Some(color) => colored.push_str(&format!("{}{}{}", color, word, RESET)),
+                    None => colored.push_str(word),
+                }
+                word_start = None;
+            }
+            _ => {}
+        }
+        if !c.is_alphanumeric() && index < line.len() {
+            colored.push(c);
+        }
+    }
+    colored
+}
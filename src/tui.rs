@@ -0,0 +1,324 @@
+use std::env;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind};
+use futures::StreamExt;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Sparkline, Wrap};
+use ratatui::Frame;
+use tokio::sync::{mpsc, Notify};
+use tokio_tungstenite::tungstenite::Message;
+use crate::alerts;
+use crate::currency::Currency;
+use crate::data_fetcher;
+use crate::memory::{AnalysisMemory, MemoryConfig, MemoryEntry};
+use crate::report::TechnicalReport;
+use crate::technical_analysis;
+
+/// Binance market data stream used for live price ticks
+const DEFAULT_STREAM_URL: &str = "wss://stream.binance.com:9443/ws";
+
+/// Seconds between price polls while the stream is unavailable
+const PRICE_POLL_SECONDS: u64 = 10;
+
+/// Something that changed, sent from the background tasks to the screen
+enum Update {
+    Price { price: f64, live: bool },
+    Report(Box<TechnicalReport>),
+    Analysis(Option<MemoryEntry>),
+    Alerts(Vec<(DateTime<Utc>, String)>),
+    Status(String),
+}
+
+/// Everything on screen
+#[derive(Default)]
+struct Dashboard {
+    price: Option<f64>,
+    live: bool,
+    price_time: Option<DateTime<Utc>>,
+    report: Option<TechnicalReport>,
+    report_time: Option<DateTime<Utc>>,
+    analysis: Option<MemoryEntry>,
+    alerts: Vec<(DateTime<Utc>, String)>,
+    status: String,
+}
+
+impl Dashboard {
+    fn apply(&mut self, update: Update) {
+        match update {
+            Update::Price { price, live } => {
+                self.price = Some(price);
+                self.live = live;
+                self.price_time = Some(Utc::now());
+            }
+            Update::Report(report) => {
+                self.report = Some(*report);
+                self.report_time = Some(Utc::now());
+                self.status.clear();
+            }
+            Update::Analysis(analysis) => self.analysis = analysis,
+            Update::Alerts(alerts) => self.alerts = alerts,
+            Update::Status(status) => self.status = status,
+        }
+    }
+}
+
+/// Full-screen dashboard: live BTCUSDT price from the Binance stream, indicator gauges refreshed every
+/// TUI_REFRESH_SECONDS (default 60), the latest stored analysis and the alerts that have fired. `q` quits, `r` refreshes.
+pub async fn run_tui(data_provider_api_key: &str, api_base_url: &str) -> Result<(), Box<dyn Error>> {
+    let refresh = env::var("TUI_REFRESH_SECONDS").ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(60)
+        .max(5);
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let refresh_now = Arc::new(Notify::new());
+
+    let price_task = tokio::spawn(stream_prices(api_base_url.to_string(), sender.clone()));
+    let data_task = tokio::spawn(refresh_data(data_provider_api_key.to_string(), api_base_url.to_string(), refresh, Arc::clone(&refresh_now), sender));
+
+    // Log lines would tear through the screen; failures show on the status line instead
+    let level = log::max_level();
+    log::set_max_level(log::LevelFilter::Off);
+    let mut terminal = ratatui::init();
+    let mut dashboard = Dashboard { status: "Loading market data...".to_string(), ..Dashboard::default() };
+    let mut events = EventStream::new();
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| draw(frame, &dashboard)) {
+            break Err(e.into());
+        }
+        tokio::select! {
+            Some(update) = updates.recv() => dashboard.apply(update),
+            event = events.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break Ok(()),
+                    KeyCode::Char('r') => {
+                        dashboard.status = "Refreshing...".to_string();
+                        refresh_now.notify_one();
+                    }
+                    _ => {}
+                },
+                Some(Ok(_)) => {}
+                Some(Err(e)) => break Err(e.into()),
+                None => break Ok(()),
+            },
+        }
+    };
+    ratatui::restore();
+    log::set_max_level(level);
+    price_task.abort();
+    data_task.abort();
+    result
+}
+
+/// Send every trade tick from the Binance mini-ticker stream (TUI_STREAM_URL); while it can't be reached,
+/// poll the REST price instead and try the stream again
+async fn stream_prices(api_base_url: String, sender: mpsc::UnboundedSender<Update>) {
+    let url = format!("{}/btcusdt@miniTicker", env::var("TUI_STREAM_URL").unwrap_or_else(|_| DEFAULT_STREAM_URL.to_string()));
+    loop {
+        if let Err(e) = stream_ticks(&url, &sender).await {
+            let _ = sender.send(Update::Status(format!("Price stream unavailable ({}), polling instead", e)));
+        }
+        match data_fetcher::fetch_spot_price(&api_base_url, "BTCUSDT").await {
+            Ok(price) => {
+                let _ = sender.send(Update::Price { price, live: false });
+            }
+            Err(e) => {
+                let _ = sender.send(Update::Status(format!("Could not fetch the price: {}", e)));
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(PRICE_POLL_SECONDS)).await;
+    }
+}
+
+async fn stream_ticks(url: &str, sender: &mpsc::UnboundedSender<Update>) -> Result<(), Box<dyn Error>> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await?;
+    while let Some(message) = socket.next().await {
+        if let Message::Text(text) = message? {
+            let tick: serde_json::Value = serde_json::from_str(&text)?;
+            if let Some(price) = tick["c"].as_str().and_then(|close| close.parse().ok()) {
+                sender.send(Update::Price { price, live: true })?;
+            }
+        }
+    }
+    Err("stream closed".into())
+}
+
+/// Rebuild the technical report and reload the stored analysis and alert history on the timer or when asked
+async fn refresh_data(data_provider_api_key: String, api_base_url: String, seconds: u64, refresh_now: Arc<Notify>,
+                      sender: mpsc::UnboundedSender<Update>) {
+    loop {
+        match load_report(&data_provider_api_key, &api_base_url).await {
+            Ok(report) => {
+                let _ = sender.send(Update::Report(Box::new(report)));
+            }
+            Err(e) => {
+                let _ = sender.send(Update::Status(format!("Could not refresh the report: {}", e)));
+            }
+        }
+        let analysis = AnalysisMemory::load(&MemoryConfig::from_env().file).ok()
+            .and_then(|mut memory| memory.entries.pop());
+        let _ = sender.send(Update::Analysis(analysis));
+        if let Ok(state) = alerts::load_state() {
+            let mut fired: Vec<(DateTime<Utc>, String)> = state.fired.into_iter().map(|(name, time)| (time, name)).collect();
+            fired.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+            let _ = sender.send(Update::Alerts(fired));
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(seconds)) => {}
+            _ = refresh_now.notified() => {}
+        }
+    }
+}
+
+async fn load_report(data_provider_api_key: &String, api_base_url: &String) -> Result<TechnicalReport, Box<dyn Error>> {
+    // Errors are kept as messages, since the refresh task can't hold them across the other fetch
+    let (btc_data, fear_and_greed_data) = tokio::join!(
+        async { data_fetcher::fetch_bitcoin_trading_data(data_provider_api_key, api_base_url).await.map_err(|e| e.to_string()) },
+        data_fetcher::load_fear_greed(),
+    );
+    Ok(technical_analysis::build_technical_report(&btc_data?, &fear_and_greed_data))
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(10), Constraint::Length(8), Constraint::Length(1)])
+        .split(frame.area());
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(rows[1]);
+
+    draw_price(frame, rows[0], dashboard);
+    draw_gauges(frame, middle[0], dashboard);
+    draw_analysis(frame, middle[1], dashboard);
+    draw_alerts(frame, rows[2], dashboard);
+
+    let status = if dashboard.status.is_empty() {
+        let updated = dashboard.report_time.map(|time| time.with_timezone(&Local).format("%H:%M:%S").to_string()).unwrap_or_default();
+        format!(" Indicators updated {}  ·  q quit  ·  r refresh", updated)
+    } else {
+        format!(" {}  ·  q quit  ·  r refresh", dashboard.status)
+    };
+    frame.render_widget(Paragraph::new(status).style(Style::default().fg(Color::DarkGray)), rows[3]);
+}
+
+/// The price with its change over the report's recent candles, and a sparkline of their closes
+fn draw_price(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let block = Block::default().borders(Borders::ALL).title(" BTCUSDT ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(36), Constraint::Min(10)])
+        .split(inner);
+
+    let closes: Vec<f64> = dashboard.report.as_ref()
+        .map(|report| report.recent_candles.iter().map(|candle| candle.close).collect())
+        .unwrap_or_default();
+    let mut lines = vec![match dashboard.price {
+        Some(price) => {
+            let change = closes.first().filter(|first| **first != 0.0).map(|first| (price - first) / first * 100.0);
+            let color = match change {
+                Some(change) if change < 0.0 => Color::Red,
+                Some(_) => Color::Green,
+                None => Color::White,
+            };
+            Line::from(vec![
+                Span::styled(Currency::USD.price(price), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                Span::raw(change.map(|change| format!("  {:+.2}% ({} candles)", change, closes.len())).unwrap_or_default()),
+            ])
+        }
+        None => Line::from("waiting for price..."),
+    }];
+    let source = if dashboard.live { "live stream" } else { "polled" };
+    if let Some(time) = dashboard.price_time {
+        lines.push(Line::styled(format!("{} · {}", source, time.with_timezone(&Local).format("%H:%M:%S")),
+                                Style::default().fg(Color::DarkGray)));
+    }
+    frame.render_widget(Paragraph::new(lines), columns[0]);
+
+    // Sparkline bars start at zero, so the closes are shifted to start just below the lowest one
+    let low = closes.iter().copied().fold(f64::MAX, f64::min);
+    let high = closes.iter().copied().fold(f64::MIN, f64::max);
+    let range = (high - low).max(f64::EPSILON);
+    let bars: Vec<u64> = closes.iter().map(|close| 1 + ((close - low) / range * 99.0) as u64).collect();
+    frame.render_widget(Sparkline::default().data(&bars).style(Style::default().fg(Color::Cyan)), columns[1]);
+}
+
+/// Composite score, RSI, Bollinger %B and Fear & Greed as gauges
+fn draw_gauges(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let block = Block::default().borders(Borders::ALL).title(" Indicators ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let Some(report) = &dashboard.report else {
+        frame.render_widget(Paragraph::new("waiting for data..."), inner);
+        return;
+    };
+
+    let composite = &report.composite;
+    let rsi = report.indicator("rsi").and_then(|rsi| rsi.latest("RSI"));
+    let percent_b = report.indicator("bollinger").and_then(|bollinger| {
+        let (upper, lower) = (bollinger.latest("Upper Band")?, bollinger.latest("Lower Band")?);
+        (upper > lower).then(|| (report.last_price() - lower) / (upper - lower) * 100.0)
+    });
+    let fear_greed = report.fear_greed.first();
+
+    let gauges = [
+        ("Composite score", Some((composite.score + 100.0) / 2.0), format!("{:+.0} {}", composite.score, composite.label())),
+        ("RSI", rsi, rsi.map(|rsi| format!("{:.1}", rsi)).unwrap_or_default()),
+        ("Bollinger %B", percent_b, percent_b.map(|b| format!("{:.0}%", b)).unwrap_or_default()),
+        ("Fear & Greed", fear_greed.map(|reading| reading.value as f64),
+         fear_greed.map(|reading| format!("{} {}", reading.value, reading.classification)).unwrap_or_default()),
+    ];
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(gauges.iter().map(|_| Constraint::Length(3)).collect::<Vec<_>>())
+        .split(inner);
+    for ((title, percent, label), row) in gauges.into_iter().zip(rows.iter()) {
+        let Some(percent) = percent else {
+            frame.render_widget(Paragraph::new(format!("{}: n/a", title)).block(Block::default().borders(Borders::ALL)), *row);
+            continue;
+        };
+        let color = if percent >= 70.0 { Color::Green } else if percent <= 30.0 { Color::Red } else { Color::Yellow };
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title(format!(" {} ", title)))
+            .gauge_style(Style::default().fg(color))
+            .ratio((percent / 100.0).clamp(0.0, 1.0))
+            .label(label);
+        frame.render_widget(gauge, *row);
+    }
+}
+
+/// The last analysis saved to the analysis memory
+fn draw_analysis(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let (title, text) = match &dashboard.analysis {
+        Some(entry) => {
+            let call = entry.recommendation.map(|action| action.as_str()).unwrap_or("no call");
+            (format!(" Latest analysis · {} · {} at {} ", entry.time.with_timezone(&Local).format("%Y-%m-%d %H:%M"), call, Currency::USD.price(entry.price)),
+             entry.analysis.clone())
+        }
+        None => (" Latest analysis ".to_string(), "No analysis stored yet; run the analysis to fill this panel.".to_string()),
+    };
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+/// Alerts and indicator events recorded by the alerts command, newest first
+fn draw_alerts(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let items: Vec<ListItem> = if dashboard.alerts.is_empty() {
+        vec![ListItem::new("No alerts have fired")]
+    } else {
+        dashboard.alerts.iter()
+            .map(|(time, name)| ListItem::new(format!("{}  {}", time.with_timezone(&Local).format("%Y-%m-%d %H:%M"), name)))
+            .collect()
+    };
+    frame.render_widget(List::new(items).block(Block::default().borders(Borders::ALL).title(" Alert history ")), area);
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::ai_client::Analysis;
+use crate::currency::Currency;
+use crate::signals::Action;
+
+/// Default location of the last sent calls
+const DEFAULT_STATE_FILE: &str = "last_sent.json";
+
+/// What to do with an analysis whose call hasn't moved since the last one sent
+#[derive(Clone, Copy, PartialEq)]
+pub enum UnchangedMode {
+    Send,   // Send it as usual
+    Skip,   // Only print it to the terminal
+    Ping,   // Send a one-line "no change" notice instead
+}
+
+/// Settings from the UNCHANGED_* environment variables
+pub struct UnchangedConfig {
+    pub mode: UnchangedMode,
+    pub confidence_change: f64,     // Confidence points that count as a change
+    pub level_change_pct: f64,      // How far entry, stop or target must move to count as a change
+    pub file: String,
+}
+
+impl UnchangedConfig {
+    /// UNCHANGED (send, skip or ping; default send), UNCHANGED_CONFIDENCE (default 10), UNCHANGED_LEVEL_PCT (default 1)
+    /// and UNCHANGED_STATE_FILE (default last_sent.json)
+    pub fn from_env() -> Self {
+        let mode = match env::var("UNCHANGED").unwrap_or_default().trim() {
+            "skip" => UnchangedMode::Skip,
+            "ping" => UnchangedMode::Ping,
+            "" | "send" => UnchangedMode::Send,
+            other => {
+                log::warn!("unknown UNCHANGED '{}', expected send, skip or ping; sending as usual", other);
+                UnchangedMode::Send
+            }
+        };
+        UnchangedConfig {
+            mode,
+            confidence_change: env_f64("UNCHANGED_CONFIDENCE", 10.0),
+            level_change_pct: env_f64("UNCHANGED_LEVEL_PCT", 1.0),
+            file: env::var("UNCHANGED_STATE_FILE").unwrap_or_else(|_| DEFAULT_STATE_FILE.to_string()),
+        }
+    }
+}
+
+/// The parts of a call compared against the last one sent
+#[derive(Serialize, Deserialize)]
+pub struct SentCall {
+    pub sent_at: DateTime<Utc>,
+    pub price: f64,
+    #[serde(default)]
+    pub currency: Currency,         // What the price and levels are in
+    pub action: Action,
+    pub outlook: Option<String>,
+    pub confidence: Option<f64>,
+    pub entry: Option<f64>,
+    pub stop: Option<f64>,
+    pub target: Option<f64>,
+}
+
+impl SentCall {
+    /// The call of an analysis; None when the model made none
+    pub fn new(analysis: &Analysis, price: f64, currency: Currency) -> Option<Self> {
+        let result = analysis.result.as_ref();
+        let recommendation = analysis.recommendation.as_ref();
+        Some(SentCall {
+            sent_at: Utc::now(),
+            price,
+            currency,
+            action: analysis.action()?,
+            outlook: analysis.outlook().map(|outlook| outlook.as_str().to_string()),
+            confidence: result.map(|result| result.confidence).or_else(|| recommendation.map(|recommendation| recommendation.confidence)),
+            entry: result.and_then(|result| result.entries.first().copied()).or_else(|| recommendation.and_then(|recommendation| recommendation.entry)),
+            stop: result.and_then(|result| result.stop_losses.first().copied()).or_else(|| recommendation.and_then(|recommendation| recommendation.stop)),
+            target: result.and_then(|result| result.take_profits.first().copied())
+                .or_else(|| recommendation.and_then(|recommendation| recommendation.targets.first().copied())),
+        })
+    }
+
+    /// What moved materially since `previous`, if anything
+    pub fn changes(&self, previous: &SentCall, config: &UnchangedConfig) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.action != previous.action {
+            changes.push(format!("call {} -> {}", previous.action.as_str(), self.action.as_str()));
+        }
+        if self.outlook != previous.outlook {
+            changes.push(format!("outlook {} -> {}", previous.outlook.as_deref().unwrap_or("none"), self.outlook.as_deref().unwrap_or("none")));
+        }
+        if let (Some(before), Some(now)) = (previous.confidence, self.confidence)
+            && (now - before).abs() >= config.confidence_change {
+            changes.push(format!("confidence {:.0}% -> {:.0}%", before, now));
+        }
+        // Levels in different currencies can't be compared, so a change of currency counts as a change by itself
+        if self.currency != previous.currency {
+            changes.push(format!("currency {} -> {}", previous.currency.code, self.currency.code));
+            return changes;
+        }
+        for (name, before, now) in [("entry", previous.entry, self.entry), ("stop", previous.stop, self.stop), ("target", previous.target, self.target)] {
+            let moved = match (before, now) {
+                (Some(before), Some(now)) if before > 0.0 => ((now - before) / before * 100.0).abs() >= config.level_change_pct,
+                (None, None) => false,
+                _ => true,
+            };
+            if moved {
+                let price = |price: Option<f64>| price.map(|price| self.currency.price(price)).unwrap_or_else(|| "none".to_string());
+                changes.push(format!("{} {} -> {}", name, price(before), price(now)));
+            }
+        }
+        changes
+    }
+}
+
+/// The last call sent for each symbol and interval, persisted between runs
+#[derive(Default, Serialize, Deserialize)]
+pub struct SentCalls {
+    pub calls: HashMap<String, SentCall>,
+}
+
+impl SentCalls {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        if !Path::new(path).exists() {
+            return Ok(SentCalls::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn env_f64(name: &str, default: f64) -> f64 {
+    env::var(name).ok().and_then(|value| value.trim().parse().ok()).unwrap_or(default)
+}
@@ -0,0 +1,60 @@
+use std::env;
+use std::time::Duration;
+use crate::report::TechnicalReport;
+use crate::signals::{Action, TradeSignal};
+
+/// Settings for --watch, read from WATCH_* environment variables
+pub struct WatchConfig {
+    pub interval: Duration,
+    pub level_change_pct: f64,  // How far support or resistance must move to count as a change
+}
+
+impl WatchConfig {
+    pub fn from_env() -> Self {
+        WatchConfig {
+            interval: Duration::from_secs((env_f64("WATCH_INTERVAL_MINUTES", 15.0).max(1.0) * 60.0) as u64),
+            level_change_pct: env_f64("WATCH_LEVEL_CHANGE_PCT", 1.0),
+        }
+    }
+}
+
+/// The parts of a check compared against the last analysis
+pub struct WatchState {
+    label: &'static str,        // Composite score label
+    action: Action,             // Rule-based signal
+    support: f64,
+    resistance: f64,
+}
+
+impl WatchState {
+    pub fn new(report: &TechnicalReport, signal: &TradeSignal) -> Self {
+        WatchState {
+            label: report.composite.label(),
+            action: signal.action,
+            support: report.levels.support,
+            resistance: report.levels.resistance,
+        }
+    }
+
+    /// What changed meaningfully since `previous`, if anything
+    pub fn changes(&self, previous: &WatchState, config: &WatchConfig) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.label != previous.label {
+            changes.push(format!("composite signal {} -> {}", previous.label, self.label));
+        }
+        if self.action != previous.action {
+            changes.push(format!("signal {} -> {}", previous.action.as_str(), self.action.as_str()));
+        }
+        for (name, before, now) in [("support", previous.support, self.support), ("resistance", previous.resistance, self.resistance)] {
+            let moved = if before > 0.0 { (now - before) / before * 100.0 } else { 0.0 };
+            if moved.abs() >= config.level_change_pct {
+                changes.push(format!("{} {:.2} -> {:.2} ({:+.1}%)", name, before, now, moved));
+            }
+        }
+        changes
+    }
+}
+
+fn env_f64(name: &str, default: f64) -> f64 {
+    env::var(name).ok().and_then(|value| value.trim().parse().ok()).unwrap_or(default)
+}
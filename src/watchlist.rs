@@ -0,0 +1,87 @@
+use std::env;
+use std::error::Error;
+use crate::ai_client::{self, Analysis, AnalysisProvider};
+use crate::currency;
+use crate::data_fetcher;
+use crate::output::{self, Outputs};
+use crate::prompt_generator::PromptTemplate;
+use crate::renderer;
+use crate::technical_analysis;
+
+/// Symbols analyzed when WATCHLIST is not set
+const DEFAULT_WATCHLIST: &str = "BTCUSDT,ETHUSDT";
+
+/// Binance symbols from WATCHLIST (comma-separated, default BTCUSDT,ETHUSDT)
+fn watchlist_symbols() -> Vec<String> {
+    env::var("WATCHLIST").unwrap_or_else(|_| DEFAULT_WATCHLIST.to_string())
+        .split(',')
+        .map(|symbol| symbol.trim().to_uppercase())
+        .filter(|symbol| !symbol.is_empty())
+        .collect()
+}
+
+/// Analyze every watchlist symbol; with a single provider the prompts go out as one batch
+pub async fn run_watchlist(data_provider_api_key: &String, api_base_url: &String, providers: &[Box<dyn AnalysisProvider>],
+                           template: &PromptTemplate, outputs: &Outputs) -> Result<(), Box<dyn Error>> {
+    // Every symbol, the Fear & Greed index and the quote currency's rate are fetched at the same time
+    let watchlist = watchlist_symbols();
+    log::info!("Fetching {} price data from API...", watchlist.join(", "));
+    let (fear_and_greed_data, fetched, rate) = tokio::join!(
+        data_fetcher::load_fear_greed(),
+        futures::future::join_all(watchlist.iter().map(|symbol| data_fetcher::fetch_trading_data(data_provider_api_key, api_base_url, symbol))),
+        currency::usd_rate(),
+    );
+    let rate = rate?;
+
+    // A symbol that can't be fetched (e.g. a typo) is skipped rather than failing the whole run
+    let mut symbols = Vec::new();
+    let mut reports = Vec::new();
+    let mut candles = Vec::new();
+    let mut prompts = Vec::new();
+    for (symbol, data) in watchlist.into_iter().zip(fetched) {
+        match data {
+            Ok(mut data) => {
+                currency::convert(&mut data, rate);
+                let report = technical_analysis::build_technical_report(&data, &fear_and_greed_data);
+                prompts.push(template.render_symbol(&symbol, report.currency, &renderer::render_text(&report)));
+                symbols.push(symbol);
+                reports.push(report);
+                candles.push(data.candles());
+            }
+            Err(e) => log::warn!("Skipping {}: {}", symbol, e),
+        }
+    }
+    if prompts.is_empty() {
+        return Err("No watchlist symbol could be fetched".into());
+    }
+
+    let analyses: Vec<Result<Analysis, String>> = match providers {
+        [provider] => ai_client::get_batch_analyses(provider.as_ref(), &prompts).await?,
+        _ => {
+            let mut analyses = Vec::new();
+            for prompt in &prompts {
+                analyses.push(ai_client::get_analysis(providers, prompt, None, false).await.map_err(|e| e.to_string()));
+            }
+            analyses
+        }
+    };
+
+    for (((symbol, report), candles), analysis) in symbols.iter().zip(&reports).zip(&candles).zip(analyses) {
+        match analysis {
+            Ok(analysis) => {
+                let mut text = format!("=== {} ===\n{}", symbol, analysis.text);
+                if let Some(result) = &analysis.result {
+                    text.push_str(&format!("\n\n{}", renderer::render_analysis_result(result, report.currency)));
+                }
+                if let Some(recommendation) = &analysis.recommendation {
+                    text.push_str(&format!("\n\n{}", renderer::render_recommendation(recommendation, report.currency)));
+                }
+                let delivery = output::Delivery { text: &text, symbol, report: Some(report), analysis: Some(&analysis), chart: None,
+                                                      candles };
+                outputs.send(&delivery).await?;
+            }
+            Err(e) => log::warn!("Analysis of {} failed: {}", symbol, e),
+        }
+    }
+    Ok(())
+}